@@ -1,4 +1,9 @@
-use std::{any::Any, future::Future, pin::Pin};
+use std::{
+    any::Any,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
 
 use super::{Actor, ActorExit, Handler, Message};
 use crate::{
@@ -42,6 +47,37 @@ where
     }
 }
 
+/// A message that its sender can take back until it is delivered.
+pub(crate) type Slot<M> = Arc<Mutex<Option<M>>>;
+
+/// A message left in a [`Slot`], and skipped if it was taken back.
+struct Deferred<M: Message>(Slot<M>);
+
+impl<A, M> Deliverable<A> for Deferred<M>
+where
+    A: Handler<M>,
+    M: Message,
+{
+    fn deliver_to<'a>(
+        self: Box<Self>,
+        actor: &'a A,
+        myself: &'a Mailbox<A>,
+        state: &'a mut A::State,
+    ) -> DeliveryFuture<'a, A::Error> {
+        let message = self.0.lock().unwrap().take();
+        Box::pin(async move {
+            match message {
+                Some(message) => Handler::<M>::handle(actor, myself, message, state).await,
+                None => Ok(()),
+            }
+        })
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any + Send> {
+        self
+    }
+}
+
 pub(crate) struct Delivering<A: Actor>(Box<dyn Deliverable<A> + Send>);
 
 impl<A: Actor> Delivering<A> {
@@ -53,6 +89,14 @@ impl<A: Actor> Delivering<A> {
         Self(Box::new(Envelope(message)))
     }
 
+    pub(crate) fn from_slot<M>(slot: Slot<M>) -> Self
+    where
+        A: Handler<M>,
+        M: Message,
+    {
+        Self(Box::new(Deferred(slot)))
+    }
+
     pub(crate) fn recover<M>(self) -> M
     where
         A: Handler<M>,
@@ -88,14 +132,25 @@ pub(crate) async fn run<A: Actor>(
             if let Some(supervision) = supervision {
                 supervision.started(&myself);
             }
+            let idle_timeout = actor.idle_timeout();
             loop {
-                match receiver.recv().await {
-                    MailboxEvent::Message(message) => {
-                        if let Err(error) = message.deliver_to(&actor, &myself, &mut state).await {
-                            break ActorExit::Failed(error);
-                        }
+                let event = match idle_timeout {
+                    Some(idle_timeout) => {
+                        let clock = myself.clock();
+                        clock.timeout(idle_timeout, receiver.recv()).await
+                    }
+                    None => Some(receiver.recv().await),
+                };
+                let result = match event {
+                    Some(MailboxEvent::Message(message)) => {
+                        let _handling = myself.handling();
+                        message.deliver_to(&actor, &myself, &mut state).await
                     }
-                    MailboxEvent::Stop => break ActorExit::Stopped,
+                    Some(MailboxEvent::Stop) => break myself.stopped(),
+                    None => actor.on_idle(&myself, &mut state).await,
+                };
+                if let Err(error) = transition(&actor, &myself, &mut state, result).await {
+                    break ActorExit::Failed(error);
                 }
             }
         }
@@ -105,6 +160,20 @@ pub(crate) async fn run<A: Actor>(
     finish(&actor, &myself, receiver, &mut state, exit).await
 }
 
+/// Runs [`Actor::on_transition`] after a successful handler that unstashed.
+pub(crate) async fn transition<A: Actor>(
+    actor: &A,
+    myself: &Mailbox<A>,
+    state: &mut A::State,
+    result: Result<(), A::Error>,
+) -> Result<(), A::Error> {
+    if result.is_ok() && myself.take_transition() {
+        actor.on_transition(myself, state).await
+    } else {
+        result
+    }
+}
+
 pub(crate) async fn finish<A: Actor>(
     actor: &A,
     myself: &Mailbox<A>,
@@ -8,13 +8,23 @@ use std::{
 
 use futures_channel::oneshot;
 
+use crate::mailbox::ActorId;
+
 /// The observed result of an actor task.
+///
+/// More ways for an actor to exit may be added, so matches on it need a
+/// wildcard arm.
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ActorExit<E: Send + 'static> {
     /// The actor stopped normally.
     Stopped,
     /// A lifecycle method failed.
     Failed(E),
+    /// The actor was stopped because this linked actor exited abnormally.
+    ///
+    /// See [`Mailbox::link`](crate::Mailbox::link).
+    Linked(ActorId),
 }
 
 /// The worker stopped before reporting an actor's exit.
@@ -3,11 +3,15 @@
 mod deliver;
 mod handle;
 
-pub(crate) use deliver::{Delivering, finish, run};
+#[cfg(feature = "testkit")]
+pub(crate) use deliver::transition;
+pub(crate) use deliver::{Delivering, Slot, finish, run};
 #[doc(inline)]
 pub use handle::{ActorExit, ActorHandle, ActorHandleError};
 
-use crate::Mailbox;
+use std::time::Duration;
+
+use crate::{Mailbox, mailbox::Priority};
 
 /// A message that can cross into an actor cluster.
 pub trait Message: Send + 'static {}
@@ -40,6 +44,39 @@ pub trait Actor: Sized + 'static {
         Ok(())
     }
 
+    /// How long the actor may wait for a message before [`on_idle`] runs.
+    ///
+    /// It's read once, after `post_start`. The default, `None`, never times
+    /// out.
+    ///
+    /// [`on_idle`]: Self::on_idle
+    fn idle_timeout(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Runs each time no message is received for [`idle_timeout`], such as
+    /// to stop an idle actor.
+    ///
+    /// [`idle_timeout`]: Self::idle_timeout
+    async fn on_idle(
+        &self,
+        _myself: &Mailbox<Self>,
+        _state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Runs after a handler called [`Mailbox::unstash_all`], before the
+    /// unstashed messages are handled, such as to set up the state the actor
+    /// moved to.
+    async fn on_transition(
+        &self,
+        _myself: &Mailbox<Self>,
+        _state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
     /// Runs after message processing ends but before the mailbox is dropped.
     async fn pre_stop(
         &self,
@@ -62,6 +99,11 @@ pub trait Actor: Sized + 'static {
 /// Handles messages of type `M` for an actor.
 #[allow(async_fn_in_trait)]
 pub trait Handler<M: Message>: Actor {
+    /// The priority of these messages when they are sent without one.
+    ///
+    /// See [`Mailbox::send_with_priority`] to override it for one message.
+    const PRIORITY: Priority = Priority::Normal;
+
     /// Handles one message at a time.
     async fn handle(
         &self,
@@ -0,0 +1,91 @@
+use std::{
+    any::type_name,
+    collections::BTreeMap,
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    Actor, Mailbox,
+    mailbox::{ActorId, ActorMetrics},
+};
+
+/// A snapshot of an actor running in a cluster.
+///
+/// See [`Cluster::actors`](super::Cluster::actors).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ActorInfo {
+    /// The actor's identifier.
+    pub id: ActorId,
+    /// The actor's registered name.
+    pub name: Option<String>,
+    /// The name of the actor's type.
+    pub type_name: &'static str,
+    /// The index of the dispatcher worker running the actor.
+    pub worker: Option<usize>,
+    /// The mailbox capacity.
+    pub capacity: NonZeroUsize,
+    /// What the actor has done so far.
+    pub metrics: ActorMetrics,
+}
+
+trait Introspect: Send + Sync {
+    fn info(&self) -> ActorInfo;
+}
+
+impl<A: Actor> Introspect for Mailbox<A> {
+    fn info(&self) -> ActorInfo {
+        ActorInfo {
+            id: self.id(),
+            name: self.name().map(str::to_owned),
+            type_name: type_name::<A>(),
+            worker: self.worker(),
+            capacity: self.capacity(),
+            metrics: self.metrics(),
+        }
+    }
+}
+
+type Actors = Arc<Mutex<BTreeMap<ActorId, Box<dyn Introspect>>>>;
+
+/// The actors running in a cluster, listed from startup to exit.
+#[derive(Default)]
+pub(super) struct Directory {
+    actors: Actors,
+}
+
+impl Directory {
+    pub(super) fn list<A: Actor>(&self, mailbox: &Mailbox<A>) -> Listing {
+        let id = mailbox.id();
+        self.actors
+            .lock()
+            .unwrap()
+            .insert(id, Box::new(mailbox.clone()));
+        Listing {
+            id,
+            actors: self.actors.clone(),
+        }
+    }
+
+    pub(super) fn snapshot(&self) -> Vec<ActorInfo> {
+        self.actors
+            .lock()
+            .unwrap()
+            .values()
+            .map(|actor| actor.info())
+            .collect()
+    }
+}
+
+/// Removes an actor from its cluster's directory when dropped.
+pub(super) struct Listing {
+    id: ActorId,
+    actors: Actors,
+}
+
+impl Drop for Listing {
+    fn drop(&mut self) {
+        let actor = self.actors.lock().unwrap().remove(&self.id);
+        drop(actor);
+    }
+}
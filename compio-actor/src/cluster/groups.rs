@@ -0,0 +1,26 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::Mutex,
+};
+
+use crate::{Message, mailbox::Name, process_group::ProcessGroup};
+
+/// The named process groups of a cluster, one per name and message type.
+#[derive(Default)]
+pub(super) struct Groups {
+    groups: Mutex<HashMap<(TypeId, Name), Box<dyn Any + Send + Sync>>>,
+}
+
+impl Groups {
+    pub(super) fn get<M: Message>(&self, name: Name) -> ProcessGroup<M> {
+        self.groups
+            .lock()
+            .unwrap()
+            .entry((TypeId::of::<M>(), name))
+            .or_insert_with(|| Box::new(ProcessGroup::<M>::new()))
+            .downcast_ref::<ProcessGroup<M>>()
+            .expect("process group registered under the wrong type")
+            .clone()
+    }
+}
@@ -1,6 +1,8 @@
 //! Actor cluster, registry, and spawn configuration.
 
 mod current;
+mod directory;
+mod groups;
 mod registry;
 mod spawn;
 
@@ -11,11 +13,15 @@ use std::{
 };
 
 use compio_dispatcher::Dispatcher;
+#[doc(inline)]
+pub use directory::ActorInfo;
+use directory::Directory;
+use groups::Groups;
 use registry::Registry;
 #[doc(inline)]
 pub use spawn::{Spawn, SpawnError, SpawnFuture, SpawnResult};
 
-use crate::{Actor, Mailbox};
+use crate::{Actor, Mailbox, Message, process_group::ProcessGroup};
 
 /// A set of Compio workers on which actors are placed.
 #[derive(Clone)]
@@ -26,6 +32,10 @@ pub struct Cluster {
 struct ClusterInner {
     dispatcher: Mutex<Option<Dispatcher>>,
     registry: Registry,
+    groups: Groups,
+    directory: Directory,
+    #[cfg(feature = "remote")]
+    exposed: crate::remote::Exposed,
 }
 
 impl Cluster {
@@ -40,6 +50,10 @@ impl Cluster {
             inner: Arc::new(ClusterInner {
                 dispatcher: Mutex::new(Some(dispatcher)),
                 registry: Registry::default(),
+                groups: Groups::default(),
+                directory: Directory::default(),
+                #[cfg(feature = "remote")]
+                exposed: Default::default(),
             }),
         }
     }
@@ -48,6 +62,7 @@ impl Cluster {
     ///
     /// The return type is a configurable future implementing [`IntoFuture`].
     /// See [`Spawn`] for detail.
+    #[track_caller]
     pub fn spawn<A, F>(&self, factory: F, arguments: A::Arguments) -> Spawn<'_, A, F>
     where
         A: Actor,
@@ -66,6 +81,32 @@ impl Cluster {
         self.inner.registry.get(&name)
     }
 
+    /// Returns the process group registered under `name` for messages of type
+    /// `M`, creating an empty one the first time.
+    ///
+    /// Groups of different message types don't share names, and a group
+    /// stays registered, even when empty, for as long as the cluster exists.
+    /// An actor usually joins from `post_start`, through [`Cluster::current`],
+    /// and keeps the membership in its state.
+    pub fn group<M, N>(&self, name: N) -> ProcessGroup<M>
+    where
+        M: Message,
+        N: Into<Cow<'static, str>>,
+    {
+        self.inner.groups.get(name.into().into())
+    }
+
+    /// Returns a snapshot of the actors running in the cluster, from the end
+    /// of their `pre_start` to their exit, in the order they were spawned.
+    pub fn actors(&self) -> Vec<ActorInfo> {
+        self.inner.directory.snapshot()
+    }
+
+    #[cfg(feature = "remote")]
+    pub(crate) fn exposed(&self) -> &crate::remote::Exposed {
+        &self.inner.exposed
+    }
+
     /// Stops the dispatcher workers and waits for their threads to exit.
     pub async fn join(self) -> io::Result<()> {
         let dispatcher = self.inner.dispatcher.lock().unwrap().take();
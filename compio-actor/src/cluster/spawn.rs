@@ -8,6 +8,8 @@ use std::{
     task::{Context, Poll, ready},
 };
 
+use compio_dispatcher::Dispatcher;
+use compio_runtime::{ResumeUnwind, SpawnMeta};
 use futures_channel::oneshot;
 use futures_util::FutureExt;
 
@@ -15,7 +17,7 @@ use super::Cluster;
 use crate::{
     Actor, Handler, Mailbox,
     actor::{ActorExit, ActorHandle, finish, run},
-    mailbox::{DEFAULT_MAILBOX_CAPACITY, Name, make_mailbox},
+    mailbox::{Clock, DEFAULT_MAILBOX_CAPACITY, ExitReason, Exiting, Name, make_mailbox},
     supervisor::{Supervision, SupervisionEvent},
 };
 
@@ -64,6 +66,9 @@ where
     name: Option<Cow<'static, str>>,
     capacity: NonZeroUsize,
     supervisor: Option<Supervision<A>>,
+    restarts: u64,
+    clock: Clock,
+    meta: SpawnMeta,
 }
 
 impl<'a, A, F> Spawn<'a, A, F>
@@ -71,6 +76,7 @@ where
     A: Actor,
     F: FnOnce() -> A + Send + 'static,
 {
+    #[track_caller]
     pub(super) fn new(cluster: &'a Cluster, factory: F, arguments: A::Arguments) -> Self {
         Self {
             cluster,
@@ -79,6 +85,9 @@ where
             name: None,
             capacity: DEFAULT_MAILBOX_CAPACITY,
             supervisor: None,
+            restarts: 0,
+            clock: Clock::System,
+            meta: SpawnMeta::capture(),
         }
     }
 
@@ -102,6 +111,22 @@ where
         self.supervisor = Some(Supervision::new(supervisor));
         self
     }
+
+    /// Makes the actor's timers and idle timeout wait for `clock` instead of
+    /// the runtime's time.
+    ///
+    /// A supervisor restarts the actor with the runtime's time.
+    #[cfg(feature = "testkit")]
+    pub fn with_clock(mut self, clock: &crate::testkit::TestClock) -> Self {
+        self.clock = Clock::Manual(clock.clone());
+        self
+    }
+
+    /// Reports the actor as restarted this many times in its metrics.
+    pub(crate) fn with_restarts(mut self, restarts: u64) -> Self {
+        self.restarts = restarts;
+        self
+    }
 }
 
 impl<A, F> IntoFuture for Spawn<'_, A, F>
@@ -113,13 +138,7 @@ where
     type Output = SpawnResult<A>;
 
     fn into_future(self) -> Self::IntoFuture {
-        self.cluster.start(
-            self.factory,
-            self.arguments,
-            self.name,
-            self.capacity,
-            self.supervisor,
-        )
+        self.cluster.start(self)
     }
 }
 
@@ -181,18 +200,22 @@ impl<A: Actor> Future for SpawnFuture<A> {
 impl<A: Actor> Unpin for SpawnFuture<A> {}
 
 impl Cluster {
-    fn start<A, F>(
-        &self,
-        factory: F,
-        arguments: A::Arguments,
-        name: Option<Cow<'static, str>>,
-        capacity: NonZeroUsize,
-        supervisor: Option<Supervision<A>>,
-    ) -> SpawnFuture<A>
+    fn start<A, F>(&self, spawn: Spawn<'_, A, F>) -> SpawnFuture<A>
     where
         A: Actor,
         F: FnOnce() -> A + Send + 'static,
     {
+        let Spawn {
+            factory,
+            arguments,
+            name,
+            capacity,
+            supervisor,
+            restarts,
+            clock,
+            meta,
+            ..
+        } = spawn;
         let (name, reg) = match name {
             Some(name) => {
                 let name = Name::from(name);
@@ -206,7 +229,8 @@ impl Cluster {
             None => (None, None),
         };
 
-        let (mailbox, receiver) = make_mailbox::<A>(name, capacity);
+        let (mailbox, receiver) = make_mailbox::<A>(name, capacity, clock);
+        mailbox.set_restarts(restarts);
         let actor_ref = mailbox.clone();
         let (started_tx, started_rx) = oneshot::channel();
         let cluster = self.clone();
@@ -216,45 +240,68 @@ impl Cluster {
                 return SpawnFuture::ready(Err(SpawnError::Unavailable));
             };
             dispatcher.dispatch(move || {
-                cluster.drive(async move {
-                    let mut reg = reg;
-                    let actor = factory();
-                    let mut state = match actor.pre_start(&actor_ref, arguments).await {
-                        Ok(state) => state,
-                        Err(error) => {
-                            reg.take();
-                            started_tx.send(Err(error)).ok();
-                            return Err(());
+                // The actor runs in a task of its own, which the console shows
+                // under the actor's type and the location of the spawn.
+                let meta = meta.named(std::any::type_name::<A>());
+                let task = compio_runtime::spawn_at(
+                    cluster.clone().drive(async move {
+                        if let Some(worker) = Dispatcher::current_worker() {
+                            actor_ref.set_worker(worker);
                         }
-                    };
-                    if let Some(registration) = &reg {
-                        registration.activate(&actor_ref);
-                    }
+                        let mut reg = reg;
+                        let exiting = Exiting::new(actor_ref.clone());
+                        let actor = factory();
+                        let mut state = match actor.pre_start(&actor_ref, arguments).await {
+                            Ok(state) => state,
+                            Err(error) => {
+                                reg.take();
+                                exiting.exit(ExitReason::Failed);
+                                started_tx.send(Err(error)).ok();
+                                return Err(());
+                            }
+                        };
+                        if let Some(registration) = &reg {
+                            registration.activate(&actor_ref);
+                        }
+                        let listing = cluster.inner.directory.list(&actor_ref);
 
-                    if started_tx.send(Ok(())).is_err() {
-                        let exit =
-                            finish(&actor, &actor_ref, receiver, &mut state, ActorExit::Stopped)
-                                .await;
-                        return Ok(exit);
-                    }
+                        if started_tx.send(Ok(())).is_err() {
+                            let exit = finish(
+                                &actor,
+                                &actor_ref,
+                                receiver,
+                                &mut state,
+                                ActorExit::Stopped,
+                            )
+                            .await;
+                            exiting.exit(ExitReason::from(&exit));
+                            return Ok(exit);
+                        }
 
-                    let exit = run(
-                        actor,
-                        actor_ref.clone(),
-                        receiver,
-                        state,
-                        supervisor.as_ref(),
-                    )
-                    .await;
-                    drop(reg);
-                    if let Some(supervisor) = supervisor {
-                        match &exit {
-                            ActorExit::Stopped => supervisor.terminated(&actor_ref),
-                            ActorExit::Failed(_) => supervisor.failed(&actor_ref),
+                        let exit = run(
+                            actor,
+                            actor_ref.clone(),
+                            receiver,
+                            state,
+                            supervisor.as_ref(),
+                        )
+                        .await;
+                        drop(listing);
+                        drop(reg);
+                        exiting.exit(ExitReason::from(&exit));
+                        if let Some(supervisor) = supervisor {
+                            match &exit {
+                                ActorExit::Stopped => supervisor.terminated(&actor_ref),
+                                ActorExit::Failed(_) | ActorExit::Linked(_) => {
+                                    supervisor.failed(&actor_ref)
+                                }
+                            }
                         }
-                    }
-                    Ok(exit)
-                })
+                        Ok(exit)
+                    }),
+                    meta,
+                );
+                async move { task.await.resume_unwind().unwrap_or(Err(())) }
             })
         };
         let result = match result {
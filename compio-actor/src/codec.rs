@@ -0,0 +1,40 @@
+use std::{fmt, io};
+
+use compio_buf::IoBufExt;
+use compio_io::framed::codec::{Decoder, Encoder};
+
+/// Encodes and decodes the messages sent to a remote actor or the events kept
+/// in a journal.
+///
+/// It is implemented for every compio-io codec that encodes `&M` and decodes
+/// `M`, such as [`SerdeJsonCodec`] for serde types.
+///
+/// [`SerdeJsonCodec`]: https://docs.rs/compio-io/latest/compio_io/framed/codec/serde_json/struct.SerdeJsonCodec.html
+pub trait Codec<M>: Clone + Send + Sync + 'static {
+    /// Encodes a message into a payload.
+    fn encode(&self, message: &M) -> io::Result<Vec<u8>>;
+
+    /// Decodes a message from a payload.
+    fn decode(&self, payload: Vec<u8>) -> io::Result<M>;
+}
+
+impl<M, C> Codec<M> for C
+where
+    C: for<'a> Encoder<&'a M, Vec<u8>> + Decoder<M, Vec<u8>> + Clone + Send + Sync + 'static,
+    for<'a> <C as Encoder<&'a M, Vec<u8>>>::Error: fmt::Display,
+    <C as Decoder<M, Vec<u8>>>::Error: fmt::Display,
+{
+    fn encode(&self, message: &M) -> io::Result<Vec<u8>> {
+        let mut payload = Vec::new();
+        Encoder::encode(&mut self.clone(), message, &mut payload).map_err(invalid_data)?;
+        Ok(payload)
+    }
+
+    fn decode(&self, payload: Vec<u8>) -> io::Result<M> {
+        Decoder::decode(&mut self.clone(), &payload.slice(..)).map_err(invalid_data)
+    }
+}
+
+pub(crate) fn invalid_data(error: impl fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
@@ -4,9 +4,17 @@
 
 pub mod actor;
 pub mod cluster;
+#[cfg(any(feature = "remote", feature = "persistence"))]
+mod codec;
 pub mod mailbox;
+#[cfg(feature = "persistence")]
+pub mod persistence;
 pub mod process_group;
+#[cfg(feature = "remote")]
+pub mod remote;
 pub mod supervisor;
+#[cfg(feature = "testkit")]
+pub mod testkit;
 
 #[doc(inline)]
 pub use actor::{Actor, ActorExit, ActorHandle, Handler, Message};
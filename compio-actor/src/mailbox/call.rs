@@ -1,9 +1,13 @@
-use std::fmt;
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
 
+use compio_runtime::time::timeout_at;
 use futures_channel::oneshot;
 
 use super::{Broker, CallError, DeliverError, Mailbox};
-use crate::{Actor, Handler, Message};
+use crate::{Actor, Handler, Message, actor::Slot};
 
 impl<A: Actor> Mailbox<A> {
     /// Sends a request and waits for the actor's reply.
@@ -13,7 +17,25 @@ impl<A: Actor> Mailbox<A> {
         M: Message,
         R: Message,
     {
-        call_with(message, |call| self.inner.send(call)).await
+        call_with(message, |call| self.send(call)).await
+    }
+
+    /// Sends a request and waits at most `timeout` for the actor's reply.
+    ///
+    /// The handler sees the resulting deadline through [`Call::deadline`]. If
+    /// the call times out before the actor started handling it, the request is
+    /// taken back, returned in [`CallError::Timeout`], and never handled.
+    ///
+    /// # Panics
+    ///
+    /// Panics when called outside a Compio runtime.
+    pub async fn call_timeout<M, R>(&self, message: M, timeout: Duration) -> Result<R, CallError<M>>
+    where
+        A: Handler<Call<M, R>>,
+        M: Message,
+        R: Message,
+    {
+        call_timeout_with(message, timeout, |call| self.inner.send_deferred(call)).await
     }
 }
 
@@ -22,19 +44,28 @@ impl<M: Message, R: Message> Broker<Call<M, R>> {
     pub async fn call(&self, message: M) -> Result<R, CallError<M>> {
         call_with(message, |call| self.send(call)).await
     }
+
+    /// Sends a request and waits at most `timeout` for the actor's reply.
+    ///
+    /// See [`Mailbox::call_timeout`].
+    pub async fn call_timeout(&self, message: M, timeout: Duration) -> Result<R, CallError<M>> {
+        call_timeout_with(message, timeout, |call| self.send_deferred(call)).await
+    }
 }
 
 /// A request together with the channel used to answer it.
 pub struct Call<M: Message, R: Message> {
     message: M,
     reply: Reply<R>,
+    deadline: Option<Instant>,
 }
 
 impl<M: Message, R: Message> Call<M, R> {
-    fn new(message: M, sender: oneshot::Sender<R>) -> Self {
+    pub(crate) fn new(message: M, sender: oneshot::Sender<R>, deadline: Option<Instant>) -> Self {
         Self {
             message,
             reply: Reply(sender),
+            deadline,
         }
     }
 
@@ -43,6 +74,14 @@ impl<M: Message, R: Message> Call<M, R> {
         &self.message
     }
 
+    /// Returns when the caller stops waiting for the reply, if it does.
+    ///
+    /// A handler can skip the work of a call whose deadline has passed, as
+    /// its reply would be dropped anyway.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
     /// Answers the call, returning the response if the caller stopped waiting.
     pub fn reply(self, response: R) -> Result<(), R> {
         self.reply.reply(response)
@@ -62,6 +101,7 @@ impl<M: Message + fmt::Debug, R: Message> fmt::Debug for Call<M, R> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Call")
             .field("message", &self.message)
+            .field("deadline", &self.deadline)
             .finish_non_exhaustive()
     }
 }
@@ -91,6 +131,29 @@ where
     R: Message,
 {
     let (sender, receiver) = oneshot::channel();
-    send(Call::new(message, sender)).map_err(CallError::from_deliver)?;
+    send(Call::new(message, sender, None)).map_err(CallError::from_deliver)?;
     receiver.await.map_err(|_| CallError::NoReply)
 }
+
+pub(crate) async fn call_timeout_with<M, R>(
+    message: M,
+    timeout: Duration,
+    send: impl FnOnce(Call<M, R>) -> Result<Slot<Call<M, R>>, DeliverError<Call<M, R>>>,
+) -> Result<R, CallError<M>>
+where
+    M: Message,
+    R: Message,
+{
+    let Some(deadline) = Instant::now().checked_add(timeout) else {
+        return call_with(message, |call| send(call).map(drop)).await;
+    };
+    let (sender, receiver) = oneshot::channel();
+    let slot = send(Call::new(message, sender, Some(deadline))).map_err(CallError::from_deliver)?;
+    match timeout_at(deadline, receiver).await {
+        Ok(reply) => reply.map_err(|_| CallError::NoReply),
+        Err(_) => {
+            let call = slot.lock().unwrap().take();
+            Err(CallError::Timeout(call.map(Call::into_message)))
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use compio_runtime::time::{sleep_until, timeout};
+
+use super::Mailbox;
+use crate::Actor;
+#[cfg(feature = "testkit")]
+use crate::testkit::TestClock;
+
+/// The time an actor's timers and idle timeout wait for.
+#[derive(Clone, Default)]
+pub(crate) enum Clock {
+    /// The runtime's timers.
+    #[default]
+    System,
+    /// A clock the test advances.
+    #[cfg(feature = "testkit")]
+    Manual(TestClock),
+}
+
+impl Clock {
+    pub(crate) fn now(&self) -> Instant {
+        match self {
+            Self::System => Instant::now(),
+            #[cfg(feature = "testkit")]
+            Self::Manual(clock) => clock.now(),
+        }
+    }
+
+    pub(crate) async fn sleep_until(&self, deadline: Instant) {
+        match self {
+            Self::System => sleep_until(deadline).await,
+            #[cfg(feature = "testkit")]
+            Self::Manual(clock) => clock.sleep_until(deadline).await,
+        }
+    }
+
+    /// Runs `future` for at most `duration`, or returns `None`.
+    pub(crate) async fn timeout<F: Future>(
+        &self,
+        duration: Duration,
+        future: F,
+    ) -> Option<F::Output> {
+        match self {
+            Self::System => timeout(duration, future).await.ok(),
+            #[cfg(feature = "testkit")]
+            Self::Manual(clock) => clock.timeout(duration, future).await,
+        }
+    }
+}
+
+impl<A: Actor> Mailbox<A> {
+    pub(crate) fn clock(&self) -> &Clock {
+        &self.inner.clock
+    }
+}
@@ -41,6 +41,9 @@ pub enum CallError<M: Message> {
     Closed(M),
     /// The actor handled the request without replying.
     NoReply,
+    /// No reply arrived in time. The request is recovered if the actor had not
+    /// started handling it.
+    Timeout(Option<M>),
 }
 
 impl<M: Message> CallError<M> {
@@ -56,6 +59,7 @@ impl<M: Message> CallError<M> {
         match self {
             Self::Full(message) | Self::Closed(message) => Some(message),
             Self::NoReply => None,
+            Self::Timeout(message) => message,
         }
     }
 }
@@ -66,6 +70,7 @@ impl<M: Message> fmt::Display for CallError<M> {
             Self::Full(_) => f.write_str("actor mailbox is full"),
             Self::Closed(_) => f.write_str("actor mailbox is closed"),
             Self::NoReply => f.write_str("actor did not reply"),
+            Self::Timeout(_) => f.write_str("actor call timed out"),
         }
     }
 }
@@ -0,0 +1,259 @@
+use std::{
+    mem,
+    sync::{
+        Arc, Weak,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use super::{Mailbox, MailboxInner};
+use crate::{Actor, ActorExit, Broker, Handler};
+
+/// A unique identifier of an actor, for as long as the process runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ActorId(u64);
+
+impl ActorId {
+    pub(super) fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// How an actor exited, without its error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExitReason {
+    /// The actor stopped normally.
+    Stopped,
+    /// The actor failed with an error, or its worker stopped.
+    Failed,
+    /// The actor was stopped because this linked actor exited abnormally.
+    Linked(ActorId),
+}
+
+impl ExitReason {
+    /// Returns whether the exit was abnormal, which is anything but
+    /// [`Stopped`](Self::Stopped).
+    pub fn is_abnormal(&self) -> bool {
+        !matches!(self, Self::Stopped)
+    }
+}
+
+impl<E: Send + 'static> From<&ActorExit<E>> for ExitReason {
+    fn from(exit: &ActorExit<E>) -> Self {
+        match exit {
+            ActorExit::Stopped => Self::Stopped,
+            ActorExit::Failed(_) => Self::Failed,
+            ActorExit::Linked(id) => Self::Linked(*id),
+        }
+    }
+}
+
+/// Sent to an actor monitoring another one when it exits.
+///
+/// See [`Mailbox::monitor`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Down {
+    /// The actor that exited.
+    pub id: ActorId,
+    /// How it exited.
+    pub exit: ExitReason,
+}
+
+/// Sent to an actor trapping exits when a linked actor exits.
+///
+/// See [`Mailbox::trap_exits`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Exited {
+    /// The linked actor that exited.
+    pub id: ActorId,
+    /// How it exited.
+    pub exit: ExitReason,
+}
+
+#[derive(Default)]
+pub(super) struct Links {
+    exited: Option<ExitReason>,
+    monitors: Vec<(ActorId, Broker<Down>)>,
+    links: Vec<(ActorId, Weak<dyn Linked>)>,
+    trap: Option<Broker<Exited>>,
+    killed_by: Option<ActorId>,
+}
+
+/// The side of a mailbox that linked actors notify when they exit.
+trait Linked: Send + Sync {
+    fn linked_exit(&self, from: ActorId, exit: ExitReason);
+}
+
+impl<A: Actor> Linked for MailboxInner<A> {
+    fn linked_exit(&self, from: ActorId, exit: ExitReason) {
+        let mut links = self.links.lock().unwrap();
+        if links.exited.is_some() {
+            return;
+        }
+        links.links.retain(|(id, _)| *id != from);
+        if let Some(trap) = &links.trap {
+            trap.send_control(Exited { id: from, exit }).ok();
+        } else if exit.is_abnormal() {
+            links.killed_by.get_or_insert(from);
+            drop(links);
+            self.stop();
+        }
+    }
+}
+
+impl<A: Actor> MailboxInner<A> {
+    /// Adds a link to `other`, or returns how it exited.
+    fn add_link(&self, id: ActorId, other: Weak<dyn Linked>) -> Result<(), ExitReason> {
+        let mut links = self.links.lock().unwrap();
+        if let Some(exit) = links.exited {
+            return Err(exit);
+        }
+        if !links.links.iter().any(|(linked, _)| *linked == id) {
+            links.links.push((id, other));
+        }
+        Ok(())
+    }
+
+    fn remove_link(&self, id: ActorId) {
+        self.links
+            .lock()
+            .unwrap()
+            .links
+            .retain(|(linked, _)| *linked != id);
+    }
+}
+
+impl<A: Actor> Mailbox<A> {
+    /// Returns the actor's unique identifier.
+    pub fn id(&self) -> ActorId {
+        self.inner.id
+    }
+
+    /// Sends [`Down`] to this actor when `other` exits.
+    ///
+    /// If `other` has already exited, [`Down`] is sent right away. Like
+    /// supervision events, it is sent on the control lane, ahead of queued
+    /// messages and regardless of the capacity, and dropped only if this
+    /// actor's mailbox is closed.
+    pub fn monitor<B: Actor>(&self, other: &Mailbox<B>)
+    where
+        A: Handler<Down>,
+    {
+        let mut links = other.inner.links.lock().unwrap();
+        match links.exited {
+            Some(exit) => {
+                drop(links);
+                self.send_control(Down {
+                    id: other.id(),
+                    exit,
+                })
+                .ok();
+            }
+            None => links.monitors.push((self.id(), self.broker())),
+        }
+    }
+
+    /// Stops monitoring `other`.
+    pub fn demonitor<B: Actor>(&self, other: &Mailbox<B>) {
+        let id = self.id();
+        other
+            .inner
+            .links
+            .lock()
+            .unwrap()
+            .monitors
+            .retain(|(watcher, _)| *watcher != id);
+    }
+
+    /// Links this actor and `other`, so that each learns about the exit of
+    /// the other.
+    ///
+    /// When one of them exits abnormally, the other is stopped with
+    /// [`ActorExit::Linked`], unless it [traps exits](Self::trap_exits). If
+    /// `other` has already exited, this actor is notified right away.
+    pub fn link<B: Actor>(&self, other: &Mailbox<B>) {
+        let this: Weak<dyn Linked> = Arc::downgrade(&self.inner) as _;
+        let that: Weak<dyn Linked> = Arc::downgrade(&other.inner) as _;
+        if let Err(exit) = self.inner.add_link(other.id(), that) {
+            other.inner.linked_exit(self.id(), exit);
+            return;
+        }
+        if let Err(exit) = other.inner.add_link(self.id(), this) {
+            self.inner.linked_exit(other.id(), exit);
+        }
+    }
+
+    /// Removes the link between this actor and `other`.
+    pub fn unlink<B: Actor>(&self, other: &Mailbox<B>) {
+        self.inner.remove_link(other.id());
+        other.inner.remove_link(self.id());
+    }
+
+    /// Sets whether the exits of linked actors are delivered to this actor as
+    /// [`Exited`] messages, instead of stopping it when they are abnormal.
+    pub fn trap_exits(&self, trap: bool)
+    where
+        A: Handler<Exited>,
+    {
+        let mut links = self.inner.links.lock().unwrap();
+        if links.exited.is_none() {
+            links.trap = trap.then(|| self.broker());
+        }
+    }
+
+    /// Returns how the actor exits after receiving a stop request.
+    pub(crate) fn stopped<E: Send + 'static>(&self) -> ActorExit<E> {
+        match self.inner.links.lock().unwrap().killed_by {
+            Some(id) => ActorExit::Linked(id),
+            None => ActorExit::Stopped,
+        }
+    }
+
+    /// Notifies monitors and linked actors that this actor exited.
+    pub(crate) fn exited(&self, exit: ExitReason) {
+        let (monitors, links) = {
+            let mut links = self.inner.links.lock().unwrap();
+            if links.exited.is_some() {
+                return;
+            }
+            links.exited = Some(exit);
+            links.trap = None;
+            (mem::take(&mut links.monitors), mem::take(&mut links.links))
+        };
+        let id = self.id();
+        for (_, down) in monitors {
+            down.send_control(Down { id, exit }).ok();
+        }
+        for (_, linked) in links {
+            if let Some(linked) = linked.upgrade() {
+                linked.linked_exit(id, exit);
+            }
+        }
+    }
+}
+
+/// Reports an actor's exit to its monitors and links, as a failure if it is
+/// dropped first.
+pub(crate) struct Exiting<A: Actor>(Option<Mailbox<A>>);
+
+impl<A: Actor> Exiting<A> {
+    pub(crate) fn new(mailbox: Mailbox<A>) -> Self {
+        Self(Some(mailbox))
+    }
+
+    pub(crate) fn exit(mut self, exit: ExitReason) {
+        if let Some(mailbox) = self.0.take() {
+            mailbox.exited(exit);
+        }
+    }
+}
+
+impl<A: Actor> Drop for Exiting<A> {
+    fn drop(&mut self) {
+        if let Some(mailbox) = self.0.take() {
+            mailbox.exited(ExitReason::Failed);
+        }
+    }
+}
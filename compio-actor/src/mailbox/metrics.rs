@@ -0,0 +1,147 @@
+use std::{
+    sync::{
+        OnceLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use super::Mailbox;
+use crate::Actor;
+
+/// A snapshot of what an actor has done since it was spawned.
+///
+/// See [`Mailbox::metrics`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ActorMetrics {
+    /// The number of messages waiting in the mailbox.
+    pub queued: usize,
+    /// The number of stashed messages.
+    pub stashed: usize,
+    /// The number of messages handled, including those that failed.
+    pub processed: u64,
+    /// The total time spent in handlers.
+    pub busy: Duration,
+    /// The time taken by the slowest handler.
+    pub slowest: Duration,
+    /// How long the current handler has been running, if one is.
+    ///
+    /// A large value points at an actor stuck in a handler.
+    pub handling: Option<Duration>,
+    /// How many times a supervisor restarted this actor, each restart being
+    /// a new actor with fresh metrics.
+    pub restarts: u64,
+    /// The time since the actor was spawned.
+    pub uptime: Duration,
+}
+
+impl ActorMetrics {
+    /// Returns the mean time taken by a handler.
+    pub fn mean_handler_time(&self) -> Duration {
+        match self.processed {
+            0 => Duration::ZERO,
+            processed => Duration::from_nanos(nanos(self.busy) / processed),
+        }
+    }
+}
+
+/// Counters shared by an actor and the observers of its mailbox.
+pub(super) struct Metrics {
+    spawned: Instant,
+    worker: OnceLock<usize>,
+    restarts: AtomicU64,
+    processed: AtomicU64,
+    /// Nanoseconds spent in handlers, and taken by the slowest one.
+    busy: AtomicU64,
+    slowest: AtomicU64,
+    /// Nanoseconds from `spawned` to the start of the current handler, plus
+    /// one, or zero between handlers.
+    handling: AtomicU64,
+}
+
+impl Metrics {
+    pub(super) fn new() -> Self {
+        Self {
+            spawned: Instant::now(),
+            worker: OnceLock::new(),
+            restarts: AtomicU64::new(0),
+            processed: AtomicU64::new(0),
+            busy: AtomicU64::new(0),
+            slowest: AtomicU64::new(0),
+            handling: AtomicU64::new(0),
+        }
+    }
+}
+
+/// Records a handler as running until dropped.
+pub(crate) struct Handling<'a> {
+    metrics: &'a Metrics,
+    started: Instant,
+}
+
+impl Drop for Handling<'_> {
+    fn drop(&mut self) {
+        let elapsed = nanos(self.started.elapsed());
+        let metrics = self.metrics;
+        metrics.handling.store(0, Ordering::Release);
+        metrics.processed.fetch_add(1, Ordering::Relaxed);
+        metrics.busy.fetch_add(elapsed, Ordering::Relaxed);
+        metrics.slowest.fetch_max(elapsed, Ordering::Relaxed);
+    }
+}
+
+fn nanos(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
+
+impl<A: Actor> Mailbox<A> {
+    /// Returns a snapshot of the actor's metrics.
+    pub fn metrics(&self) -> ActorMetrics {
+        let metrics = &self.inner.metrics;
+        let now = Instant::now();
+        let handling = match metrics.handling.load(Ordering::Acquire) {
+            0 => None,
+            since => Some(
+                now.saturating_duration_since(metrics.spawned + Duration::from_nanos(since - 1)),
+            ),
+        };
+        ActorMetrics {
+            queued: self.queued(),
+            stashed: self.stashed(),
+            processed: metrics.processed.load(Ordering::Relaxed),
+            busy: Duration::from_nanos(metrics.busy.load(Ordering::Relaxed)),
+            slowest: Duration::from_nanos(metrics.slowest.load(Ordering::Relaxed)),
+            handling,
+            restarts: metrics.restarts.load(Ordering::Relaxed),
+            uptime: now.saturating_duration_since(metrics.spawned),
+        }
+    }
+
+    /// Returns the index of the dispatcher worker running the actor, once it
+    /// has started on one.
+    pub fn worker(&self) -> Option<usize> {
+        self.inner.metrics.worker.get().copied()
+    }
+
+    pub(crate) fn set_worker(&self, worker: usize) {
+        self.inner.metrics.worker.set(worker).ok();
+    }
+
+    pub(crate) fn set_restarts(&self, restarts: u64) {
+        self.inner
+            .metrics
+            .restarts
+            .store(restarts, Ordering::Relaxed);
+    }
+
+    /// Records a handler as running until the returned guard is dropped.
+    pub(crate) fn handling(&self) -> Handling<'_> {
+        let metrics = &self.inner.metrics;
+        let started = Instant::now();
+        let since = nanos(started.saturating_duration_since(metrics.spawned));
+        metrics
+            .handling
+            .store(since.saturating_add(1), Ordering::Release);
+        Handling { metrics, started }
+    }
+}
@@ -1,44 +1,95 @@
 //! Typed mailboxes, calls, brokers, and delivery errors.
 
 mod call;
+mod clock;
 mod error;
+mod link;
+mod metrics;
 mod name;
+mod priority;
 mod receiver;
+mod stash;
+mod timer;
 
 use std::{
     any::Any,
     fmt,
     num::NonZeroUsize,
     sync::{
-        Arc,
-        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
     },
 };
 
-pub(crate) use call::call_with;
 #[doc(inline)]
 pub use call::{Call, Reply};
+pub(crate) use call::{call_timeout_with, call_with};
+pub(crate) use clock::Clock;
+use compio_runtime::sync::Notify;
 #[doc(inline)]
 pub use error::{CallError, DeliverError};
-use flume::{Sender, TrySendError};
+use flume::{SendError, Sender};
+pub(crate) use link::Exiting;
+use link::Links;
+#[doc(inline)]
+pub use link::{ActorId, Down, ExitReason, Exited};
+#[doc(inline)]
+pub use metrics::ActorMetrics;
+use metrics::Metrics;
 pub(crate) use name::Name;
+#[doc(inline)]
+pub use priority::Priority;
 pub(crate) use receiver::{MailboxEvent, Receiver, make_mailbox};
+use stash::Stash;
+#[doc(inline)]
+pub use timer::TimerHandle;
 
-use crate::{Actor, Handler, Message, actor::Delivering};
+use crate::{
+    Actor, Handler, Message,
+    actor::{Delivering, Slot},
+};
 
 /// Default number of messages reserved for each mailbox.
 pub const DEFAULT_MAILBOX_CAPACITY: NonZeroUsize = NonZeroUsize::new(64).unwrap();
 
 struct MailboxInner<A: Actor> {
+    id: ActorId,
     name: Option<Name>,
-    messages: Sender<Delivering<A>>,
+    /// System messages, handled before the others and exempt from capacity.
+    control: Sender<Delivering<A>>,
+    /// One lane per [`Priority`], from the highest.
+    lanes: [Sender<Delivering<A>>; Priority::COUNT],
+    /// The number of messages waiting in the lanes.
+    queued: Arc<AtomicUsize>,
+    stash: Arc<Mutex<Stash<A>>>,
     stop: Sender<()>,
     stopping: AtomicBool,
+    /// Notified when the mailbox starts rejecting messages.
+    closed: Notify,
     capacity: NonZeroUsize,
+    links: Mutex<Links>,
+    metrics: Metrics,
+    /// The time the actor's timers and idle timeout wait for.
+    clock: Clock,
+}
+
+/// Why a mailbox rejected a message.
+enum Rejected {
+    Full,
+    Closed,
+}
+
+impl Rejected {
+    fn with<M: Message>(self, message: M) -> DeliverError<M> {
+        match self {
+            Self::Full => DeliverError::Full(message),
+            Self::Closed => DeliverError::Closed(message),
+        }
+    }
 }
 
 impl<A: Actor> MailboxInner<A> {
-    fn send<M>(&self, message: M) -> Result<(), DeliverError<M>>
+    fn send<M>(&self, message: M, priority: Priority) -> Result<(), DeliverError<M>>
     where
         A: Handler<M>,
         M: Message,
@@ -47,11 +98,70 @@ impl<A: Actor> MailboxInner<A> {
             return Err(DeliverError::Closed(message));
         }
 
-        self.messages
-            .try_send(Delivering::<A>::from_msg(message))
-            .map_err(|error| match error {
-                TrySendError::Full(message) => DeliverError::Full(message.recover::<M>()),
-                TrySendError::Disconnected(message) => DeliverError::Closed(message.recover::<M>()),
+        self.enqueue(Delivering::<A>::from_msg(message), priority)
+            .map_err(|(message, rejected)| rejected.with(message.recover::<M>()))
+    }
+
+    /// Enqueues a message in a [`Slot`], from which the sender can take it
+    /// back until it is delivered.
+    fn send_deferred<M>(&self, message: M) -> Result<Slot<M>, DeliverError<M>>
+    where
+        A: Handler<M>,
+        M: Message,
+    {
+        if self.is_closed() {
+            return Err(DeliverError::Closed(message));
+        }
+
+        let slot = Arc::new(Mutex::new(Some(message)));
+        let rejected = match self.enqueue(Delivering::<A>::from_slot(slot.clone()), A::PRIORITY) {
+            Ok(()) => return Ok(slot),
+            Err((_, rejected)) => rejected,
+        };
+        let message = slot
+            .lock()
+            .unwrap()
+            .take()
+            .expect("rejected message was taken from its slot");
+        Err(rejected.with(message))
+    }
+
+    /// Enqueues a message on the control lane.
+    fn send_control<M>(&self, message: M) -> Result<(), DeliverError<M>>
+    where
+        A: Handler<M>,
+        M: Message,
+    {
+        if self.is_closed() {
+            return Err(DeliverError::Closed(message));
+        }
+
+        self.control
+            .send(Delivering::<A>::from_msg(message))
+            .map_err(|SendError(message)| DeliverError::Closed(message.recover::<M>()))
+    }
+
+    /// Reserves room for a message, then enqueues it on its priority's lane.
+    fn enqueue(
+        &self,
+        message: Delivering<A>,
+        priority: Priority,
+    ) -> Result<(), (Delivering<A>, Rejected)> {
+        let capacity = self.capacity.get();
+        if self
+            .queued
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |queued| {
+                (queued < capacity).then_some(queued + 1)
+            })
+            .is_err()
+        {
+            return Err((message, Rejected::Full));
+        }
+        self.lanes[priority.lane()]
+            .send(message)
+            .map_err(|SendError(message)| {
+                self.queued.fetch_sub(1, Ordering::AcqRel);
+                (message, Rejected::Closed)
             })
     }
 
@@ -59,18 +169,25 @@ impl<A: Actor> MailboxInner<A> {
         if self.stopping.swap(true, Ordering::AcqRel) {
             return false;
         }
+        self.closed.notify_waiters();
         self.stop.try_send(()).is_ok()
     }
 
     fn begin_stop(&self) {
-        self.stopping.store(true, Ordering::Release);
+        if !self.stopping.swap(true, Ordering::AcqRel) {
+            self.closed.notify_waiters();
+        }
     }
 
     fn is_closed(&self) -> bool {
         self.stopping.load(Ordering::Acquire)
-            || self.messages.is_disconnected()
+            || self.control.is_disconnected()
             || self.stop.is_disconnected()
     }
+
+    fn queued(&self) -> usize {
+        self.queued.load(Ordering::Acquire)
+    }
 }
 
 /// A typed reference to an actor in a cluster.
@@ -89,12 +206,39 @@ impl<A: Actor> Mailbox<A> {
     }
 
     /// Enqueues a message handled by this actor without waiting.
+    ///
+    /// The message is queued with the priority declared by
+    /// [`Handler::PRIORITY`].
     pub fn send<M>(&self, message: M) -> Result<(), DeliverError<M>>
     where
         A: Handler<M>,
         M: Message,
     {
-        self.inner.send(message)
+        self.inner.send(message, A::PRIORITY)
+    }
+
+    /// Enqueues a message with the given priority, instead of the one
+    /// declared by its handler.
+    pub fn send_with_priority<M>(
+        &self,
+        message: M,
+        priority: Priority,
+    ) -> Result<(), DeliverError<M>>
+    where
+        A: Handler<M>,
+        M: Message,
+    {
+        self.inner.send(message, priority)
+    }
+
+    /// Enqueues a message on the control lane, which is handled before the
+    /// other messages and is not limited by the capacity.
+    pub(crate) fn send_control<M>(&self, message: M) -> Result<(), DeliverError<M>>
+    where
+        A: Handler<M>,
+        M: Message,
+    {
+        self.inner.send_control(message)
     }
 
     /// Creates a send-only capability for one message type.
@@ -121,6 +265,12 @@ impl<A: Actor> Mailbox<A> {
     pub fn capacity(&self) -> NonZeroUsize {
         self.inner.capacity
     }
+
+    /// Returns the number of messages waiting in the mailbox, which counts
+    /// against its capacity.
+    pub fn queued(&self) -> usize {
+        self.inner.queued()
+    }
 }
 
 impl<A: Actor> Clone for Mailbox<A> {
@@ -134,17 +284,22 @@ impl<A: Actor> Clone for Mailbox<A> {
 impl<A: Actor> fmt::Debug for Mailbox<A> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Mailbox")
+            .field("id", &self.id())
             .field("name", &self.name())
             .field("capacity", &self.inner.capacity)
-            .field("queued", &self.inner.messages.len())
+            .field("queued", &self.queued())
             .field("closed", &self.is_closed())
             .finish()
     }
 }
 
 trait BrokerSink<M: Message>: Send + Sync {
+    fn id(&self) -> ActorId;
     fn name(&self) -> Option<&str>;
-    fn send(&self, message: M) -> Result<(), DeliverError<M>>;
+    fn queued(&self) -> usize;
+    fn send(&self, message: M, priority: Option<Priority>) -> Result<(), DeliverError<M>>;
+    fn send_control(&self, message: M) -> Result<(), DeliverError<M>>;
+    fn send_deferred(&self, message: M) -> Result<Slot<M>, DeliverError<M>>;
 }
 
 impl<A, M> BrokerSink<M> for MailboxInner<A>
@@ -152,12 +307,28 @@ where
     A: Handler<M>,
     M: Message,
 {
+    fn id(&self) -> ActorId {
+        self.id
+    }
+
     fn name(&self) -> Option<&str> {
         self.name.as_ref().map(Name::as_str)
     }
 
-    fn send(&self, message: M) -> Result<(), DeliverError<M>> {
-        MailboxInner::send(self, message)
+    fn queued(&self) -> usize {
+        MailboxInner::queued(self)
+    }
+
+    fn send(&self, message: M, priority: Option<Priority>) -> Result<(), DeliverError<M>> {
+        MailboxInner::send(self, message, priority.unwrap_or(A::PRIORITY))
+    }
+
+    fn send_control(&self, message: M) -> Result<(), DeliverError<M>> {
+        MailboxInner::send_control(self, message)
+    }
+
+    fn send_deferred(&self, message: M) -> Result<Slot<M>, DeliverError<M>> {
+        MailboxInner::send_deferred(self, message)
     }
 }
 
@@ -167,14 +338,44 @@ pub struct Broker<M: Message> {
 }
 
 impl<M: Message> Broker<M> {
+    /// Returns the actor's unique identifier.
+    pub fn id(&self) -> ActorId {
+        self.inner.id()
+    }
+
     /// Returns the actor's registered name.
     pub fn name(&self) -> Option<&str> {
         self.inner.name()
     }
 
-    /// Enqueues a message without waiting.
+    /// Enqueues a message without waiting, with the priority declared by its
+    /// handler.
     pub fn send(&self, message: M) -> Result<(), DeliverError<M>> {
-        self.inner.send(message)
+        self.inner.send(message, None)
+    }
+
+    /// Enqueues a message with the given priority, instead of the one
+    /// declared by its handler.
+    pub fn send_with_priority(
+        &self,
+        message: M,
+        priority: Priority,
+    ) -> Result<(), DeliverError<M>> {
+        self.inner.send(message, Some(priority))
+    }
+
+    /// Enqueues a message on the actor's control lane.
+    pub(crate) fn send_control(&self, message: M) -> Result<(), DeliverError<M>> {
+        self.inner.send_control(message)
+    }
+
+    /// Returns the number of messages waiting in the actor's mailbox.
+    pub fn queued(&self) -> usize {
+        self.inner.queued()
+    }
+
+    pub(crate) fn send_deferred(&self, message: M) -> Result<Slot<M>, DeliverError<M>> {
+        self.inner.send_deferred(message)
     }
 }
 
@@ -189,6 +390,7 @@ impl<M: Message> Clone for Broker<M> {
 impl<M: Message> fmt::Debug for Broker<M> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Broker")
+            .field("id", &self.id())
             .field("name", &self.name())
             .finish_non_exhaustive()
     }
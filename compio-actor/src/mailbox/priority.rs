@@ -0,0 +1,32 @@
+/// How urgently a message is handled, relative to the others waiting in a
+/// mailbox.
+///
+/// Messages of a higher priority are handled before any queued message of a
+/// lower one, and messages of the same priority in the order they were sent.
+/// All priorities share the mailbox capacity.
+///
+/// As with the priority of executor tasks, the variants are declared from the
+/// highest, so a higher priority compares as less than a lower one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Handled before any other message.
+    High,
+    /// The priority of messages, unless their [`Handler`] says otherwise.
+    ///
+    /// [`Handler`]: crate::Handler::PRIORITY
+    #[default]
+    Normal,
+    /// Handled once no other message is waiting.
+    Low,
+}
+
+impl Priority {
+    /// The number of priorities, which is also the number of lanes in a
+    /// mailbox.
+    pub(super) const COUNT: usize = 3;
+
+    /// Returns the lane of this priority, lanes being polled in order.
+    pub(super) fn lane(self) -> usize {
+        self as usize
+    }
+}
@@ -1,32 +1,94 @@
 use std::{
     num::NonZeroUsize,
-    sync::{Arc, atomic::AtomicBool},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
 };
 
-use flume::Receiver as FlumeReceiver;
-use futures_util::{FutureExt, pin_mut, select_biased};
+use compio_runtime::sync::Notify;
+use flume::{Receiver as FlumeReceiver, RecvError};
+use futures_util::{
+    FutureExt,
+    future::{self, Either},
+    pin_mut, select_biased,
+};
 
-use super::{Mailbox, MailboxInner, Name};
+use super::{ActorId, Clock, Mailbox, MailboxInner, Metrics, Name, Priority, Stash};
 use crate::{Actor, actor::Delivering};
 
 pub(crate) struct Receiver<A: Actor> {
-    messages: FlumeReceiver<Delivering<A>>,
+    control: FlumeReceiver<Delivering<A>>,
+    lanes: [FlumeReceiver<Delivering<A>>; Priority::COUNT],
+    queued: Arc<AtomicUsize>,
+    stash: Arc<Mutex<Stash<A>>>,
     stop: FlumeReceiver<()>,
 }
 
 impl<A: Actor> Receiver<A> {
+    /// Waits for a stop request, or the next message from the control lane,
+    /// then from the unstashed messages, then from each lane in priority
+    /// order.
     pub(crate) async fn recv(&self) -> MailboxEvent<A> {
+        let [high, normal, low] = &self.lanes;
         let stop = self.stop.recv_async().fuse();
-        let message = self.messages.recv_async().fuse();
-        pin_mut!(stop, message);
+        let control = self.control.recv_async().fuse();
+        // Only the receiver takes unstashed messages, so they are still there
+        // when this branch is selected.
+        let unstashed = if self.stash.lock().unwrap().has_unstashed() {
+            Either::Left(future::ready(()))
+        } else {
+            Either::Right(future::pending())
+        };
+        let high = high.recv_async().fuse();
+        let normal = normal.recv_async().fuse();
+        let low = low.recv_async().fuse();
+        pin_mut!(stop, control, unstashed, high, normal, low);
 
         select_biased! {
             _ = stop => MailboxEvent::Stop,
-            message = message => match message {
-                Ok(message) => MailboxEvent::Message(message),
-                Err(_) => MailboxEvent::Stop,
-            },
+            message = control => MailboxEvent::from(message),
+            () = unstashed => self.unstashed(),
+            message = high => self.dequeued(message),
+            message = normal => self.dequeued(message),
+            message = low => self.dequeued(message),
+        }
+    }
+
+    /// Takes the next message without waiting, in the same order as
+    /// [`recv`](Self::recv) but regardless of stop requests.
+    #[cfg(feature = "testkit")]
+    pub(crate) fn try_recv(&self) -> Option<Delivering<A>> {
+        if let Ok(message) = self.control.try_recv() {
+            return Some(message);
+        }
+        if let Some(message) = self.stash.lock().unwrap().pop_unstashed() {
+            return Some(message);
         }
+        let message = self.lanes.iter().find_map(|lane| lane.try_recv().ok())?;
+        self.queued.fetch_sub(1, Ordering::AcqRel);
+        Some(message)
+    }
+
+    fn dequeued(&self, message: Result<Delivering<A>, RecvError>) -> MailboxEvent<A> {
+        if message.is_ok() {
+            self.queued.fetch_sub(1, Ordering::AcqRel);
+        }
+        MailboxEvent::from(message)
+    }
+
+    fn unstashed(&self) -> MailboxEvent<A> {
+        let message = self.stash.lock().unwrap().pop_unstashed();
+        MailboxEvent::Message(message.expect("unstashed message was taken"))
+    }
+}
+
+impl<A: Actor> Drop for Receiver<A> {
+    /// Drops the stashed messages with the receiver, so that their callers
+    /// are not left waiting.
+    fn drop(&mut self) {
+        let messages = self.stash.lock().unwrap().take();
+        drop(messages);
     }
 }
 
@@ -35,24 +97,48 @@ pub(crate) enum MailboxEvent<A: Actor> {
     Stop,
 }
 
+impl<A: Actor> From<Result<Delivering<A>, RecvError>> for MailboxEvent<A> {
+    fn from(message: Result<Delivering<A>, RecvError>) -> Self {
+        match message {
+            Ok(message) => Self::Message(message),
+            Err(_) => Self::Stop,
+        }
+    }
+}
+
 pub(crate) fn make_mailbox<A: Actor>(
     name: Option<Name>,
     capacity: NonZeroUsize,
+    clock: Clock,
 ) -> (Mailbox<A>, Receiver<A>) {
-    let (message_tx, message_rx) = flume::bounded(capacity.get());
+    let (control_tx, control_rx) = flume::unbounded();
+    let [high, normal, low] = [(); Priority::COUNT].map(|()| flume::unbounded());
+    let queued = Arc::new(AtomicUsize::new(0));
+    let stash = Arc::new(Mutex::new(Stash::new()));
     let (stop_tx, stop_rx) = flume::bounded(1);
     let inner = Arc::new(MailboxInner {
+        id: ActorId::next(),
         name,
-        messages: message_tx,
+        control: control_tx,
+        lanes: [high.0, normal.0, low.0],
+        queued: queued.clone(),
+        stash: stash.clone(),
         stop: stop_tx,
         stopping: AtomicBool::new(false),
+        closed: Notify::new(),
         capacity,
+        links: Mutex::default(),
+        metrics: Metrics::new(),
+        clock,
     });
 
     (
         Mailbox { inner },
         Receiver {
-            messages: message_rx,
+            control: control_rx,
+            lanes: [high.1, normal.1, low.1],
+            queued,
+            stash,
             stop: stop_rx,
         },
     )
@@ -0,0 +1,87 @@
+use std::{collections::VecDeque, mem};
+
+use super::{DeliverError, Mailbox};
+use crate::{Actor, Handler, Message, actor::Delivering};
+
+/// Messages an actor deferred, shared by its mailbox and its receiver.
+pub(crate) struct Stash<A: Actor> {
+    stashed: VecDeque<Delivering<A>>,
+    /// Messages given back by `unstash_all`, received before the lanes.
+    unstashed: VecDeque<Delivering<A>>,
+    transitioned: bool,
+}
+
+impl<A: Actor> Stash<A> {
+    pub(crate) fn new() -> Self {
+        Self {
+            stashed: VecDeque::new(),
+            unstashed: VecDeque::new(),
+            transitioned: false,
+        }
+    }
+
+    pub(crate) fn has_unstashed(&self) -> bool {
+        !self.unstashed.is_empty()
+    }
+
+    pub(crate) fn pop_unstashed(&mut self) -> Option<Delivering<A>> {
+        self.unstashed.pop_front()
+    }
+
+    /// Takes the messages out, so that they can be dropped without the lock.
+    pub(crate) fn take(&mut self) -> (VecDeque<Delivering<A>>, VecDeque<Delivering<A>>) {
+        (mem::take(&mut self.stashed), mem::take(&mut self.unstashed))
+    }
+}
+
+impl<A: Actor> Mailbox<A> {
+    /// Sets a message aside until [`unstash_all`](Self::unstash_all), such as
+    /// one that arrived before the actor is ready for it.
+    ///
+    /// This is meant for the actor's own handlers. Stashed messages no longer
+    /// count against the capacity, but at most `capacity` of them can be
+    /// stashed at once. They are dropped when the actor stops.
+    pub fn stash<M>(&self, message: M) -> Result<(), DeliverError<M>>
+    where
+        A: Handler<M>,
+        M: Message,
+    {
+        if self.is_closed() {
+            return Err(DeliverError::Closed(message));
+        }
+
+        let mut stash = self.inner.stash.lock().unwrap();
+        if stash.stashed.len() >= self.inner.capacity.get() {
+            return Err(DeliverError::Full(message));
+        }
+        stash.stashed.push_back(Delivering::from_msg(message));
+        Ok(())
+    }
+
+    /// Moves every stashed message back to the front of the mailbox, in the
+    /// order they were stashed, and returns how many there were.
+    ///
+    /// Unstashing marks a transition of the actor's state machine: once the
+    /// current handler returns, [`Actor::on_transition`] runs before the
+    /// unstashed messages are handled.
+    pub fn unstash_all(&self) -> usize {
+        let mut stash = self.inner.stash.lock().unwrap();
+        let mut unstashed = mem::take(&mut stash.stashed);
+        let count = unstashed.len();
+        unstashed.append(&mut stash.unstashed);
+        stash.unstashed = unstashed;
+        stash.transitioned = true;
+        count
+    }
+
+    /// Returns the number of stashed messages.
+    pub fn stashed(&self) -> usize {
+        self.inner.stash.lock().unwrap().stashed.len()
+    }
+
+    /// Returns whether [`unstash_all`](Self::unstash_all) was called since
+    /// the last check.
+    pub(crate) fn take_transition(&self) -> bool {
+        mem::take(&mut self.inner.stash.lock().unwrap().transitioned)
+    }
+}
@@ -0,0 +1,126 @@
+use std::{
+    fmt,
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use compio_runtime::JoinHandle;
+use futures_util::{FutureExt, pin_mut, select_biased};
+
+use super::{Mailbox, MailboxInner};
+use crate::{Actor, Handler, Message};
+
+impl<A: Actor> Mailbox<A> {
+    /// Sends `message` to this actor once `delay` has elapsed.
+    ///
+    /// The timer runs on the current runtime, which is the actor's worker when
+    /// called from one of its hooks or handlers. It ends early when the actor
+    /// stops, and the message is dropped if the mailbox is full when it fires.
+    ///
+    /// # Panics
+    ///
+    /// Panics when called outside a Compio runtime.
+    pub fn send_after<M>(&self, message: M, delay: Duration) -> TimerHandle
+    where
+        A: Handler<M>,
+        M: Message,
+    {
+        let mailbox = self.clone();
+        let deadline = self.inner.clock.now() + delay;
+        TimerHandle(compio_runtime::spawn(async move {
+            let sleep = mailbox.inner.clock.sleep_until(deadline);
+            if until_closed(&mailbox.inner, sleep).await.is_some() {
+                mailbox.send(message).ok();
+            }
+        }))
+    }
+
+    /// Sends a message made by `factory` to this actor every `period`,
+    /// starting one `period` from now.
+    ///
+    /// Like [`send_after`](Self::send_after), the timer runs on the current
+    /// runtime and ends when the actor stops. A tick is skipped if the mailbox
+    /// is full.
+    ///
+    /// # Panics
+    ///
+    /// Panics when called outside a Compio runtime, or if `period` is zero.
+    pub fn send_interval<M, F>(&self, mut factory: F, period: Duration) -> TimerHandle
+    where
+        A: Handler<M>,
+        M: Message,
+        F: FnMut() -> M + 'static,
+    {
+        assert!(period > Duration::ZERO, "`period` must be non-zero.");
+        let mailbox = self.clone();
+        let mut deadline = self.inner.clock.now() + period;
+        TimerHandle(compio_runtime::spawn(async move {
+            let clock = &mailbox.inner.clock;
+            while until_closed(&mailbox.inner, clock.sleep_until(deadline))
+                .await
+                .is_some()
+            {
+                if mailbox.send(factory()).is_err() && mailbox.is_closed() {
+                    break;
+                }
+                deadline = next_tick(deadline, period, clock.now());
+            }
+        }))
+    }
+}
+
+/// Returns the first tick after `now`, skipping those missed since `deadline`
+/// like an [`Interval`](compio_runtime::time::Interval) does.
+fn next_tick(deadline: Instant, period: Duration, now: Instant) -> Instant {
+    let behind = now.saturating_duration_since(deadline);
+    let skipped = behind.as_nanos() / period.as_nanos() * period.as_nanos();
+    deadline + period + Duration::from_nanos(skipped as u64)
+}
+
+/// Runs `future` until it completes or the mailbox closes.
+async fn until_closed<A: Actor, F: Future>(
+    inner: &MailboxInner<A>,
+    future: F,
+) -> Option<F::Output> {
+    let closed = inner.closed.notified().fuse();
+    if inner.is_closed() {
+        return None;
+    }
+    let future = future.fuse();
+    pin_mut!(closed, future);
+    select_biased! {
+        () = closed => None,
+        output = future => Some(output),
+    }
+}
+
+/// A timer started by [`Mailbox::send_after`] or [`Mailbox::send_interval`].
+///
+/// Dropping the handle cancels the timer. Use [`detach`](Self::detach) to keep
+/// it running until it fires or the actor stops.
+#[must_use = "dropping the handle cancels the timer. Use `detach` to keep it running."]
+pub struct TimerHandle(JoinHandle<()>);
+
+impl TimerHandle {
+    /// Cancels the timer.
+    pub fn cancel(self) {}
+
+    /// Keeps the timer running without the handle.
+    pub fn detach(self) {
+        self.0.detach();
+    }
+
+    /// Returns whether the timer has fired for the last time, or ended because
+    /// the actor stopped.
+    pub fn is_finished(&self) -> bool {
+        self.0.is_finished()
+    }
+}
+
+impl fmt::Debug for TimerHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimerHandle")
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
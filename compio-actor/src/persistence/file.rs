@@ -0,0 +1,165 @@
+use std::{io, path::Path};
+
+use compio_buf::BufResult;
+use compio_fs::{File, OpenOptions};
+use compio_io::{AsyncReadAtExt, AsyncWriteAtExt};
+use crc32fast::Hasher;
+
+use super::{Journal, Recovery};
+use crate::codec::{Codec, invalid_data};
+
+/// A record's kind, sequence number, payload length, and the CRC-32 of the
+/// rest of the header and the payload.
+const HEADER: usize = 1 + 8 + 4 + 4;
+
+const EVENT: u8 = 0;
+const SNAPSHOT: u8 = 1;
+
+/// A [`Journal`] kept in an append-only file.
+///
+/// Each event and snapshot is encoded with the codec and appended as a
+/// record, and the file is synced with [`File::sync_data`] before an append
+/// returns. Each record carries a checksum, and opening a journal drops the
+/// records from the first one that is incomplete or fails its checksum, as
+/// left by a crash, so an event either persisted entirely or not at all.
+///
+/// Snapshots shorten recovery, as the events before the latest one are
+/// skipped, but the file keeps growing. Only one journal may have the file
+/// open at a time.
+#[derive(Debug)]
+pub struct FileJournal<C> {
+    file: File,
+    codec: C,
+    end: u64,
+}
+
+impl<C> FileJournal<C> {
+    /// Opens the journal at `path`, creating it if it does not exist.
+    pub async fn open(path: impl AsRef<Path>, codec: C) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(path)
+            .await?;
+        let contents = read(&file).await?;
+        let end = records(&contents)?.1 as u64;
+        if end < contents.len() as u64 {
+            file.set_len(end).await?;
+            file.sync_data().await?;
+        }
+        Ok(Self { file, codec, end })
+    }
+
+    /// Returns the length of the records in the file.
+    pub fn len(&self) -> u64 {
+        self.end
+    }
+
+    /// Returns whether nothing has been persisted.
+    pub fn is_empty(&self) -> bool {
+        self.end == 0
+    }
+
+    async fn write(&mut self, records: Vec<u8>) -> io::Result<()> {
+        let len = records.len() as u64;
+        self.file.write_all_at(records, self.end).await.0?;
+        self.file.sync_data().await?;
+        self.end += len;
+        Ok(())
+    }
+}
+
+impl<E, S, C: Codec<E> + Codec<S>> Journal<E, S> for FileJournal<C> {
+    async fn append(&mut self, sequence: u64, events: &[E]) -> io::Result<()> {
+        let mut records = Vec::new();
+        for (sequence, event) in (sequence..).zip(events) {
+            let payload = Codec::<E>::encode(&self.codec, event)?;
+            record(&mut records, EVENT, sequence, &payload)?;
+        }
+        self.write(records).await
+    }
+
+    async fn save_snapshot(&mut self, sequence: u64, snapshot: &S) -> io::Result<()> {
+        let mut records = Vec::new();
+        let payload = Codec::<S>::encode(&self.codec, snapshot)?;
+        record(&mut records, SNAPSHOT, sequence, &payload)?;
+        self.write(records).await
+    }
+
+    async fn recover(&mut self) -> io::Result<Recovery<E, S>> {
+        let mut contents = read(&self.file).await?;
+        contents.truncate(self.end as usize);
+        let mut recovery = Recovery::default();
+        for (kind, sequence, payload) in records(&contents)?.0 {
+            match kind {
+                EVENT => recovery
+                    .events
+                    .push(Codec::<E>::decode(&self.codec, payload.to_vec())?),
+                _ => {
+                    let snapshot = Codec::<S>::decode(&self.codec, payload.to_vec())?;
+                    recovery.snapshot = Some((sequence, snapshot));
+                    recovery.events.clear();
+                }
+            }
+        }
+        Ok(recovery)
+    }
+}
+
+async fn read(file: &File) -> io::Result<Vec<u8>> {
+    let BufResult(result, contents) = file.read_to_end_at(Vec::new(), 0).await;
+    result?;
+    Ok(contents)
+}
+
+fn record(records: &mut Vec<u8>, kind: u8, sequence: u64, payload: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(payload.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "record too large"))?;
+    let start = records.len();
+    records.push(kind);
+    records.extend_from_slice(&sequence.to_le_bytes());
+    records.extend_from_slice(&len.to_le_bytes());
+    let crc = checksum(&records[start..], payload);
+    records.extend_from_slice(&crc.to_le_bytes());
+    records.extend_from_slice(payload);
+    Ok(())
+}
+
+/// Returns the CRC-32 of a record's header, without the checksum, and payload.
+fn checksum(header: &[u8], payload: &[u8]) -> u32 {
+    let mut hasher = Hasher::new();
+    hasher.update(header);
+    hasher.update(payload);
+    hasher.finalize()
+}
+
+type Record<'a> = (u8, u64, &'a [u8]);
+
+/// Splits the complete records off the front of `contents`, up to the first
+/// that is cut short or fails its checksum, returning them and their length.
+fn records(contents: &[u8]) -> io::Result<(Vec<Record<'_>>, usize)> {
+    let mut records = Vec::new();
+    let mut rest = contents;
+    while let Some((header, body)) = rest.split_first_chunk::<HEADER>() {
+        let (checked, crc) = header.split_at(HEADER - 4);
+        let (kind, fields) = checked.split_first().unwrap();
+        let (sequence, len) = fields.split_at(8);
+        let sequence = u64::from_le_bytes(sequence.try_into().unwrap());
+        let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+        let crc = u32::from_le_bytes(crc.try_into().unwrap());
+        if body.len() < len {
+            break;
+        }
+        let (payload, body) = body.split_at(len);
+        if checksum(checked, payload) != crc {
+            break;
+        }
+        if !matches!(*kind, EVENT | SNAPSHOT) {
+            return Err(invalid_data(format_args!("unknown journal record {kind}")));
+        }
+        records.push((*kind, sequence, payload));
+        rest = body;
+    }
+    Ok((records, contents.len() - rest.len()))
+}
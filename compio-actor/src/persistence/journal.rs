@@ -0,0 +1,37 @@
+use std::io;
+
+/// A durable store for the events and snapshots of a
+/// [`PersistentActor`](super::PersistentActor).
+///
+/// Events are numbered from one, in the order they were persisted. A journal
+/// must not return from [`append`](Self::append) or
+/// [`save_snapshot`](Self::save_snapshot) until the data survives a crash.
+#[allow(async_fn_in_trait)]
+pub trait Journal<E, S> {
+    /// Appends events, the first of which has the number `sequence`.
+    async fn append(&mut self, sequence: u64, events: &[E]) -> io::Result<()>;
+
+    /// Saves the state reached after the first `sequence` events.
+    async fn save_snapshot(&mut self, sequence: u64, snapshot: &S) -> io::Result<()>;
+
+    /// Loads the latest snapshot and the events persisted after it.
+    async fn recover(&mut self) -> io::Result<Recovery<E, S>>;
+}
+
+/// What a [`Journal`] holds for an actor that starts again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recovery<E, S> {
+    /// The latest snapshot, with the number of events it covers.
+    pub snapshot: Option<(u64, S)>,
+    /// The events persisted after the snapshot, in order.
+    pub events: Vec<E>,
+}
+
+impl<E, S> Default for Recovery<E, S> {
+    fn default() -> Self {
+        Self {
+            snapshot: None,
+            events: Vec::new(),
+        }
+    }
+}
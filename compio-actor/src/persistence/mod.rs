@@ -0,0 +1,171 @@
+//! Event-sourced actors that recover their state after a restart.
+//!
+//! A [`PersistentActor`] keeps the state it rebuilds from events in a
+//! [`Persistent`], usually as its [`Actor::State`]. Handlers persist events to
+//! a [`Journal`] before they are applied, and `pre_start` replays the latest
+//! snapshot and the events after it:
+//!
+//! ```rust,no_run
+//! use std::{io, path::PathBuf};
+//!
+//! use compio_actor::{
+//!     Actor, Call, Handler, Mailbox,
+//!     persistence::{FileJournal, Persistent, PersistentActor},
+//! };
+//! use compio_io::framed::codec::serde_json::SerdeJsonCodec;
+//!
+//! struct Counter;
+//!
+//! impl Actor for Counter {
+//!     type Arguments = PathBuf;
+//!     type Error = io::Error;
+//!     type State = Persistent<Self, FileJournal<SerdeJsonCodec>>;
+//!
+//!     async fn pre_start(&self, _myself: &Mailbox<Self>, path: PathBuf) -> io::Result<Self::State> {
+//!         let journal = FileJournal::open(path, SerdeJsonCodec::new()).await?;
+//!         Persistent::recover(journal, 0).await
+//!     }
+//! }
+//!
+//! impl PersistentActor for Counter {
+//!     type Data = u64;
+//!     type Event = u64;
+//!
+//!     fn apply(total: &mut u64, added: &u64) {
+//!         *total += added;
+//!     }
+//! }
+//!
+//! impl Handler<u64> for Counter {
+//!     async fn handle(&self, _myself: &Mailbox<Self>, added: u64, state: &mut Self::State) -> io::Result<()> {
+//!         state.persist(added).await
+//!     }
+//! }
+//!
+//! impl Handler<Call<(), u64>> for Counter {
+//!     async fn handle(&self, _myself: &Mailbox<Self>, call: Call<(), u64>, state: &mut Self::State) -> io::Result<()> {
+//!         call.reply(**state).ok();
+//!         Ok(())
+//!     }
+//! }
+//! ```
+
+mod file;
+mod journal;
+
+use std::{fmt, io, ops::Deref};
+
+#[doc(inline)]
+pub use file::FileJournal;
+#[doc(inline)]
+pub use journal::{Journal, Recovery};
+
+use crate::Actor;
+#[doc(inline)]
+pub use crate::codec::Codec;
+
+/// An actor whose state is rebuilt from the events it persisted.
+pub trait PersistentActor: Actor {
+    /// The state rebuilt from events, and saved in snapshots.
+    type Data: 'static;
+    /// A change to the data.
+    type Event: 'static;
+
+    /// Applies an event to the data.
+    ///
+    /// It runs both for new events and for those replayed by
+    /// [`Persistent::recover`], so it must not have other effects, nor fail.
+    fn apply(data: &mut Self::Data, event: &Self::Event);
+}
+
+/// The data of a [`PersistentActor`] and the journal that keeps it.
+///
+/// It dereferences to the data, which only changes through persisted events.
+pub struct Persistent<A: PersistentActor, J> {
+    data: A::Data,
+    journal: J,
+    sequence: u64,
+}
+
+impl<A: PersistentActor, J: Journal<A::Event, A::Data>> Persistent<A, J> {
+    /// Recovers the data from a journal, starting from `initial` when it holds
+    /// no snapshot.
+    pub async fn recover(mut journal: J, initial: A::Data) -> io::Result<Self> {
+        let Recovery { snapshot, events } = journal.recover().await?;
+        let (sequence, mut data) = snapshot.unwrap_or((0, initial));
+        for event in &events {
+            A::apply(&mut data, event);
+        }
+        Ok(Self {
+            data,
+            journal,
+            sequence: sequence + events.len() as u64,
+        })
+    }
+
+    /// Persists an event, then applies it.
+    ///
+    /// The data is unchanged if the journal fails.
+    pub async fn persist(&mut self, event: A::Event) -> io::Result<()> {
+        self.persist_all([event]).await
+    }
+
+    /// Persists events together, then applies them in order.
+    ///
+    /// The data is unchanged if the journal fails.
+    pub async fn persist_all(
+        &mut self,
+        events: impl IntoIterator<Item = A::Event>,
+    ) -> io::Result<()> {
+        let events = events.into_iter().collect::<Vec<_>>();
+        if events.is_empty() {
+            return Ok(());
+        }
+        self.journal.append(self.sequence + 1, &events).await?;
+        for event in &events {
+            A::apply(&mut self.data, event);
+        }
+        self.sequence += events.len() as u64;
+        Ok(())
+    }
+
+    /// Saves a snapshot of the data, so that a recovery skips the events
+    /// persisted so far.
+    pub async fn snapshot(&mut self) -> io::Result<()> {
+        self.journal.save_snapshot(self.sequence, &self.data).await
+    }
+}
+
+impl<A: PersistentActor, J> Persistent<A, J> {
+    /// Returns the number of events persisted, including those before a
+    /// restart.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Returns the journal.
+    pub fn journal(&self) -> &J {
+        &self.journal
+    }
+}
+
+impl<A: PersistentActor, J> Deref for Persistent<A, J> {
+    type Target = A::Data;
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl<A: PersistentActor, J: fmt::Debug> fmt::Debug for Persistent<A, J>
+where
+    A::Data: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Persistent")
+            .field("data", &self.data)
+            .field("journal", &self.journal)
+            .field("sequence", &self.sequence)
+            .finish()
+    }
+}
@@ -4,18 +4,30 @@ mod strategy;
 
 use std::{
     fmt,
+    hash::Hash,
     sync::{Arc, Mutex, Weak},
+    time::{Duration, Instant},
 };
 
+use compio_runtime::time::timeout_at;
+use futures_channel::oneshot;
+use futures_util::{StreamExt, stream::FuturesUnordered};
 #[doc(inline)]
 pub use strategy::Strategy;
+use strategy::{KeyFn, Routing};
 
 use crate::{
     Broker, Call, Message,
-    mailbox::{CallError, DeliverError, call_with},
+    mailbox::{ActorId, CallError, DeliverError, call_timeout_with, call_with},
 };
 
+#[cfg(doc)]
+use crate::Cluster;
+
 /// A group of actors that share messages using a routing [`Strategy`].
+///
+/// A group is an anonymous value shared by cloning it, or a named one
+/// returned by [`Cluster::group`].
 pub struct ProcessGroup<M: Message> {
     inner: Arc<GroupInner<M>>,
 }
@@ -32,9 +44,11 @@ impl<M: Message> ProcessGroup<M> {
             inner: Arc::new(GroupInner {
                 state: Mutex::new(GroupState {
                     next_id: 0,
-                    cursor: 0,
+                    routing: Routing::new(),
                     members: Vec::new(),
+                    subscribers: Vec::new(),
                     strategy,
+                    key: None,
                 }),
             }),
         }
@@ -45,32 +59,102 @@ impl<M: Message> ProcessGroup<M> {
         let mut state = self.inner.state.lock().unwrap();
         let id = state.next_id;
         state.next_id = state.next_id.wrapping_add(1);
-        state.members.push(Member { id, broker });
+        state.members.push(Member {
+            id,
+            broker: broker.clone(),
+        });
+        state.notify(GroupEvent::Joined(broker));
         Membership {
             id,
             group: Arc::downgrade(&self.inner),
         }
     }
 
+    /// Sends [`GroupEvent`]s to `broker` as members join and leave, until the
+    /// returned subscription is dropped.
+    ///
+    /// The current members are sent as [`GroupEvent::Joined`] right away.
+    /// Like [`Down`](crate::mailbox::Down), the events travel on the control
+    /// lane, so they are not dropped while the subscriber's mailbox is open.
+    pub fn subscribe(&self, broker: Broker<GroupEvent<M>>) -> Subscription<M> {
+        let mut state = self.inner.state.lock().unwrap();
+        let id = state.next_id;
+        state.next_id = state.next_id.wrapping_add(1);
+        for member in &state.members {
+            broker
+                .send_control(GroupEvent::Joined(member.broker.clone()))
+                .ok();
+        }
+        state.subscribers.push((id, broker));
+        Subscription {
+            id,
+            group: Arc::downgrade(&self.inner),
+        }
+    }
+
+    /// Creates an empty process group routing messages by the key returned
+    /// by `key`, with [`Strategy::ConsistentHash`].
+    pub fn consistent_hash<K, F>(key: F) -> Self
+    where
+        K: Hash,
+        F: Fn(&M) -> K + Send + Sync + 'static,
+    {
+        let group = Self::with_strategy(Strategy::ConsistentHash);
+        group.set_hash_key(key);
+        group
+    }
+
+    /// Replaces the routing strategy.
+    pub fn set_strategy(&self, strategy: Strategy) {
+        let mut state = self.inner.state.lock().unwrap();
+        state.strategy = strategy;
+        state.routing = Routing::new();
+    }
+
+    /// Sets the key that [`Strategy::ConsistentHash`] routes messages by.
+    pub fn set_hash_key<K, F>(&self, key: F)
+    where
+        K: Hash,
+        F: Fn(&M) -> K + Send + Sync + 'static,
+    {
+        self.inner.state.lock().unwrap().key = Some(KeyFn::new(key));
+    }
+
     /// Routes a message to the next available member.
-    pub fn send(&self, mut message: M) -> Result<(), DeliverError<M>> {
+    pub fn send(&self, message: M) -> Result<(), DeliverError<M>> {
+        self.route(message, Broker::send)
+    }
+
+    /// Routes a message with `send`, trying each member once from the one the
+    /// strategy picked.
+    fn route<T>(
+        &self,
+        mut message: M,
+        send: impl Fn(&Broker<M>, M) -> Result<T, DeliverError<M>>,
+    ) -> Result<T, DeliverError<M>> {
         let mut state = self.inner.state.lock().unwrap();
+        let state = &mut *state;
         let attempts = state.members.len();
         let mut attempted = 0;
         let mut saw_full = false;
-        let mut index = match std::num::NonZeroUsize::new(state.members.len()) {
-            Some(members) => {
-                let strategy = state.strategy;
-                strategy.select(&mut state.cursor, members)
-            }
-            None => return Err(DeliverError::Closed(message)),
-        };
+        if state.members.is_empty() {
+            return Err(DeliverError::Closed(message));
+        }
+        let mut index = state.strategy.select(
+            &mut state.routing,
+            &state.members,
+            &message,
+            state.key.as_ref(),
+        );
 
         while attempted < attempts && !state.members.is_empty() {
             attempted += 1;
 
-            match state.members[index].broker.send(message) {
-                Ok(()) => return Ok(()),
+            match send(&state.members[index].broker, message) {
+                Ok(sent) => {
+                    state.routing.sticky = Some(state.members[index].id);
+                    return Ok(sent);
+                }
                 Err(DeliverError::Full(returned)) => {
                     saw_full = true;
                     message = returned;
@@ -78,7 +162,7 @@ impl<M: Message> ProcessGroup<M> {
                 }
                 Err(DeliverError::Closed(returned)) => {
                     message = returned;
-                    state.members.remove(index);
+                    state.remove(index);
                     if !state.members.is_empty() {
                         index %= state.members.len();
                     }
@@ -104,11 +188,79 @@ impl<M: Message> ProcessGroup<M> {
     }
 }
 
+impl<M: Message + Clone> ProcessGroup<M> {
+    /// Sends a copy of `message` to every member.
+    ///
+    /// Returns the members that rejected their copy, along with the reason.
+    /// Closed members are removed from the group.
+    pub fn broadcast(&self, message: M) -> Vec<(Broker<M>, DeliverError<M>)> {
+        let mut state = self.inner.state.lock().unwrap();
+        let mut rejected = Vec::new();
+        state.retain(|member| match member.broker.send(message.clone()) {
+            Ok(()) => true,
+            Err(error) => {
+                let closed = matches!(error, DeliverError::Closed(_));
+                rejected.push((member.broker.clone(), error));
+                !closed
+            }
+        });
+        rejected
+    }
+}
+
 impl<M: Message, R: Message> ProcessGroup<Call<M, R>> {
     /// Routes a request and waits for the selected actor's reply.
     pub async fn call(&self, message: M) -> Result<R, CallError<M>> {
         call_with(message, |call| self.send(call)).await
     }
+
+    /// Routes a request and waits at most `timeout` for the selected actor's
+    /// reply.
+    ///
+    /// See [`Mailbox::call_timeout`](crate::Mailbox::call_timeout).
+    pub async fn call_timeout(&self, message: M, timeout: Duration) -> Result<R, CallError<M>> {
+        call_timeout_with(message, timeout, |call| {
+            self.route(call, Broker::send_deferred)
+        })
+        .await
+    }
+}
+
+impl<M: Message + Clone, R: Message> ProcessGroup<Call<M, R>> {
+    /// Sends a copy of the request to every member, and collects the replies
+    /// that arrive within `timeout`, in the order they arrive.
+    ///
+    /// Members that reject the request or don't reply in time are left out.
+    /// Closed members are removed from the group.
+    pub async fn call_all(&self, message: M, timeout: Duration) -> Vec<R> {
+        let deadline = Instant::now() + timeout;
+        let mut pending = {
+            let mut state = self.inner.state.lock().unwrap();
+            let pending = FuturesUnordered::new();
+            state.retain(|member| {
+                let (sender, receiver) = oneshot::channel();
+                match member
+                    .broker
+                    .send(Call::new(message.clone(), sender, Some(deadline)))
+                {
+                    Ok(()) => {
+                        pending.push(receiver);
+                        true
+                    }
+                    Err(error) => !matches!(error, DeliverError::Closed(_)),
+                }
+            });
+            pending
+        };
+
+        let mut replies = Vec::with_capacity(pending.len());
+        while let Ok(Some(reply)) = timeout_at(deadline, pending.next()).await {
+            if let Ok(reply) = reply {
+                replies.push(reply);
+            }
+        }
+        replies
+    }
 }
 
 impl<M: Message> Clone for ProcessGroup<M> {
@@ -152,7 +304,7 @@ impl<M: Message> Drop for Membership<M> {
         };
         let mut state = group.state.lock().unwrap();
         if let Some(index) = state.members.iter().position(|member| member.id == self.id) {
-            state.members.remove(index);
+            state.remove(index);
         }
     }
 }
@@ -163,15 +315,102 @@ impl<M: Message> fmt::Debug for Membership<M> {
     }
 }
 
+/// A change to the members of a [`ProcessGroup`].
+///
+/// See [`ProcessGroup::subscribe`].
+pub enum GroupEvent<M: Message> {
+    /// An actor joined the group.
+    Joined(Broker<M>),
+    /// An actor left the group, or was removed once its mailbox closed.
+    Left(ActorId),
+}
+
+impl<M: Message> Clone for GroupEvent<M> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Joined(broker) => Self::Joined(broker.clone()),
+            Self::Left(id) => Self::Left(*id),
+        }
+    }
+}
+
+impl<M: Message> fmt::Debug for GroupEvent<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Joined(broker) => f.debug_tuple("Joined").field(broker).finish(),
+            Self::Left(id) => f.debug_tuple("Left").field(id).finish(),
+        }
+    }
+}
+
+/// A subscription to the [`GroupEvent`]s of a [`ProcessGroup`].
+#[must_use = "dropping the subscription stops the group events"]
+pub struct Subscription<M: Message> {
+    id: u64,
+    group: Weak<GroupInner<M>>,
+}
+
+impl<M: Message> Subscription<M> {
+    /// Stops sending group events.
+    pub fn unsubscribe(self) {}
+}
+
+impl<M: Message> Drop for Subscription<M> {
+    fn drop(&mut self) {
+        let Some(group) = self.group.upgrade() else {
+            return;
+        };
+        let mut state = group.state.lock().unwrap();
+        state.subscribers.retain(|(id, _)| *id != self.id);
+    }
+}
+
+impl<M: Message> fmt::Debug for Subscription<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
 struct GroupInner<M: Message> {
     state: Mutex<GroupState<M>>,
 }
 
 struct GroupState<M: Message> {
     next_id: u64,
-    cursor: usize,
+    routing: Routing,
     members: Vec<Member<M>>,
+    subscribers: Vec<(u64, Broker<GroupEvent<M>>)>,
     strategy: Strategy,
+    key: Option<KeyFn<M>>,
+}
+
+impl<M: Message> GroupState<M> {
+    fn remove(&mut self, index: usize) {
+        let member = self.members.remove(index);
+        self.notify(GroupEvent::Left(member.broker.id()));
+    }
+
+    fn retain(&mut self, mut keep: impl FnMut(&Member<M>) -> bool) {
+        let mut left = Vec::new();
+        self.members.retain(|member| {
+            let kept = keep(member);
+            if !kept {
+                left.push(member.broker.id());
+            }
+            kept
+        });
+        for id in left {
+            self.notify(GroupEvent::Left(id));
+        }
+    }
+
+    /// Sends an event to every subscriber, dropping those that are closed.
+    fn notify(&mut self, event: GroupEvent<M>) {
+        self.subscribers
+            .retain(|(_, broker)| broker.send_control(event.clone()).is_ok());
+    }
 }
 
 struct Member<M: Message> {
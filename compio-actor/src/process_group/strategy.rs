@@ -1,4 +1,7 @@
-use std::num::NonZeroUsize;
+use std::hash::{BuildHasher, DefaultHasher, Hash, Hasher, RandomState};
+
+use super::Member;
+use crate::Message;
 
 /// Routing policy used by a process group.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
@@ -6,16 +9,108 @@ pub enum Strategy {
     /// Routes each message to the member after the previous selection.
     #[default]
     RoundRobin,
+    /// Routes each message to a member picked at random.
+    Random,
+    /// Routes each message to the member with the fewest queued messages.
+    LeastLoaded,
+    /// Routes messages with the same key to the same member, for as long as
+    /// the membership does not change. The key is set with
+    /// [`ProcessGroup::set_hash_key`], and the group routes like
+    /// [`RoundRobin`](Self::RoundRobin) until then.
+    ///
+    /// When members join or leave, only the keys routed to them move.
+    ///
+    /// [`ProcessGroup::set_hash_key`]: super::ProcessGroup::set_hash_key
+    ConsistentHash,
+    /// Routes every message to the same member, and only moves on to another
+    /// one once it fails to accept a message.
+    Sticky,
 }
 
 impl Strategy {
-    pub(super) fn select(&self, cursor: &mut usize, members: NonZeroUsize) -> usize {
-        match self {
-            Self::RoundRobin => {
-                let selected = *cursor % members.get();
-                *cursor = cursor.wrapping_add(1);
-                selected
+    pub(super) fn select<M: Message>(
+        &self,
+        routing: &mut Routing,
+        members: &[Member<M>],
+        message: &M,
+        key: Option<&KeyFn<M>>,
+    ) -> usize {
+        let len = members.len();
+        match (self, key) {
+            (Self::RoundRobin, _) | (Self::ConsistentHash, None) => routing.next_cursor() % len,
+            (Self::Random, _) => (routing.next_random() % len as u64) as usize,
+            (Self::LeastLoaded, _) => {
+                let start = routing.next_cursor();
+                (0..len)
+                    .map(|offset| (start + offset) % len)
+                    .min_by_key(|&index| members[index].broker.queued())
+                    .unwrap_or_default()
+            }
+            (Self::ConsistentHash, Some(key)) => {
+                let key = (key.0)(message);
+                members
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|(_, member)| hash(&(key, member.id)))
+                    .map_or(0, |(index, _)| index)
             }
+            (Self::Sticky, _) => routing
+                .sticky
+                .and_then(|id| members.iter().position(|member| member.id == id))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// The key function of [`Strategy::ConsistentHash`], hashing the key of a
+/// message.
+pub(super) struct KeyFn<M>(Box<dyn Fn(&M) -> u64 + Send + Sync>);
+
+impl<M> KeyFn<M> {
+    pub(super) fn new<K, F>(key: F) -> Self
+    where
+        K: Hash,
+        F: Fn(&M) -> K + Send + Sync + 'static,
+    {
+        Self(Box::new(move |message| hash(&key(message))))
+    }
+}
+
+/// The routing state of a group, shared by all strategies.
+pub(super) struct Routing {
+    cursor: usize,
+    random: u64,
+    /// The member that [`Strategy::Sticky`] routes to.
+    pub(super) sticky: Option<u64>,
+}
+
+impl Routing {
+    pub(super) fn new() -> Self {
+        Self {
+            cursor: 0,
+            random: RandomState::new().build_hasher().finish(),
+            sticky: None,
         }
     }
+
+    fn next_cursor(&mut self) -> usize {
+        let cursor = self.cursor;
+        self.cursor = cursor.wrapping_add(1);
+        cursor
+    }
+
+    /// SplitMix64.
+    fn next_random(&mut self) -> u64 {
+        self.random = self.random.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.random;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
+
+fn hash<K: Hash + ?Sized>(key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
 }
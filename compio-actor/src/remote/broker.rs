@@ -0,0 +1,228 @@
+use std::{
+    fmt,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+};
+
+use super::{
+    Codec,
+    node::{Connection, Response},
+    packet::{Packet, fits},
+};
+use crate::{
+    Call, Message,
+    actor::Slot,
+    mailbox::{CallError, DeliverError, Name, call_with},
+};
+
+/// A send-only capability for messages of type `M`, handled by an actor of a
+/// [`RemoteNode`](super::RemoteNode).
+///
+/// Messages are encoded and queued on the connection without waiting. Once
+/// queued, they are lost if the peer cannot deliver them, like casts to a full
+/// mailbox.
+pub struct RemoteBroker<M: Message> {
+    inner: Arc<dyn RemoteSink<M>>,
+}
+
+impl<M: Message> RemoteBroker<M> {
+    pub(super) fn new(sink: impl RemoteSink<M> + 'static) -> Self {
+        Self {
+            inner: Arc::new(sink),
+        }
+    }
+
+    /// Returns the name the actor is exposed under.
+    pub fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    /// Encodes and queues a message without waiting.
+    ///
+    /// Returns [`DeliverError::Closed`] once the connection is closed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the codec fails to encode the message, or if it encodes to
+    /// more than the 8 MiB a packet can hold.
+    pub fn send(&self, message: M) -> Result<(), DeliverError<M>> {
+        self.inner.send(message)
+    }
+
+    /// Returns whether the connection to the actor's node is closed.
+    pub fn is_closed(&self) -> bool {
+        self.inner.connection().is_closed()
+    }
+}
+
+impl<M: Message, R: Message> RemoteBroker<Call<M, R>> {
+    /// Sends a request and waits for the actor's reply.
+    ///
+    /// Fails with [`CallError::NoReply`] if the peer could not deliver the
+    /// request, its handler did not reply, or the connection closed first.
+    ///
+    /// Fails with [`CallError::NoReply`] too if the reply encodes to more than
+    /// the 8 MiB a packet can hold.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the codec fails to encode the request, or if it encodes to
+    /// more than 8 MiB.
+    pub async fn call(&self, message: M) -> Result<R, CallError<M>> {
+        call_with(message, |call| self.send(call)).await
+    }
+}
+
+impl<M: Message> Clone for RemoteBroker<M> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<M: Message> fmt::Debug for RemoteBroker<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteBroker")
+            .field("name", &self.name())
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+pub(super) trait RemoteSink<M: Message>: Send + Sync {
+    fn name(&self) -> &str;
+    fn connection(&self) -> &Connection;
+    fn send(&self, message: M) -> Result<(), DeliverError<M>>;
+}
+
+/// Sends messages as casts.
+pub(super) struct Cast<M, C> {
+    connection: Arc<Connection>,
+    name: Name,
+    codec: C,
+    message: PhantomData<fn(M)>,
+}
+
+impl<M, C> Cast<M, C> {
+    pub(super) fn new(connection: Arc<Connection>, name: Name, codec: C) -> Self {
+        Self {
+            connection,
+            name,
+            codec,
+            message: PhantomData,
+        }
+    }
+}
+
+impl<M, C> RemoteSink<M> for Cast<M, C>
+where
+    M: Message,
+    C: Codec<M>,
+{
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    fn send(&self, message: M) -> Result<(), DeliverError<M>> {
+        if self.connection.is_closed() {
+            return Err(DeliverError::Closed(message));
+        }
+        let payload = encode(&self.codec, &self.name, &message);
+        let packet = Packet::Cast {
+            name: self.name.clone(),
+            payload,
+        };
+        if self.connection.send(packet) {
+            Ok(())
+        } else {
+            Err(DeliverError::Closed(message))
+        }
+    }
+}
+
+/// Sends calls as requests, replying to them with the peer's response.
+pub(super) struct Request<M, R, C> {
+    connection: Arc<Connection>,
+    name: Name,
+    codec: C,
+    call: PhantomData<fn(M) -> R>,
+}
+
+impl<M, R, C> Request<M, R, C> {
+    pub(super) fn new(connection: Arc<Connection>, name: Name, codec: C) -> Self {
+        Self {
+            connection,
+            name,
+            codec,
+            call: PhantomData,
+        }
+    }
+}
+
+impl<M, R, C> RemoteSink<Call<M, R>> for Request<M, R, C>
+where
+    M: Message,
+    R: Message,
+    C: Codec<M> + Codec<R>,
+{
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    fn send(&self, call: Call<M, R>) -> Result<(), DeliverError<Call<M, R>>> {
+        if self.connection.is_closed() {
+            return Err(DeliverError::Closed(call));
+        }
+        let payload = encode(&self.codec, &self.name, call.message());
+        // The call is dropped, failing with `NoReply`, if the connection closes
+        // before its response arrives.
+        let slot: Slot<Call<M, R>> = Arc::new(Mutex::new(Some(call)));
+        let pending = slot.clone();
+        let codec = self.codec.clone();
+        let sent = self.connection.request(
+            |id| Packet::Request {
+                id,
+                name: self.name.clone(),
+                payload,
+            },
+            Box::new(move |response| {
+                let call = pending.lock().unwrap().take();
+                if let (Some(call), Response::Reply(Some(payload))) = (call, response)
+                    && let Ok(response) = Codec::<R>::decode(&codec, payload)
+                {
+                    call.reply(response).ok();
+                }
+            }),
+        );
+        if sent {
+            return Ok(());
+        }
+        let call = slot.lock().unwrap().take();
+        Err(DeliverError::Closed(
+            call.expect("rejected call was taken from its slot"),
+        ))
+    }
+}
+
+/// Encodes a message for `name`, before anything is queued on the connection.
+fn encode<M, C: Codec<M>>(codec: &C, name: &Name, message: &M) -> Vec<u8> {
+    let payload = match codec.encode(message) {
+        Ok(payload) => payload,
+        Err(error) => panic!("failed to encode a remote message: {error}"),
+    };
+    assert!(
+        fits(Some(name), &payload),
+        "remote message of {} bytes does not fit in a packet",
+        payload.len()
+    );
+    payload
+}
@@ -0,0 +1,73 @@
+//! Message passing between clusters over TCP or Unix sockets.
+//!
+//! A cluster [exposes](Cluster::expose) brokers under a name and
+//! [listens](Cluster::listen) for peers. A peer connects with a
+//! [`RemoteNode`], looks the names up, and talks to them through
+//! [`RemoteBroker`]s:
+//!
+//! ```rust,no_run
+//! use compio_actor::{Broker, Call, Cluster, remote::RemoteNode};
+//! use compio_io::framed::codec::serde_json::SerdeJsonCodec;
+//! use compio_net::{TcpListener, TcpStream};
+//!
+//! # async fn example(cluster: Cluster, add: Broker<u32>, total: Broker<Call<(), u32>>) -> std::io::Result<()> {
+//! cluster.expose("add", add, SerdeJsonCodec::new());
+//! cluster.expose_call("total", total, SerdeJsonCodec::new());
+//! let listener = TcpListener::bind("127.0.0.1:4000").await?;
+//! compio_runtime::spawn(async move { cluster.listen(listener).await }).detach();
+//!
+//! let node = RemoteNode::connect(TcpStream::connect("127.0.0.1:4000").await?);
+//! let add = node.lookup::<u32, _>("add", SerdeJsonCodec::new()).await?.unwrap();
+//! let total = node
+//!     .lookup_call::<(), u32, _>("total", SerdeJsonCodec::new())
+//!     .await?
+//!     .unwrap();
+//! add.send(2).unwrap();
+//! assert_eq!(total.call(()).await.unwrap(), 2);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Each packet is framed by a [`LengthDelimited`] prefix, and messages and
+//! replies are encoded with the [`Codec`] given for each name. A peer that
+//! sends a packet longer than 8 MiB is disconnected, so a [`RemoteBroker`]
+//! panics on a larger message instead of sending it, and a larger reply fails
+//! its call with [`CallError::NoReply`].
+//!
+//! [`CallError::NoReply`]: crate::mailbox::CallError::NoReply
+//!
+//! [`LengthDelimited`]: compio_io::framed::frame::LengthDelimited
+
+mod broker;
+mod node;
+mod packet;
+mod server;
+
+#[doc(inline)]
+pub use broker::RemoteBroker;
+use compio_io::{AsyncRead, AsyncWrite, util::Splittable};
+#[doc(inline)]
+pub use node::RemoteNode;
+pub(crate) use server::Exposed;
+#[doc(inline)]
+pub use server::Listener;
+
+#[doc(inline)]
+pub use crate::codec::Codec;
+use crate::codec::invalid_data;
+
+#[cfg(doc)]
+use crate::Cluster;
+
+/// A connected stream that can be split into halves, such as a `TcpStream` or
+/// a `UnixStream`.
+pub trait Transport:
+    Splittable<ReadHalf: AsyncRead + Unpin + 'static, WriteHalf: AsyncWrite + Unpin + 'static> + 'static
+{
+}
+
+impl<S> Transport for S where
+    S: Splittable<ReadHalf: AsyncRead + Unpin + 'static, WriteHalf: AsyncWrite + Unpin + 'static>
+        + 'static
+{
+}
@@ -0,0 +1,243 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt, io, mem,
+    sync::{Arc, Mutex, Weak},
+};
+
+use compio_io::AsyncRead;
+use futures_channel::oneshot;
+use futures_util::StreamExt;
+
+use super::{
+    Codec, RemoteBroker, Transport,
+    broker::{Cast, Request},
+    packet::{Exposure, Packet, read_packets, write_packets},
+};
+use crate::{Call, Message, mailbox::Name};
+
+/// A connection to a cluster [listening](crate::Cluster::listen) for peers.
+///
+/// The connection is closed once the node and every [`RemoteBroker`] looked up
+/// through it are dropped, or when the peer goes away. Brokers then reject
+/// messages with [`DeliverError::Closed`](crate::mailbox::DeliverError::Closed).
+#[derive(Clone)]
+pub struct RemoteNode {
+    connection: Arc<Connection>,
+}
+
+impl RemoteNode {
+    /// Starts talking to a peer over a connected stream, such as a
+    /// `TcpStream` or a `UnixStream`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when called outside a Compio runtime, which runs the connection.
+    pub fn connect(stream: impl Transport) -> Self {
+        let (reader, writer) = stream.split();
+        let (outgoing, packets) = flume::unbounded();
+        let connection = Arc::new(Connection {
+            outgoing,
+            pending: Mutex::default(),
+        });
+
+        let weak = Arc::downgrade(&connection);
+        compio_runtime::spawn(async move {
+            write_packets(writer, packets).await.ok();
+            if let Some(connection) = weak.upgrade() {
+                connection.close();
+            }
+        })
+        .detach();
+        compio_runtime::spawn(read_responses(reader, Arc::downgrade(&connection))).detach();
+        Self { connection }
+    }
+
+    /// Looks up an actor exposed with [`Cluster::expose`] on the peer.
+    ///
+    /// Returns `None` if nothing is exposed under `name`, and an error if the
+    /// connection is closed, or if the name is exposed with
+    /// [`Cluster::expose_call`] instead. The codec must match the one the
+    /// actor was exposed with.
+    ///
+    /// [`Cluster::expose`]: crate::Cluster::expose
+    /// [`Cluster::expose_call`]: crate::Cluster::expose_call
+    pub async fn lookup<M, C>(
+        &self,
+        name: impl Into<Cow<'static, str>>,
+        codec: C,
+    ) -> io::Result<Option<RemoteBroker<M>>>
+    where
+        M: Message,
+        C: Codec<M>,
+    {
+        let name = Name::from(name.into());
+        Ok(self
+            .find(&name, Exposure::Cast)
+            .await?
+            .then(|| RemoteBroker::new(Cast::new(self.connection.clone(), name, codec))))
+    }
+
+    /// Looks up an actor exposed with [`Cluster::expose_call`] on the peer.
+    ///
+    /// See [`lookup`](Self::lookup). It's an error if the name is exposed with
+    /// [`Cluster::expose`] instead.
+    ///
+    /// [`Cluster::expose`]: crate::Cluster::expose
+    ///
+    /// [`Cluster::expose_call`]: crate::Cluster::expose_call
+    pub async fn lookup_call<M, R, C>(
+        &self,
+        name: impl Into<Cow<'static, str>>,
+        codec: C,
+    ) -> io::Result<Option<RemoteBroker<Call<M, R>>>>
+    where
+        M: Message,
+        R: Message,
+        C: Codec<M> + Codec<R>,
+    {
+        let name = Name::from(name.into());
+        Ok(self
+            .find(&name, Exposure::Call)
+            .await?
+            .then(|| RemoteBroker::new(Request::new(self.connection.clone(), name, codec))))
+    }
+
+    /// Returns whether the connection is closed.
+    pub fn is_closed(&self) -> bool {
+        self.connection.is_closed()
+    }
+
+    /// Returns whether `name` is exposed, failing if it's exposed otherwise
+    /// than `expected`.
+    async fn find(&self, name: &Name, expected: Exposure) -> io::Result<bool> {
+        let (sender, receiver) = oneshot::channel();
+        let sent = self.connection.request(
+            |id| Packet::Lookup {
+                id,
+                name: name.clone(),
+            },
+            Box::new(move |response| {
+                if let Response::Found(exposure) = response {
+                    sender.send(exposure).ok();
+                }
+            }),
+        );
+        if !sent {
+            return Err(not_connected());
+        }
+        match receiver.await.map_err(|_| not_connected())? {
+            Some(exposure) if exposure != expected => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                match exposure {
+                    Exposure::Cast => "remote actor is exposed for casts, not calls",
+                    Exposure::Call => "remote actor is exposed for calls, not casts",
+                },
+            )),
+            exposure => Ok(exposure.is_some()),
+        }
+    }
+}
+
+impl fmt::Debug for RemoteNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteNode")
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+fn not_connected() -> io::Error {
+    io::Error::new(io::ErrorKind::NotConnected, "remote node is disconnected")
+}
+
+/// The answer to a request, matched to it by id.
+pub(super) enum Response {
+    Found(Option<Exposure>),
+    Reply(Option<Vec<u8>>),
+}
+
+/// Runs when the response to a request arrives, or is dropped if the
+/// connection closes first.
+pub(super) type Respond = Box<dyn FnOnce(Response) + Send>;
+
+pub(super) struct Connection {
+    outgoing: flume::Sender<Packet>,
+    pending: Mutex<Pending>,
+}
+
+#[derive(Default)]
+struct Pending {
+    next_id: u64,
+    responses: HashMap<u64, Respond>,
+    closed: bool,
+}
+
+impl Connection {
+    pub(super) fn is_closed(&self) -> bool {
+        self.pending.lock().unwrap().closed || self.outgoing.is_disconnected()
+    }
+
+    /// Queues a packet, returning whether the connection accepted it.
+    pub(super) fn send(&self, packet: Packet) -> bool {
+        !self.is_closed() && self.outgoing.send(packet).is_ok()
+    }
+
+    /// Queues the packet built for a new request id, and calls `respond` with
+    /// its response.
+    pub(super) fn request(&self, packet: impl FnOnce(u64) -> Packet, respond: Respond) -> bool {
+        let id = {
+            let mut pending = self.pending.lock().unwrap();
+            if pending.closed {
+                return false;
+            }
+            let id = pending.next_id;
+            pending.next_id = id.wrapping_add(1);
+            pending.responses.insert(id, respond);
+            id
+        };
+        if self.outgoing.send(packet(id)).is_err() {
+            self.pending.lock().unwrap().responses.remove(&id);
+            return false;
+        }
+        true
+    }
+
+    fn respond(&self, id: u64, response: Response) {
+        let respond = self.pending.lock().unwrap().responses.remove(&id);
+        if let Some(respond) = respond {
+            respond(response);
+        }
+    }
+
+    /// Rejects new packets and drops the requests still waiting for a
+    /// response.
+    fn close(&self) {
+        let responses = {
+            let mut pending = self.pending.lock().unwrap();
+            pending.closed = true;
+            mem::take(&mut pending.responses)
+        };
+        drop(responses);
+    }
+}
+
+async fn read_responses<R>(reader: R, connection: Weak<Connection>)
+where
+    R: AsyncRead + Unpin + 'static,
+{
+    let mut packets = std::pin::pin!(read_packets(reader));
+    while let Some(Ok(packet)) = packets.next().await {
+        let Some(connection) = connection.upgrade() else {
+            return;
+        };
+        match packet {
+            Packet::Found { id, exposure } => connection.respond(id, Response::Found(exposure)),
+            Packet::Reply { id, payload } => connection.respond(id, Response::Reply(payload)),
+            Packet::Lookup { .. } | Packet::Cast { .. } | Packet::Request { .. } => break,
+        }
+    }
+    if let Some(connection) = connection.upgrade() {
+        connection.close();
+    }
+}
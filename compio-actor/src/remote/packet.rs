@@ -0,0 +1,195 @@
+use std::{borrow::Cow, io};
+
+use compio_buf::{IoBuf, Slice};
+use compio_io::{
+    AsyncRead, AsyncWrite,
+    framed::{
+        Framed,
+        codec::{Decoder, Encoder},
+        frame::LengthDelimited,
+    },
+};
+use futures_util::{SinkExt, Stream};
+
+use super::invalid_data;
+use crate::mailbox::Name;
+
+const LOOKUP: u8 = 0;
+const FOUND: u8 = 1;
+const MISSING: u8 = 2;
+const CAST: u8 = 3;
+const REQUEST: u8 = 4;
+const REPLY: u8 = 5;
+const NO_REPLY: u8 = 6;
+const FOUND_CALL: u8 = 7;
+
+/// The longest packet a node accepts from a peer, 8 MiB.
+const MAX_PACKET_LENGTH: usize = 8 * 1024 * 1024;
+
+/// Returns whether a packet carrying `payload`, and `name` if it has one, is
+/// short enough for the peer to accept it.
+///
+/// A longer one would make the peer drop the connection.
+pub(super) fn fits(name: Option<&Name>, payload: &[u8]) -> bool {
+    let header = 1 + 8 + name.map_or(0, |name| 2 + name.as_str().len());
+    header + payload.len() <= MAX_PACKET_LENGTH
+}
+
+/// How a name is exposed, which decides the brokers a peer can look it up as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Exposure {
+    /// With [`Cluster::expose`](crate::Cluster::expose).
+    Cast,
+    /// With [`Cluster::expose_call`](crate::Cluster::expose_call).
+    Call,
+}
+
+/// A unit of the remote protocol.
+///
+/// Requests and their responses share an `id` chosen by the requesting side.
+pub(super) enum Packet {
+    /// Asks whether a name is exposed.
+    Lookup { id: u64, name: Name },
+    /// Answers a lookup, with `None` when the name is not exposed.
+    Found { id: u64, exposure: Option<Exposure> },
+    /// Delivers a message without waiting for a reply.
+    Cast { name: Name, payload: Vec<u8> },
+    /// Delivers a call.
+    Request {
+        id: u64,
+        name: Name,
+        payload: Vec<u8>,
+    },
+    /// Answers a call, with `None` when it was not delivered or answered.
+    Reply { id: u64, payload: Option<Vec<u8>> },
+}
+
+/// Lays a packet out as a tag, an id, a name, then the payload.
+struct PacketCodec;
+
+impl Encoder<Packet, Vec<u8>> for PacketCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, packet: Packet, buf: &mut Vec<u8>) -> Result<(), Self::Error> {
+        let (tag, id, name, payload) = match packet {
+            Packet::Lookup { id, name } => (LOOKUP, id, Some(name), None),
+            Packet::Found { id, exposure } => {
+                let tag = match exposure {
+                    Some(Exposure::Cast) => FOUND,
+                    Some(Exposure::Call) => FOUND_CALL,
+                    None => MISSING,
+                };
+                (tag, id, None, None)
+            }
+            Packet::Cast { name, payload } => (CAST, 0, Some(name), Some(payload)),
+            Packet::Request { id, name, payload } => (REQUEST, id, Some(name), Some(payload)),
+            Packet::Reply {
+                id,
+                payload: Some(payload),
+            } => (REPLY, id, None, Some(payload)),
+            Packet::Reply { id, payload: None } => (NO_REPLY, id, None, None),
+        };
+        buf.push(tag);
+        buf.extend_from_slice(&id.to_be_bytes());
+        if let Some(name) = name {
+            let len = u16::try_from(name.as_str().len())
+                .map_err(|_| invalid_data("remote actor name is too long"))?;
+            buf.extend_from_slice(&len.to_be_bytes());
+            buf.extend_from_slice(name.as_str().as_bytes());
+        }
+        if let Some(payload) = payload {
+            buf.extend_from_slice(&payload);
+        }
+        Ok(())
+    }
+}
+
+impl<B: IoBuf> Decoder<Packet, B> for PacketCodec {
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &Slice<B>) -> Result<Packet, Self::Error> {
+        let mut reader = PacketReader(buf.as_init());
+        let tag = reader.take(1)?[0];
+        let id = u64::from_be_bytes(reader.take(8)?.try_into().unwrap());
+        Ok(match tag {
+            LOOKUP => Packet::Lookup {
+                id,
+                name: reader.name()?,
+            },
+            FOUND => Packet::Found {
+                id,
+                exposure: Some(Exposure::Cast),
+            },
+            FOUND_CALL => Packet::Found {
+                id,
+                exposure: Some(Exposure::Call),
+            },
+            MISSING => Packet::Found { id, exposure: None },
+            CAST => Packet::Cast {
+                name: reader.name()?,
+                payload: reader.rest(),
+            },
+            REQUEST => Packet::Request {
+                id,
+                name: reader.name()?,
+                payload: reader.rest(),
+            },
+            REPLY => Packet::Reply {
+                id,
+                payload: Some(reader.rest()),
+            },
+            NO_REPLY => Packet::Reply { id, payload: None },
+            _ => return Err(invalid_data("unknown remote packet")),
+        })
+    }
+}
+
+struct PacketReader<'a>(&'a [u8]);
+
+impl<'a> PacketReader<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(invalid_data("truncated remote packet"));
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn name(&mut self) -> io::Result<Name> {
+        let len = u16::from_be_bytes(self.take(2)?.try_into().unwrap());
+        let name = std::str::from_utf8(self.take(len.into())?)
+            .map_err(|_| invalid_data("remote actor name is not UTF-8"))?;
+        Ok(Name::from(Cow::Owned(name.to_owned())))
+    }
+
+    fn rest(self) -> Vec<u8> {
+        self.0.to_vec()
+    }
+}
+
+/// Reads packets until the peer closes the stream or sends an invalid one.
+pub(super) fn read_packets<R>(reader: R) -> impl Stream<Item = io::Result<Packet>>
+where
+    R: AsyncRead + Unpin + 'static,
+{
+    let framer = LengthDelimited::new().set_max_frame_length(MAX_PACKET_LENGTH);
+    Framed::symmetric::<Packet>(PacketCodec, framer).with_reader(reader)
+}
+
+/// Writes packets until every sender is dropped, then shuts the stream down.
+pub(super) async fn write_packets<W>(writer: W, packets: flume::Receiver<Packet>) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin + 'static,
+{
+    let mut sink =
+        Framed::symmetric::<Packet>(PacketCodec, LengthDelimited::new()).with_writer(writer);
+    while let Ok(packet) = packets.recv_async().await {
+        sink.feed(packet).await?;
+        while let Ok(packet) = packets.try_recv() {
+            sink.feed(packet).await?;
+        }
+        sink.flush().await?;
+    }
+    sink.close().await
+}
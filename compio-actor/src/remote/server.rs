@@ -0,0 +1,259 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    future::Future,
+    io,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+};
+
+use compio_net::{TcpListener, TcpStream, UnixListener, UnixStream};
+use futures_util::{FutureExt, StreamExt, future::LocalBoxFuture};
+
+use super::{
+    Codec, Transport,
+    packet::{Exposure, Packet, fits, read_packets, write_packets},
+};
+use crate::{
+    Broker, Call, Cluster, Message,
+    mailbox::{Name, call_with},
+};
+
+/// A listener accepting the peers of a [`Cluster`].
+///
+/// See [`Cluster::listen`].
+pub trait Listener {
+    /// A connected stream.
+    type Stream: Transport;
+
+    /// Waits for a peer to connect.
+    fn accept(&self) -> impl Future<Output = io::Result<Self::Stream>>;
+}
+
+impl Listener for TcpListener {
+    type Stream = TcpStream;
+
+    async fn accept(&self) -> io::Result<Self::Stream> {
+        TcpListener::accept(self).await.map(|(stream, _)| stream)
+    }
+}
+
+impl Listener for UnixListener {
+    type Stream = UnixStream;
+
+    async fn accept(&self) -> io::Result<Self::Stream> {
+        UnixListener::accept(self).await.map(|(stream, _)| stream)
+    }
+}
+
+/// The brokers a cluster exposes to its peers, by name.
+#[derive(Default)]
+pub(crate) struct Exposed {
+    endpoints: Mutex<HashMap<Name, Arc<dyn Endpoint>>>,
+}
+
+impl Exposed {
+    fn insert(&self, name: Name, endpoint: Arc<dyn Endpoint>) -> bool {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        if endpoints.contains_key(name.as_str()) {
+            return false;
+        }
+        endpoints.insert(name, endpoint);
+        true
+    }
+
+    fn get(&self, name: &str) -> Option<Arc<dyn Endpoint>> {
+        self.endpoints.lock().unwrap().get(name).cloned()
+    }
+}
+
+/// Delivers the encoded messages received for an exposed name.
+trait Endpoint: Send + Sync {
+    fn exposure(&self) -> Exposure;
+
+    fn cast(&self, payload: Vec<u8>);
+
+    /// Resolves to the encoded reply, or `None` if there is none.
+    fn call(&self, payload: Vec<u8>) -> LocalBoxFuture<'static, Option<Vec<u8>>>;
+}
+
+struct CastEndpoint<M: Message, C> {
+    broker: Broker<M>,
+    codec: C,
+}
+
+impl<M, C> Endpoint for CastEndpoint<M, C>
+where
+    M: Message,
+    C: Codec<M>,
+{
+    fn exposure(&self) -> Exposure {
+        Exposure::Cast
+    }
+
+    fn cast(&self, payload: Vec<u8>) {
+        if let Ok(message) = self.codec.decode(payload) {
+            self.broker.send(message).ok();
+        }
+    }
+
+    fn call(&self, _payload: Vec<u8>) -> LocalBoxFuture<'static, Option<Vec<u8>>> {
+        async { None }.boxed_local()
+    }
+}
+
+struct CallEndpoint<M: Message, R: Message, C> {
+    broker: Broker<Call<M, R>>,
+    codec: C,
+    reply: PhantomData<fn() -> R>,
+}
+
+impl<M, R, C> Endpoint for CallEndpoint<M, R, C>
+where
+    M: Message,
+    R: Message,
+    C: Codec<M> + Codec<R>,
+{
+    fn exposure(&self) -> Exposure {
+        Exposure::Call
+    }
+
+    fn cast(&self, _payload: Vec<u8>) {}
+
+    fn call(&self, payload: Vec<u8>) -> LocalBoxFuture<'static, Option<Vec<u8>>> {
+        let broker = self.broker.clone();
+        let codec = self.codec.clone();
+        async move {
+            let message = Codec::<M>::decode(&codec, payload).ok()?;
+            let reply = call_with(message, |call| broker.send(call)).await.ok()?;
+            Codec::<R>::encode(&codec, &reply).ok()
+        }
+        .boxed_local()
+    }
+}
+
+impl Cluster {
+    /// Exposes a broker to the peers of this cluster under `name`, decoding
+    /// the messages they send with `codec`.
+    ///
+    /// Returns `false` if another broker is exposed under that name. Peers
+    /// find it with [`RemoteNode::lookup`](super::RemoteNode::lookup).
+    pub fn expose<M, C>(
+        &self,
+        name: impl Into<Cow<'static, str>>,
+        broker: Broker<M>,
+        codec: C,
+    ) -> bool
+    where
+        M: Message,
+        C: Codec<M>,
+    {
+        self.exposed().insert(
+            Name::from(name.into()),
+            Arc::new(CastEndpoint { broker, codec }),
+        )
+    }
+
+    /// Exposes a broker answering calls to the peers of this cluster under
+    /// `name`, encoding its replies with `codec`.
+    ///
+    /// Returns `false` if another broker is exposed under that name. Peers
+    /// find it with [`RemoteNode::lookup_call`](super::RemoteNode::lookup_call).
+    pub fn expose_call<M, R, C>(
+        &self,
+        name: impl Into<Cow<'static, str>>,
+        broker: Broker<Call<M, R>>,
+        codec: C,
+    ) -> bool
+    where
+        M: Message,
+        R: Message,
+        C: Codec<M> + Codec<R>,
+    {
+        let endpoint = CallEndpoint {
+            broker,
+            codec,
+            reply: PhantomData,
+        };
+        self.exposed()
+            .insert(Name::from(name.into()), Arc::new(endpoint))
+    }
+
+    /// Stops exposing the broker under `name`, returning whether there was
+    /// one.
+    pub fn unexpose(&self, name: &str) -> bool {
+        self.exposed()
+            .endpoints
+            .lock()
+            .unwrap()
+            .remove(name)
+            .is_some()
+    }
+
+    /// Accepts peers until the listener fails, serving each of them like
+    /// [`serve`](Self::serve).
+    ///
+    /// Drop the future to stop accepting peers; the ones already connected
+    /// stay connected.
+    ///
+    /// # Panics
+    ///
+    /// Panics when polled outside a Compio runtime.
+    pub async fn listen<L: Listener>(&self, listener: L) -> io::Result<()> {
+        loop {
+            let stream = listener.accept().await?;
+            compio_runtime::spawn(self.clone().serve(stream)).detach();
+        }
+    }
+
+    /// Serves one peer over a connected stream until it disconnects.
+    ///
+    /// The peer can look up, send to, and call the exposed brokers.
+    ///
+    /// # Panics
+    ///
+    /// Panics when polled outside a Compio runtime.
+    pub async fn serve(self, stream: impl Transport) {
+        let (reader, writer) = stream.split();
+        let (outgoing, packets) = flume::unbounded();
+        let writer = compio_runtime::spawn(write_packets(writer, packets));
+        let mut packets = std::pin::pin!(read_packets(reader));
+        while let Some(Ok(packet)) = packets.next().await {
+            match packet {
+                Packet::Lookup { id, name } => {
+                    let exposure = self
+                        .exposed()
+                        .get(name.as_str())
+                        .map(|endpoint| endpoint.exposure());
+                    outgoing.send(Packet::Found { id, exposure }).ok();
+                }
+                Packet::Cast { name, payload } => {
+                    if let Some(endpoint) = self.exposed().get(name.as_str()) {
+                        endpoint.cast(payload);
+                    }
+                }
+                Packet::Request { id, name, payload } => {
+                    let reply = self
+                        .exposed()
+                        .get(name.as_str())
+                        .map(|endpoint| endpoint.call(payload));
+                    let outgoing = outgoing.clone();
+                    compio_runtime::spawn(async move {
+                        let payload = match reply {
+                            Some(reply) => reply.await,
+                            None => None,
+                        };
+                        // A reply too long for the peer fails this call only,
+                        // instead of the connection.
+                        let payload = payload.filter(|payload| fits(None, payload));
+                        outgoing.send(Packet::Reply { id, payload }).ok();
+                    })
+                    .detach();
+                }
+                Packet::Found { .. } | Packet::Reply { .. } => break,
+            }
+        }
+        drop(outgoing);
+        writer.await.ok();
+    }
+}
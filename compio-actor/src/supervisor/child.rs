@@ -0,0 +1,178 @@
+use std::{borrow::Cow, fmt, future::Future, num::NonZeroUsize, pin::Pin};
+
+use crate::{Actor, ActorExit, Cluster, mailbox::DEFAULT_MAILBOX_CAPACITY};
+
+/// When a [`Supervisor`](super::Supervisor) restarts a child that exited.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Restart {
+    /// Always restart the child.
+    #[default]
+    Permanent,
+    /// Restart the child only if it failed.
+    Transient,
+    /// Never restart the child.
+    Temporary,
+}
+
+impl Restart {
+    pub(super) fn applies(self, failed: bool) -> bool {
+        match self {
+            Self::Permanent => true,
+            Self::Transient => failed,
+            Self::Temporary => false,
+        }
+    }
+}
+
+/// How a [`Supervisor`](super::Supervisor) starts one of its children.
+///
+/// The factory and arguments are cloned for every start, so that the child can
+/// be restarted from scratch.
+pub struct ChildSpec {
+    id: Cow<'static, str>,
+    restart: Restart,
+    name: Option<Cow<'static, str>>,
+    capacity: NonZeroUsize,
+    start: Box<dyn StartChild>,
+}
+
+impl ChildSpec {
+    /// Describes a child identified by `id` within its supervisor.
+    pub fn new<A, F>(id: impl Into<Cow<'static, str>>, factory: F, arguments: A::Arguments) -> Self
+    where
+        A: Actor,
+        A::Arguments: Clone,
+        F: FnOnce() -> A + Clone + Send + 'static,
+    {
+        Self {
+            id: id.into(),
+            restart: Restart::default(),
+            name: None,
+            capacity: DEFAULT_MAILBOX_CAPACITY,
+            start: Box::new(Start::<A, F> { factory, arguments }),
+        }
+    }
+
+    /// Sets when the child is restarted. The default is
+    /// [`Restart::Permanent`].
+    pub fn with_restart(mut self, restart: Restart) -> Self {
+        self.restart = restart;
+        self
+    }
+
+    /// Registers each incarnation of the child under `name`.
+    pub fn with_name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets the child's bounded mailbox capacity.
+    pub fn with_capacity(mut self, capacity: NonZeroUsize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Returns the child's identifier within its supervisor.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns when the child is restarted.
+    pub fn restart(&self) -> Restart {
+        self.restart
+    }
+
+    /// Starts the child, reporting it as restarted `restarts` times.
+    pub(super) fn start(&self, cluster: &Cluster, restarts: u64) -> StartFuture {
+        self.start
+            .start(cluster, self.name.clone(), self.capacity, restarts)
+    }
+}
+
+impl Clone for ChildSpec {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id.clone(),
+            restart: self.restart,
+            name: self.name.clone(),
+            capacity: self.capacity,
+            start: self.start.clone_box(),
+        }
+    }
+}
+
+impl fmt::Debug for ChildSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChildSpec")
+            .field("id", &self.id)
+            .field("restart", &self.restart)
+            .field("name", &self.name)
+            .field("capacity", &self.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A started child, with its actor type erased.
+pub(super) struct Running {
+    /// Requests a graceful stop.
+    pub(super) stop: Box<dyn Fn() -> bool>,
+    /// Resolves once the child exited, to whether it failed.
+    pub(super) exit: Pin<Box<dyn Future<Output = bool>>>,
+}
+
+pub(super) type StartFuture = Pin<Box<dyn Future<Output = Option<Running>>>>;
+
+trait StartChild: Send {
+    fn start(
+        &self,
+        cluster: &Cluster,
+        name: Option<Cow<'static, str>>,
+        capacity: NonZeroUsize,
+        restarts: u64,
+    ) -> StartFuture;
+
+    fn clone_box(&self) -> Box<dyn StartChild>;
+}
+
+struct Start<A: Actor, F> {
+    factory: F,
+    arguments: A::Arguments,
+}
+
+impl<A, F> StartChild for Start<A, F>
+where
+    A: Actor,
+    A::Arguments: Clone,
+    F: FnOnce() -> A + Clone + Send + 'static,
+{
+    fn start(
+        &self,
+        cluster: &Cluster,
+        name: Option<Cow<'static, str>>,
+        capacity: NonZeroUsize,
+        restarts: u64,
+    ) -> StartFuture {
+        let mut spawn = cluster
+            .spawn(self.factory.clone(), self.arguments.clone())
+            .with_capacity(capacity)
+            .with_restarts(restarts);
+        if let Some(name) = name {
+            spawn = spawn.with_name(name);
+        }
+        let spawn = spawn.into_future();
+        Box::pin(async move {
+            let (mailbox, handle) = spawn.await.ok()?;
+            Some(Running {
+                stop: Box::new(move || mailbox.stop()),
+                exit: Box::pin(async move { !matches!(handle.await, Ok(ActorExit::Stopped)) }),
+            })
+        })
+    }
+
+    fn clone_box(&self) -> Box<dyn StartChild> {
+        Box::new(Self {
+            factory: self.factory.clone(),
+            arguments: self.arguments.clone(),
+        })
+    }
+}
@@ -0,0 +1,25 @@
+use std::{borrow::Cow, error::Error, fmt};
+
+/// The reason a [`Supervisor`](super::Supervisor) failed.
+///
+/// A failed supervisor has stopped all of its children, and reports the
+/// failure to its own parent like any other actor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SupervisorError {
+    /// The child with this id failed to start with the supervisor.
+    Start(Cow<'static, str>),
+    /// Children were restarted more often than the supervisor's intensity
+    /// allows.
+    TooManyRestarts,
+}
+
+impl fmt::Display for SupervisorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Start(id) => write!(f, "supervised child {id:?} failed to start"),
+            Self::TooManyRestarts => f.write_str("supervisor exceeded its restart intensity"),
+        }
+    }
+}
+
+impl Error for SupervisorError {}
@@ -1,7 +1,45 @@
-//! Actor lifecycle notifications.
+//! Actor lifecycle notifications and restart strategies.
 //!
-//! A supervisor is an ordinary actor that handles [`SupervisionEvent`] for a
-//! child type. Attach it while spawning the child:
+//! The built-in [`Supervisor`] actor starts the children described by a
+//! [`SupervisorSpec`] and restarts them according to a [`Strategy`]:
+//!
+//! ```rust
+//! # use std::convert::Infallible;
+//! use std::time::Duration;
+//!
+//! use compio_actor::{
+//!     Actor, Cluster, Mailbox,
+//!     supervisor::{ChildSpec, Restart, Strategy, Supervisor, SupervisorSpec},
+//! };
+//!
+//! struct Worker;
+//! # impl Actor for Worker {
+//! #     type Arguments = ();
+//! #     type Error = Infallible;
+//! #     type State = ();
+//! #
+//! #     async fn pre_start(
+//! #         &self,
+//! #         _myself: &Mailbox<Self>,
+//! #         (): Self::Arguments,
+//! #     ) -> Result<Self::State, Self::Error> {
+//! #         Ok(())
+//! #     }
+//! # }
+//!
+//! # async fn example() -> std::io::Result<()> {
+//! let cluster = Cluster::new()?;
+//! let spec = SupervisorSpec::new(Strategy::OneForAll)
+//!     .with_intensity(3, Duration::from_secs(10))
+//!     .with_child(ChildSpec::new("worker", || Worker, ()).with_name("worker"))
+//!     .with_child(ChildSpec::new("helper", || Worker, ()).with_restart(Restart::Transient));
+//! let (_supervisor, _handle) = cluster.spawn(|| Supervisor, spec).await.unwrap();
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Any actor can also supervise others by hand: it handles
+//! [`SupervisionEvent`] for a child type. Attach it while spawning the child:
 //!
 //! ```rust
 //! use std::{convert::Infallible, marker::PhantomData};
@@ -76,8 +114,22 @@
 //! # }
 //! ```
 
+mod child;
+mod error;
+mod strategy;
+mod tree;
+
 use std::fmt;
 
+#[doc(inline)]
+pub use child::{ChildSpec, Restart};
+#[doc(inline)]
+pub use error::SupervisorError;
+#[doc(inline)]
+pub use strategy::Strategy;
+#[doc(inline)]
+pub use tree::{ChildExited, Supervisor, SupervisorSpec, SupervisorState};
+
 use crate::{Actor, Broker, Handler, Mailbox};
 
 /// A lifecycle event emitted by a supervised actor.
@@ -86,7 +138,8 @@ pub enum SupervisionEvent<A: Actor> {
     ActorStarted(Mailbox<A>),
     /// The actor stopped normally.
     ActorTerminated(Mailbox<A>),
-    /// The actor exited after a lifecycle or handler error.
+    /// The actor exited after a lifecycle or handler error, or was stopped by
+    /// a linked actor.
     ActorFailed(Mailbox<A>),
 }
 
@@ -127,19 +180,19 @@ impl<A: Actor> Supervision<A> {
 
     pub(crate) fn started(&self, actor: &Mailbox<A>) {
         self.broker
-            .send(SupervisionEvent::ActorStarted(actor.clone()))
+            .send_control(SupervisionEvent::ActorStarted(actor.clone()))
             .ok();
     }
 
     pub(crate) fn terminated(&self, actor: &Mailbox<A>) {
         self.broker
-            .send(SupervisionEvent::ActorTerminated(actor.clone()))
+            .send_control(SupervisionEvent::ActorTerminated(actor.clone()))
             .ok();
     }
 
     pub(crate) fn failed(&self, actor: &Mailbox<A>) {
         self.broker
-            .send(SupervisionEvent::ActorFailed(actor.clone()))
+            .send_control(SupervisionEvent::ActorFailed(actor.clone()))
             .ok();
     }
 }
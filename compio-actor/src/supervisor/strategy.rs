@@ -0,0 +1,60 @@
+use std::{
+    collections::VecDeque,
+    ops::Range,
+    time::{Duration, Instant},
+};
+
+/// Which children a [`Supervisor`](super::Supervisor) restarts when one of
+/// them exits and should be restarted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Strategy {
+    /// Restart only the child that exited.
+    #[default]
+    OneForOne,
+    /// Stop every other child, then restart all of them.
+    OneForAll,
+    /// Stop the children started after the one that exited, then restart it
+    /// and them.
+    RestForOne,
+}
+
+impl Strategy {
+    pub(super) fn affected(&self, child: usize, children: usize) -> Range<usize> {
+        match self {
+            Self::OneForOne => child..child + 1,
+            Self::OneForAll => 0..children,
+            Self::RestForOne => child..children,
+        }
+    }
+}
+
+/// The restarts of a supervisor within its intensity period.
+pub(super) struct Restarts {
+    max_restarts: usize,
+    period: Duration,
+    recent: VecDeque<Instant>,
+}
+
+impl Restarts {
+    pub(super) fn new(max_restarts: usize, period: Duration) -> Self {
+        Self {
+            max_restarts,
+            period,
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// Records a restart, returning whether it stays within the intensity.
+    pub(super) fn record(&mut self) -> bool {
+        let now = Instant::now();
+        while self
+            .recent
+            .front()
+            .is_some_and(|restart| now.duration_since(*restart) >= self.period)
+        {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(now);
+        self.recent.len() <= self.max_restarts
+    }
+}
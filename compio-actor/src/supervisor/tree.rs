@@ -0,0 +1,245 @@
+use std::{borrow::Cow, cell::RefCell, collections::VecDeque, ops::Range, rc::Rc, time::Duration};
+
+use compio_runtime::JoinHandle;
+
+use super::{ChildSpec, Restart, Strategy, SupervisorError, strategy::Restarts};
+use crate::{Actor, Cluster, Handler, Mailbox};
+
+/// The children of a [`Supervisor`] and how they are restarted.
+#[derive(Clone, Debug)]
+pub struct SupervisorSpec {
+    strategy: Strategy,
+    max_restarts: usize,
+    period: Duration,
+    children: Vec<ChildSpec>,
+}
+
+impl SupervisorSpec {
+    /// Creates a spec without children, restarting them with `strategy`.
+    ///
+    /// The default intensity allows one restart every five seconds.
+    pub fn new(strategy: Strategy) -> Self {
+        Self {
+            strategy,
+            max_restarts: 1,
+            period: Duration::from_secs(5),
+            children: Vec::new(),
+        }
+    }
+
+    /// Allows at most `max_restarts` restarts within any `period`. One more
+    /// restart fails the supervisor with [`SupervisorError::TooManyRestarts`].
+    pub fn with_intensity(mut self, max_restarts: usize, period: Duration) -> Self {
+        self.max_restarts = max_restarts;
+        self.period = period;
+        self
+    }
+
+    /// Adds a child, started after the ones added before it.
+    pub fn with_child(mut self, child: ChildSpec) -> Self {
+        self.children.push(child);
+        self
+    }
+}
+
+impl Default for SupervisorSpec {
+    fn default() -> Self {
+        Self::new(Strategy::default())
+    }
+}
+
+/// An actor that starts a list of children and restarts them as they exit.
+///
+/// Children are started in order by `pre_start`, which fails if any of them
+/// fails to start. When a child exits, its [`Restart`] type decides whether it
+/// should be restarted, and the [`Strategy`] which of its siblings are
+/// restarted along with it. Restarts beyond the spec's intensity fail the
+/// supervisor, escalating to its own parent. Stopping the supervisor stops its
+/// children in reverse order.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Supervisor;
+
+/// The state of a running [`Supervisor`].
+pub struct SupervisorState {
+    strategy: Strategy,
+    restarts: Restarts,
+    children: Vec<Child>,
+    exits: Rc<RefCell<VecDeque<Exited>>>,
+}
+
+struct Child {
+    spec: ChildSpec,
+    generation: u64,
+    /// The number of times the child was started.
+    starts: u64,
+    running: Option<Watched>,
+}
+
+struct Watched {
+    stop: Box<dyn Fn() -> bool>,
+    watcher: JoinHandle<()>,
+}
+
+struct Exited {
+    child: usize,
+    generation: u64,
+    failed: bool,
+}
+
+/// Wakes a supervisor to handle the exits of its children.
+///
+/// The exits themselves are queued in the supervisor's state, so that a single
+/// wake handles all the exits that happened since the last one.
+#[derive(Debug)]
+pub struct ChildExited(());
+
+impl SupervisorState {
+    /// Starts a child, watching for its exit.
+    async fn start(
+        &mut self,
+        cluster: &Cluster,
+        myself: &Mailbox<Supervisor>,
+        index: usize,
+    ) -> bool {
+        let child = &mut self.children[index];
+        child.generation += 1;
+        let restarts = child.starts;
+        child.starts += 1;
+        let Some(running) = child.spec.start(cluster, restarts).await else {
+            return false;
+        };
+        let exits = self.exits.clone();
+        let myself = myself.clone();
+        let generation = child.generation;
+        let watcher = compio_runtime::spawn(async move {
+            let failed = running.exit.await;
+            exits.borrow_mut().push_back(Exited {
+                child: index,
+                generation,
+                failed,
+            });
+            myself.send_control(ChildExited(())).ok();
+        });
+        child.running = Some(Watched {
+            stop: running.stop,
+            watcher,
+        });
+        true
+    }
+
+    /// Stops the running children in `range`, in reverse order, and waits for
+    /// them to exit.
+    async fn stop(&mut self, range: Range<usize>) {
+        for child in self.children[range].iter_mut().rev() {
+            if let Some(running) = child.running.take() {
+                child.generation += 1;
+                (running.stop)();
+                running.watcher.await.ok();
+            }
+        }
+    }
+
+    async fn restart(
+        &mut self,
+        cluster: &Cluster,
+        myself: &Mailbox<Supervisor>,
+        exited: Exited,
+    ) -> Result<(), SupervisorError> {
+        let child = &mut self.children[exited.child];
+        if child.generation != exited.generation {
+            return Ok(());
+        }
+        child.running = None;
+        if !child.spec.restart().applies(exited.failed) {
+            return Ok(());
+        }
+        if !self.restarts.record() {
+            return Err(SupervisorError::TooManyRestarts);
+        }
+
+        let affected = self.strategy.affected(exited.child, self.children.len());
+        let restarted = affected
+            .clone()
+            .filter(|&index| {
+                let child = &self.children[index];
+                index == exited.child
+                    || (child.running.is_some() && child.spec.restart() != Restart::Temporary)
+            })
+            .collect::<Vec<_>>();
+        self.stop(affected).await;
+        for index in restarted {
+            if !self.start(cluster, myself, index).await {
+                self.exits.borrow_mut().push_back(Exited {
+                    child: index,
+                    generation: self.children[index].generation,
+                    failed: true,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Actor for Supervisor {
+    type Arguments = SupervisorSpec;
+    type Error = SupervisorError;
+    type State = SupervisorState;
+
+    async fn pre_start(
+        &self,
+        myself: &Mailbox<Self>,
+        spec: Self::Arguments,
+    ) -> Result<Self::State, Self::Error> {
+        let mut state = SupervisorState {
+            strategy: spec.strategy,
+            restarts: Restarts::new(spec.max_restarts, spec.period),
+            children: spec
+                .children
+                .into_iter()
+                .map(|spec| Child {
+                    spec,
+                    generation: 0,
+                    starts: 0,
+                    running: None,
+                })
+                .collect(),
+            exits: Rc::default(),
+        };
+        let cluster = Cluster::current();
+        for index in 0..state.children.len() {
+            if !state.start(&cluster, myself, index).await {
+                state.stop(0..index).await;
+                let id = state.children[index].spec.id().to_owned();
+                return Err(SupervisorError::Start(Cow::Owned(id)));
+            }
+        }
+        Ok(state)
+    }
+
+    async fn pre_stop(
+        &self,
+        _myself: &Mailbox<Self>,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        state.stop(0..state.children.len()).await;
+        Ok(())
+    }
+}
+
+impl Handler<ChildExited> for Supervisor {
+    async fn handle(
+        &self,
+        myself: &Mailbox<Self>,
+        _exited: ChildExited,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        let cluster = Cluster::current();
+        loop {
+            let exited = state.exits.borrow_mut().pop_front();
+            let Some(exited) = exited else {
+                return Ok(());
+            };
+            state.restart(&cluster, myself, exited).await?;
+        }
+    }
+}
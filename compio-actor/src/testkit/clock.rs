@@ -0,0 +1,169 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+use futures_util::{FutureExt, pin_mut, select_biased};
+
+/// A clock that only moves when the test advances it.
+///
+/// Actors started with it, through [`TestSpawn::with_clock`] or
+/// [`Spawn::with_clock`], wait for this clock instead of the runtime's in
+/// [`send_after`] and [`send_interval`], and a cluster actor also in its
+/// [`idle_timeout`]. Clones share the same time.
+///
+/// [`TestSpawn::with_clock`]: super::TestSpawn::with_clock
+/// [`Spawn::with_clock`]: crate::cluster::Spawn::with_clock
+/// [`send_after`]: crate::Mailbox::send_after
+/// [`send_interval`]: crate::Mailbox::send_interval
+/// [`idle_timeout`]: crate::Actor::idle_timeout
+#[derive(Clone)]
+pub struct TestClock {
+    inner: Arc<Mutex<State>>,
+}
+
+struct State {
+    start: Instant,
+    now: Instant,
+    next_id: u64,
+    /// The deadline and waker of each pending sleep.
+    sleepers: HashMap<u64, (Instant, Waker)>,
+}
+
+impl TestClock {
+    /// Creates a clock stopped at the current time.
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            inner: Arc::new(Mutex::new(State {
+                start: now,
+                now,
+                next_id: 0,
+                sleepers: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Returns the clock's current time.
+    pub fn now(&self) -> Instant {
+        self.inner.lock().unwrap().now
+    }
+
+    /// Returns how far the clock has been advanced since it was created.
+    pub fn elapsed(&self) -> Duration {
+        let state = self.inner.lock().unwrap();
+        state.now - state.start
+    }
+
+    /// Moves the clock forward by `duration`, waking the timers that are due.
+    ///
+    /// The woken timers run once their runtime gets to them, so a test
+    /// usually yields or steps its actor afterwards.
+    pub fn advance(&self, duration: Duration) {
+        let mut woken = Vec::new();
+        {
+            let mut state = self.inner.lock().unwrap();
+            state.now += duration;
+            let now = state.now;
+            state.sleepers.retain(|_, (deadline, waker)| {
+                if *deadline <= now {
+                    woken.push(waker.clone());
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+        woken.into_iter().for_each(Waker::wake);
+    }
+
+    /// Returns how many timers are waiting on the clock.
+    ///
+    /// A test can wait for this to grow before advancing, when the actor
+    /// starts its timer on another thread.
+    pub fn sleepers(&self) -> usize {
+        self.inner.lock().unwrap().sleepers.len()
+    }
+
+    pub(crate) fn sleep_until(&self, deadline: Instant) -> Sleep {
+        Sleep {
+            clock: self.clone(),
+            deadline,
+            id: None,
+        }
+    }
+
+    pub(crate) async fn timeout<F: Future>(
+        &self,
+        duration: Duration,
+        future: F,
+    ) -> Option<F::Output> {
+        let sleep = self.sleep_until(self.now() + duration);
+        let future = future.fuse();
+        pin_mut!(future);
+        select_biased! {
+            output = future => Some(output),
+            () = sleep.fuse() => None,
+        }
+    }
+}
+
+impl Default for TestClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for TestClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.inner.lock().unwrap();
+        f.debug_struct("TestClock")
+            .field("elapsed", &(state.now - state.start))
+            .field("sleepers", &state.sleepers.len())
+            .finish()
+    }
+}
+
+/// Waits until a [`TestClock`] reaches a deadline.
+pub(crate) struct Sleep {
+    clock: TestClock,
+    deadline: Instant,
+    /// The key in the clock's sleepers once registered.
+    id: Option<u64>,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        let mut state = this.clock.inner.lock().unwrap();
+        if state.now >= this.deadline {
+            if let Some(id) = this.id.take() {
+                state.sleepers.remove(&id);
+            }
+            return Poll::Ready(());
+        }
+        let id = *this.id.get_or_insert_with(|| {
+            state.next_id += 1;
+            state.next_id
+        });
+        state
+            .sleepers
+            .insert(id, (this.deadline, cx.waker().clone()));
+        Poll::Pending
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            self.clock.inner.lock().unwrap().sleepers.remove(&id);
+        }
+    }
+}
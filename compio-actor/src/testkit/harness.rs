@@ -0,0 +1,229 @@
+use std::{
+    future::{Future, IntoFuture},
+    num::NonZeroUsize,
+    pin::Pin,
+};
+
+use super::TestClock;
+use crate::{
+    Actor, ActorExit, Handler, Mailbox, Message,
+    actor::{finish, transition},
+    mailbox::{Clock, DEFAULT_MAILBOX_CAPACITY, ExitReason, Exiting, Receiver, make_mailbox},
+    supervisor::{Supervision, SupervisionEvent},
+};
+
+/// An actor run inline on the current runtime, one step at a time.
+///
+/// Nothing happens unless the test asks for it: messages wait in the mailbox
+/// until [`step`](Self::step) or [`run_until_idle`](Self::run_until_idle)
+/// handles them, and [`on_idle`](Actor::on_idle) only runs when
+/// [`idle`](Self::idle) is called, whatever the actor's idle timeout.
+///
+/// [`Cluster::current`](crate::Cluster::current) is not available to its
+/// hooks and handlers.
+pub struct TestActor<A: Actor> {
+    actor: A,
+    mailbox: Mailbox<A>,
+    receiver: Receiver<A>,
+    state: A::State,
+    exiting: Exiting<A>,
+    supervision: Option<Supervision<A>>,
+}
+
+impl<A: Actor> TestActor<A> {
+    /// Starts `actor` with `arguments` once the returned builder is awaited.
+    pub fn spawn(actor: A, arguments: A::Arguments) -> TestSpawn<A> {
+        TestSpawn {
+            actor,
+            arguments,
+            capacity: DEFAULT_MAILBOX_CAPACITY,
+            supervisor: None,
+            clock: Clock::System,
+        }
+    }
+
+    /// Returns the actor's mailbox, to send it messages or hand it to others.
+    pub fn mailbox(&self) -> &Mailbox<A> {
+        &self.mailbox
+    }
+
+    /// Returns the actor's state.
+    pub fn state(&self) -> &A::State {
+        &self.state
+    }
+
+    /// Returns the actor's state mutably, such as to set up a test.
+    pub fn state_mut(&mut self) -> &mut A::State {
+        &mut self.state
+    }
+
+    /// Handles `message` right away, without going through the mailbox.
+    ///
+    /// Like every step, it runs [`on_transition`](Actor::on_transition)
+    /// afterwards if the handler unstashed.
+    pub async fn handle<M>(&mut self, message: M) -> Result<(), A::Error>
+    where
+        A: Handler<M>,
+        M: Message,
+    {
+        let handling = self.mailbox.handling();
+        let result =
+            Handler::<M>::handle(&self.actor, &self.mailbox, message, &mut self.state).await;
+        drop(handling);
+        transition(&self.actor, &self.mailbox, &mut self.state, result).await
+    }
+
+    /// Handles the next waiting message, if any.
+    ///
+    /// Messages are taken in the same order as a running actor would, but a
+    /// stop request is left for [`stop`](Self::stop).
+    pub async fn step(&mut self) -> Option<Result<(), A::Error>> {
+        let message = self.receiver.try_recv()?;
+        let handling = self.mailbox.handling();
+        let result = message
+            .deliver_to(&self.actor, &self.mailbox, &mut self.state)
+            .await;
+        drop(handling);
+        Some(transition(&self.actor, &self.mailbox, &mut self.state, result).await)
+    }
+
+    /// Handles waiting messages until there are none left, returning how many
+    /// were handled, or stops at the first error.
+    pub async fn run_until_idle(&mut self) -> Result<usize, A::Error> {
+        let mut handled = 0;
+        while let Some(result) = self.step().await {
+            result?;
+            handled += 1;
+        }
+        Ok(handled)
+    }
+
+    /// Runs [`on_idle`](Actor::on_idle), as if the idle timeout elapsed.
+    pub async fn idle(&mut self) -> Result<(), A::Error> {
+        let result = self.actor.on_idle(&self.mailbox, &mut self.state).await;
+        transition(&self.actor, &self.mailbox, &mut self.state, result).await
+    }
+
+    /// Stops the actor, running its stop hooks, and returns how it exited.
+    ///
+    /// Messages still waiting are dropped. Monitors, linked actors and the
+    /// supervisor are notified like for a running actor.
+    pub async fn stop(self) -> ActorExit<A::Error> {
+        let exit = self.mailbox.stopped();
+        self.exit(exit).await
+    }
+
+    /// Stops the actor as if a hook or handler failed with `error`.
+    pub async fn fail(self, error: A::Error) -> ActorExit<A::Error> {
+        self.exit(ActorExit::Failed(error)).await
+    }
+
+    async fn exit(mut self, exit: ActorExit<A::Error>) -> ActorExit<A::Error> {
+        let exit = finish(
+            &self.actor,
+            &self.mailbox,
+            self.receiver,
+            &mut self.state,
+            exit,
+        )
+        .await;
+        self.exiting.exit(ExitReason::from(&exit));
+        if let Some(supervision) = &self.supervision {
+            match &exit {
+                ActorExit::Stopped => supervision.terminated(&self.mailbox),
+                ActorExit::Failed(_) | ActorExit::Linked(_) => supervision.failed(&self.mailbox),
+            }
+        }
+        exit
+    }
+}
+
+/// A configurable [`TestActor`] start.
+///
+/// Returned by [`TestActor::spawn`].
+#[must_use = "test actors are not started until this builder is awaited"]
+pub struct TestSpawn<A: Actor> {
+    actor: A,
+    arguments: A::Arguments,
+    capacity: NonZeroUsize,
+    supervisor: Option<Supervision<A>>,
+    clock: Clock,
+}
+
+impl<A: Actor> TestSpawn<A> {
+    /// Sets the actor's bounded mailbox capacity.
+    pub fn with_capacity(mut self, capacity: NonZeroUsize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Sends actor lifecycle events to `supervisor`, such as a
+    /// [`TestProbe`](super::TestProbe).
+    pub fn with_supervisor<S>(mut self, supervisor: &Mailbox<S>) -> Self
+    where
+        S: Handler<SupervisionEvent<A>>,
+    {
+        self.supervisor = Some(Supervision::new(supervisor));
+        self
+    }
+
+    /// Makes the actor's timers wait for `clock` instead of the runtime's
+    /// time.
+    pub fn with_clock(mut self, clock: &TestClock) -> Self {
+        self.clock = Clock::Manual(clock.clone());
+        self
+    }
+
+    /// Runs `pre_start` then `post_start`, and returns the error of the first
+    /// that fails, after running the stop hooks.
+    async fn start(self) -> Result<TestActor<A>, A::Error> {
+        let Self {
+            actor,
+            arguments,
+            capacity,
+            supervisor,
+            clock,
+        } = self;
+        let (mailbox, receiver) = make_mailbox::<A>(None, capacity, clock);
+        let exiting = Exiting::new(mailbox.clone());
+        let state = match actor.pre_start(&mailbox, arguments).await {
+            Ok(state) => state,
+            Err(error) => {
+                exiting.exit(ExitReason::Failed);
+                return Err(error);
+            }
+        };
+
+        let mut harness = TestActor {
+            actor,
+            mailbox,
+            receiver,
+            state,
+            exiting,
+            supervision: supervisor,
+        };
+        if let Err(error) = harness
+            .actor
+            .post_start(&harness.mailbox, &mut harness.state)
+            .await
+        {
+            return match harness.fail(error).await {
+                ActorExit::Failed(error) => Err(error),
+                ActorExit::Stopped | ActorExit::Linked(_) => unreachable!(),
+            };
+        }
+        if let Some(supervision) = &harness.supervision {
+            supervision.started(&harness.mailbox);
+        }
+        Ok(harness)
+    }
+}
+
+impl<A: Actor> IntoFuture for TestSpawn<A> {
+    type IntoFuture = Pin<Box<dyn Future<Output = Self::Output>>>;
+    type Output = Result<TestActor<A>, A::Error>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(self.start())
+    }
+}
@@ -0,0 +1,66 @@
+//! Tools to test actors without a cluster.
+//!
+//! A [`TestActor`] runs an actor inline on the current runtime and handles
+//! its messages only when the test steps it. A [`TestProbe`] stands in for
+//! the actors it talks to, recording what they are sent. A [`TestClock`]
+//! stands in for the runtime's time in the actor's timers, and only moves
+//! when the test advances it:
+//!
+//! ```rust
+//! use std::{convert::Infallible, time::Duration};
+//!
+//! use compio_actor::{
+//!     Actor, Broker, Handler, Mailbox,
+//!     testkit::{TestActor, TestProbe},
+//! };
+//!
+//! struct Doubler;
+//!
+//! impl Actor for Doubler {
+//!     type Arguments = Broker<u32>;
+//!     type Error = Infallible;
+//!     type State = Broker<u32>;
+//!
+//!     async fn pre_start(
+//!         &self,
+//!         _myself: &Mailbox<Self>,
+//!         output: Self::Arguments,
+//!     ) -> Result<Self::State, Self::Error> {
+//!         Ok(output)
+//!     }
+//! }
+//!
+//! impl Handler<u32> for Doubler {
+//!     async fn handle(
+//!         &self,
+//!         _myself: &Mailbox<Self>,
+//!         value: u32,
+//!         output: &mut Self::State,
+//!     ) -> Result<(), Self::Error> {
+//!         output.send(value * 2).ok();
+//!         Ok(())
+//!     }
+//! }
+//!
+//! # compio_runtime::Runtime::new().unwrap().block_on(async {
+//! let probe = TestProbe::new();
+//! let mut doubler = TestActor::spawn(Doubler, probe.broker()).await.unwrap();
+//! doubler.mailbox().send(2).unwrap();
+//! probe.expect_no_msg(Duration::from_millis(10)).await;
+//!
+//! assert_eq!(doubler.run_until_idle().await, Ok(1));
+//! assert_eq!(probe.expect_msg(Duration::from_secs(1)).await, 4);
+//! doubler.stop().await;
+//! # });
+//! ```
+
+mod clock;
+mod harness;
+mod probe;
+
+#[doc(inline)]
+pub use clock::TestClock;
+#[doc(inline)]
+pub use harness::{TestActor, TestSpawn};
+#[doc(inline)]
+pub use probe::{Probe, TestProbe};
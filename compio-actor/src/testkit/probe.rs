@@ -0,0 +1,152 @@
+use std::{
+    convert::Infallible,
+    fmt,
+    marker::PhantomData,
+    num::NonZeroUsize,
+    time::{Duration, Instant},
+};
+
+use compio_runtime::time::timeout_at;
+use futures_util::FutureExt;
+
+use crate::{
+    Actor, Broker, Handler, Mailbox, Message,
+    mailbox::{Clock, DEFAULT_MAILBOX_CAPACITY, MailboxEvent, Receiver, make_mailbox},
+};
+
+/// The actor behind a [`TestProbe`]'s mailbox.
+///
+/// It never runs: the probe takes its messages itself.
+pub struct Probe<M>(PhantomData<fn() -> M>);
+
+impl<M: Message> Actor for Probe<M> {
+    type Arguments = ();
+    type Error = Infallible;
+    type State = Option<M>;
+
+    async fn pre_start(
+        &self,
+        _myself: &Mailbox<Self>,
+        (): Self::Arguments,
+    ) -> Result<Self::State, Self::Error> {
+        Ok(None)
+    }
+}
+
+impl<M: Message> Handler<M> for Probe<M> {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        message: M,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        *state = Some(message);
+        Ok(())
+    }
+}
+
+/// A mailbox that records the messages of type `M` sent to it, for tests to
+/// check.
+///
+/// Hand out its [`broker`](Self::broker) where an actor expects a
+/// `Broker<M>`, or its [`mailbox`](Self::mailbox) to monitor an actor with a
+/// `TestProbe<Down>` or to supervise one with a `TestProbe<SupervisionEvent<A>>`.
+pub struct TestProbe<M: Message> {
+    mailbox: Mailbox<Probe<M>>,
+    receiver: Receiver<Probe<M>>,
+}
+
+impl<M: Message> TestProbe<M> {
+    /// Creates a probe with the default mailbox capacity.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_MAILBOX_CAPACITY)
+    }
+
+    /// Creates a probe that holds at most `capacity` messages.
+    pub fn with_capacity(capacity: NonZeroUsize) -> Self {
+        let (mailbox, receiver) = make_mailbox(None, capacity, Clock::System);
+        Self { mailbox, receiver }
+    }
+
+    /// Returns the probe's mailbox.
+    pub fn mailbox(&self) -> &Mailbox<Probe<M>> {
+        &self.mailbox
+    }
+
+    /// Creates a send-only capability that delivers to this probe.
+    pub fn broker(&self) -> Broker<M> {
+        self.mailbox.broker()
+    }
+
+    /// Takes the next recorded message without waiting.
+    pub fn try_recv(&self) -> Option<M> {
+        let mut message = None;
+        while message.is_none() {
+            self.receiver
+                .try_recv()?
+                .deliver_to(&Probe(PhantomData), &self.mailbox, &mut message)
+                .now_or_never();
+        }
+        message
+    }
+
+    /// Waits up to `timeout` for the next message.
+    pub async fn recv(&self, timeout: Duration) -> Option<M> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let message = match timeout_at(deadline, self.receiver.recv()).await {
+                Ok(MailboxEvent::Message(message)) => message,
+                Ok(MailboxEvent::Stop) | Err(_) => return None,
+            };
+            let mut recorded = None;
+            message
+                .deliver_to(&Probe(PhantomData), &self.mailbox, &mut recorded)
+                .await
+                .ok();
+            // A call taken back by its caller leaves nothing to record.
+            if recorded.is_some() {
+                return recorded;
+            }
+        }
+    }
+
+    /// Waits up to `timeout` for the next message, and returns it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no message arrives in time.
+    pub async fn expect_msg(&self, timeout: Duration) -> M {
+        match self.recv(timeout).await {
+            Some(message) => message,
+            None => panic!("expected a message within {timeout:?}"),
+        }
+    }
+
+    /// Waits for `timeout`, checking that no message arrives.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a message arrives.
+    pub async fn expect_no_msg(&self, timeout: Duration)
+    where
+        M: fmt::Debug,
+    {
+        if let Some(message) = self.recv(timeout).await {
+            panic!("expected no message within {timeout:?}, received {message:?}");
+        }
+    }
+}
+
+impl<M: Message> Default for TestProbe<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: Message> fmt::Debug for TestProbe<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestProbe")
+            .field("mailbox", &self.mailbox)
+            .finish()
+    }
+}
@@ -8,7 +8,7 @@ use std::{
         atomic::{AtomicBool, AtomicUsize, Ordering},
         mpsc,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use compio_actor::{
@@ -681,3 +681,69 @@ async fn named_actor_is_hidden_until_startup_succeeds() {
     assert_eq!(handle.await.unwrap(), ActorExit::Stopped);
     Arc::try_unwrap(cluster).ok().unwrap().join().await.unwrap();
 }
+
+#[derive(Debug, PartialEq, Eq)]
+struct Nap(Duration);
+
+struct Sleeper;
+
+impl Actor for Sleeper {
+    type Arguments = Arc<AtomicUsize>;
+    type Error = Infallible;
+    type State = Arc<AtomicUsize>;
+
+    async fn pre_start(
+        &self,
+        _myself: &compio_actor::Mailbox<Self>,
+        handled: Self::Arguments,
+    ) -> Result<Self::State, Self::Error> {
+        Ok(handled)
+    }
+}
+
+impl Handler<Call<Nap, Option<Instant>>> for Sleeper {
+    async fn handle(
+        &self,
+        _myself: &compio_actor::Mailbox<Self>,
+        call: Call<Nap, Option<Instant>>,
+        handled: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        handled.fetch_add(1, Ordering::SeqCst);
+        compio_runtime::time::sleep(call.message().0).await;
+        let deadline = call.deadline();
+        call.reply(deadline).ok();
+        Ok(())
+    }
+}
+
+#[compio_macros::test]
+async fn call_timeout_exposes_the_deadline_and_recovers_undelivered_requests() {
+    let cluster = cluster();
+    let handled = Arc::new(AtomicUsize::new(0));
+    let (sleeper, handle) = cluster.spawn(|| Sleeper, handled.clone()).await.unwrap();
+    let broker = sleeper.broker::<Call<Nap, Option<Instant>>>();
+
+    assert_eq!(sleeper.call(Nap(Duration::ZERO)).await.unwrap(), None);
+    let before = Instant::now();
+    let deadline = sleeper
+        .call_timeout(Nap(Duration::ZERO), Duration::from_secs(10))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(deadline >= before + Duration::from_secs(10));
+
+    let napping = sleeper.call_timeout(Nap(Duration::from_millis(600)), Duration::from_millis(150));
+    assert_eq!(napping.await, Err(CallError::Timeout(None)));
+    let queued = broker.call_timeout(Nap(Duration::ZERO), Duration::from_millis(50));
+    assert_eq!(
+        queued.await,
+        Err(CallError::Timeout(Some(Nap(Duration::ZERO))))
+    );
+
+    assert_eq!(sleeper.call(Nap(Duration::ZERO)).await.unwrap(), None);
+    assert_eq!(handled.load(Ordering::SeqCst), 4);
+
+    sleeper.stop();
+    assert_eq!(handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+}
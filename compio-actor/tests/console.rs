@@ -0,0 +1,128 @@
+//! Assert that actors show up in [`tokio-console`] under their type, pointing
+//! at the call that spawned them.
+//!
+//! [`tokio-console`]: https://github.com/tokio-rs/console
+#![cfg(feature = "console")]
+
+use std::{
+    convert::Infallible,
+    fmt::Debug,
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+};
+
+use compio_actor::{Actor, ActorExit, Cluster, Mailbox};
+use compio_dispatcher::Dispatcher;
+use tracing::{
+    Event, Metadata, Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+};
+
+/// Where the console says a task came from.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Task {
+    name: Option<String>,
+    file: String,
+    line: u64,
+}
+
+/// A subscriber recording the tasks `console-subscriber` would report.
+#[derive(Debug, Default, Clone)]
+struct Recorder(Arc<Mutex<Vec<Task>>>);
+
+impl Recorder {
+    fn named(&self, name: &str) -> Vec<Task> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|it| it.name.as_deref() == Some(name))
+            .cloned()
+            .collect()
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, meta: &Metadata<'_>) -> bool {
+        meta.name() == "runtime.spawn"
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let mut task = Task::default();
+        attrs.record(&mut TaskVisitor(&mut task));
+
+        let mut tasks = self.0.lock().unwrap();
+        tasks.push(task);
+        Id::from_u64(tasks.len() as u64)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+struct TaskVisitor<'a>(&'a mut Task);
+
+impl Visit for TaskVisitor<'_> {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "loc.line" {
+            self.0.line = value;
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "task.name" => self.0.name = Some(value.to_owned()),
+            "loc.file" => self.0.file = value.to_owned(),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn Debug) {}
+}
+
+struct Idle;
+
+impl Actor for Idle {
+    type Arguments = ();
+    type Error = Infallible;
+    type State = ();
+
+    async fn pre_start(
+        &self,
+        _myself: &Mailbox<Self>,
+        (): Self::Arguments,
+    ) -> Result<Self::State, Self::Error> {
+        Ok(())
+    }
+}
+
+/// The workers spawn on threads of their own, so the recorder has to be the
+/// global subscriber rather than this thread's.
+#[compio_macros::test]
+async fn actors_are_named_after_their_type_and_point_at_their_spawn() {
+    let recorder = Recorder::default();
+    tracing::subscriber::set_global_default(recorder.clone()).unwrap();
+
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(1).unwrap())
+        .build()
+        .unwrap();
+    let cluster = Cluster::from_dispatcher(dispatcher);
+    let spawned = u64::from(line!()) + 1;
+    let (idle, handle) = cluster.spawn(|| Idle, ()).await.unwrap();
+    idle.stop();
+    assert_eq!(handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+
+    let tasks = recorder.named(std::any::type_name::<Idle>());
+    assert_eq!(tasks.len(), 1, "one task per actor");
+    assert_eq!((tasks[0].file.as_str(), tasks[0].line), (file!(), spawned));
+}
@@ -0,0 +1,257 @@
+use std::{convert::Infallible, num::NonZeroUsize};
+
+use compio_actor::{
+    Actor, ActorExit, Call, Cluster, Handler, Mailbox,
+    mailbox::{Down, ExitReason, Exited},
+};
+use compio_dispatcher::Dispatcher;
+
+fn cluster() -> Cluster {
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(2).unwrap())
+        .build()
+        .unwrap();
+    Cluster::from_dispatcher(dispatcher)
+}
+
+#[derive(Debug)]
+struct Stop;
+
+#[derive(Debug)]
+struct Fail;
+
+#[derive(Debug)]
+struct Ping;
+
+struct Node;
+
+impl Actor for Node {
+    type Arguments = ();
+    type Error = &'static str;
+    type State = ();
+
+    async fn pre_start(
+        &self,
+        _myself: &Mailbox<Self>,
+        (): Self::Arguments,
+    ) -> Result<Self::State, Self::Error> {
+        Ok(())
+    }
+}
+
+impl Handler<Stop> for Node {
+    async fn handle(
+        &self,
+        myself: &Mailbox<Self>,
+        _stop: Stop,
+        _state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        myself.stop();
+        Ok(())
+    }
+}
+
+impl Handler<Fail> for Node {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        Fail: Fail,
+        _state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        Err("node failed")
+    }
+}
+
+impl Handler<Call<Ping, ()>> for Node {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        call: Call<Ping, ()>,
+        _state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        call.reply(()).ok();
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct Notices;
+
+/// Records the [`Down`] and [`Exited`] messages it receives.
+struct Watcher;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Notice {
+    Down(Down),
+    Exited(Exited),
+}
+
+impl Actor for Watcher {
+    type Arguments = bool;
+    type Error = Infallible;
+    type State = Vec<Notice>;
+
+    async fn pre_start(
+        &self,
+        myself: &Mailbox<Self>,
+        trap: Self::Arguments,
+    ) -> Result<Self::State, Self::Error> {
+        myself.trap_exits(trap);
+        Ok(Vec::new())
+    }
+}
+
+impl Handler<Down> for Watcher {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        down: Down,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        state.push(Notice::Down(down));
+        Ok(())
+    }
+}
+
+impl Handler<Exited> for Watcher {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        exited: Exited,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        state.push(Notice::Exited(exited));
+        Ok(())
+    }
+}
+
+impl Handler<Call<Notices, Vec<Notice>>> for Watcher {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        call: Call<Notices, Vec<Notice>>,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        call.reply(state.clone()).ok();
+        Ok(())
+    }
+}
+
+#[compio_macros::test]
+async fn monitors_receive_down_when_an_actor_exits() {
+    let cluster = cluster();
+    let (watcher, watcher_handle) = cluster.spawn(|| Watcher, false).await.unwrap();
+    let (stopping, stopping_handle) = cluster.spawn(|| Node, ()).await.unwrap();
+    let (failing, failing_handle) = cluster.spawn(|| Node, ()).await.unwrap();
+    let (ignored, ignored_handle) = cluster.spawn(|| Node, ()).await.unwrap();
+    assert_ne!(stopping.id(), failing.id());
+
+    watcher.monitor(&stopping);
+    watcher.monitor(&failing);
+    watcher.monitor(&ignored);
+    watcher.demonitor(&ignored);
+
+    stopping.send(Stop).unwrap();
+    assert_eq!(stopping_handle.await.unwrap(), ActorExit::Stopped);
+    failing.send(Fail).unwrap();
+    assert_eq!(
+        failing_handle.await.unwrap(),
+        ActorExit::Failed("node failed")
+    );
+    ignored.send(Stop).unwrap();
+    assert_eq!(ignored_handle.await.unwrap(), ActorExit::Stopped);
+    watcher.monitor(&stopping);
+
+    let stopped = Notice::Down(Down {
+        id: stopping.id(),
+        exit: ExitReason::Stopped,
+    });
+    assert_eq!(
+        watcher.call(Notices).await.unwrap(),
+        [
+            stopped,
+            Notice::Down(Down {
+                id: failing.id(),
+                exit: ExitReason::Failed,
+            }),
+            stopped,
+        ]
+    );
+
+    watcher.stop();
+    assert_eq!(watcher_handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn a_failure_stops_linked_actors() {
+    let cluster = cluster();
+    let (connection, connection_handle) = cluster.spawn(|| Node, ()).await.unwrap();
+    let (session, session_handle) = cluster.spawn(|| Node, ()).await.unwrap();
+    let (stream, stream_handle) = cluster.spawn(|| Node, ()).await.unwrap();
+    session.link(&connection);
+    stream.link(&session);
+
+    connection.send(Fail).unwrap();
+    assert_eq!(
+        connection_handle.await.unwrap(),
+        ActorExit::Failed("node failed")
+    );
+    assert_eq!(
+        session_handle.await.unwrap(),
+        ActorExit::Linked(connection.id())
+    );
+    assert_eq!(
+        stream_handle.await.unwrap(),
+        ActorExit::Linked(session.id())
+    );
+    cluster.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn a_normal_stop_or_unlink_spares_linked_actors() {
+    let cluster = cluster();
+    let (first, first_handle) = cluster.spawn(|| Node, ()).await.unwrap();
+    let (second, second_handle) = cluster.spawn(|| Node, ()).await.unwrap();
+    let (third, third_handle) = cluster.spawn(|| Node, ()).await.unwrap();
+    first.link(&second);
+    second.link(&third);
+    third.unlink(&second);
+
+    first.send(Stop).unwrap();
+    assert_eq!(first_handle.await.unwrap(), ActorExit::Stopped);
+    second.call(Ping).await.unwrap();
+
+    second.send(Fail).unwrap();
+    assert_eq!(
+        second_handle.await.unwrap(),
+        ActorExit::Failed("node failed")
+    );
+    third.call(Ping).await.unwrap();
+
+    third.stop();
+    assert_eq!(third_handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn trapping_exits_turns_them_into_messages() {
+    let cluster = cluster();
+    let (watcher, watcher_handle) = cluster.spawn(|| Watcher, true).await.unwrap();
+    let (node, node_handle) = cluster.spawn(|| Node, ()).await.unwrap();
+    watcher.link(&node);
+
+    node.send(Fail).unwrap();
+    assert_eq!(node_handle.await.unwrap(), ActorExit::Failed("node failed"));
+    watcher.link(&node);
+
+    let failed = Notice::Exited(Exited {
+        id: node.id(),
+        exit: ExitReason::Failed,
+    });
+    assert_eq!(watcher.call(Notices).await.unwrap(), [failed, failed]);
+
+    watcher.stop();
+    assert_eq!(watcher_handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+}
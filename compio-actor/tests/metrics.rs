@@ -0,0 +1,163 @@
+use std::{
+    num::NonZeroUsize,
+    sync::{Arc, Barrier, mpsc},
+    time::Duration,
+};
+
+use compio_actor::{
+    Actor, ActorExit, Call, Cluster, Handler, Mailbox,
+    supervisor::{ChildSpec, Strategy, Supervisor, SupervisorSpec},
+};
+use compio_dispatcher::Dispatcher;
+
+fn cluster() -> Cluster {
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(2).unwrap())
+        .build()
+        .unwrap();
+    Cluster::from_dispatcher(dispatcher)
+}
+
+#[derive(Debug)]
+struct Block(mpsc::Sender<()>, Arc<Barrier>);
+
+#[derive(Debug)]
+struct Fail;
+
+#[derive(Debug)]
+struct Ping;
+
+struct Worker;
+
+impl Actor for Worker {
+    type Arguments = ();
+    type Error = &'static str;
+    type State = ();
+
+    async fn pre_start(
+        &self,
+        _myself: &Mailbox<Self>,
+        (): Self::Arguments,
+    ) -> Result<Self::State, Self::Error> {
+        Ok(())
+    }
+}
+
+impl Handler<Block> for Worker {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        Block(entered, release): Block,
+        _state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        entered.send(()).unwrap();
+        release.wait();
+        Ok(())
+    }
+}
+
+impl Handler<Fail> for Worker {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        Fail: Fail,
+        _state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        Err("failed")
+    }
+}
+
+impl Handler<Call<Ping, ()>> for Worker {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        call: Call<Ping, ()>,
+        _state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        call.reply(()).ok();
+        Ok(())
+    }
+}
+
+#[compio_macros::test]
+async fn metrics_track_handlers_and_the_cluster_lists_running_actors() {
+    let cluster = cluster();
+    let (worker, handle) = cluster
+        .spawn(|| Worker, ())
+        .with_name("worker")
+        .await
+        .unwrap();
+    let (other, other_handle) = cluster.spawn(|| Worker, ()).await.unwrap();
+    worker.call(Ping).await.unwrap();
+    worker.call(Ping).await.unwrap();
+
+    // Replies wake the caller before the handler returns, so only count the
+    // calls once the next handler has started.
+    let (entered_tx, entered) = mpsc::channel();
+    let release = Arc::new(Barrier::new(2));
+    worker.send(Block(entered_tx, release.clone())).unwrap();
+    worker.send(Fail).unwrap();
+    entered.recv_timeout(Duration::from_secs(2)).unwrap();
+    std::thread::sleep(Duration::from_millis(20));
+    let stuck = worker.metrics();
+    assert_eq!(stuck.processed, 2);
+    assert_eq!(stuck.queued, 1);
+    assert_eq!(stuck.restarts, 0);
+    assert!(stuck.handling.unwrap() >= Duration::from_millis(20));
+    assert!(stuck.uptime >= stuck.handling.unwrap());
+    assert!(stuck.slowest <= stuck.busy);
+    assert!(stuck.mean_handler_time() <= stuck.slowest);
+
+    let actors = cluster.actors();
+    assert_eq!(actors.len(), 2);
+    assert_eq!(actors[0].id, worker.id());
+    assert_eq!(actors[0].name.as_deref(), Some("worker"));
+    assert_eq!(actors[0].type_name, std::any::type_name::<Worker>());
+    assert_eq!(actors[0].capacity, worker.capacity());
+    assert!(actors[0].metrics.handling.is_some());
+    assert_eq!(actors[1].id, other.id());
+    assert_eq!(actors[1].name, None);
+    for actor in &actors {
+        assert!(actor.worker.unwrap() < 2);
+    }
+    assert_eq!(actors[0].worker, worker.worker());
+
+    // The other actor may share the blocked worker.
+    release.wait();
+    assert_eq!(handle.await.unwrap(), ActorExit::Failed("failed"));
+    let metrics = worker.metrics();
+    assert_eq!(metrics.processed, 4);
+    assert_eq!(metrics.handling, None);
+    assert!(metrics.slowest >= Duration::from_millis(20));
+    let actors = cluster.actors();
+    assert_eq!(actors.len(), 1);
+    assert_eq!(actors[0].id, other.id());
+
+    other.stop();
+    assert_eq!(other_handle.await.unwrap(), ActorExit::Stopped);
+    assert!(cluster.actors().is_empty());
+    cluster.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn restarted_children_report_their_restarts() {
+    let cluster = cluster();
+    let spec = SupervisorSpec::new(Strategy::OneForOne)
+        .with_intensity(3, Duration::from_secs(10))
+        .with_child(ChildSpec::new("worker", || Worker, ()).with_name("worker"));
+    let (supervisor, handle) = cluster.spawn(|| Supervisor, spec).await.unwrap();
+
+    for restarts in 0..2 {
+        let worker = loop {
+            match cluster.lookup::<Worker, _>("worker") {
+                Some(worker) if worker.metrics().restarts == restarts => break worker,
+                _ => compio_runtime::time::sleep(Duration::from_millis(5)).await,
+            }
+        };
+        worker.send(Fail).unwrap();
+    }
+
+    supervisor.stop();
+    assert_eq!(handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+}
@@ -0,0 +1,197 @@
+use std::{io, io::Write, num::NonZeroUsize, path::PathBuf};
+
+use compio_actor::{
+    Actor, ActorExit, Call, Cluster, Handler, Mailbox,
+    persistence::{FileJournal, Journal, Persistent, PersistentActor, Recovery},
+};
+use compio_dispatcher::Dispatcher;
+use compio_io::framed::codec::serde_json::SerdeJsonCodec;
+
+fn cluster() -> Cluster {
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(1).unwrap())
+        .build()
+        .unwrap();
+    Cluster::from_dispatcher(dispatcher)
+}
+
+#[derive(Debug)]
+struct Add(u64);
+
+#[derive(Debug)]
+struct Snapshot;
+
+#[derive(Debug)]
+struct Total;
+
+/// Sums numbers, surviving restarts.
+struct Counter;
+
+impl Actor for Counter {
+    type Arguments = PathBuf;
+    type Error = io::Error;
+    type State = Persistent<Self, FileJournal<SerdeJsonCodec>>;
+
+    async fn pre_start(
+        &self,
+        _myself: &Mailbox<Self>,
+        path: Self::Arguments,
+    ) -> Result<Self::State, Self::Error> {
+        let journal = FileJournal::open(path, SerdeJsonCodec::new()).await?;
+        Persistent::recover(journal, 0).await
+    }
+}
+
+impl PersistentActor for Counter {
+    type Data = u64;
+    type Event = u64;
+
+    fn apply(total: &mut u64, added: &u64) {
+        *total += added;
+    }
+}
+
+impl Handler<Add> for Counter {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        Add(value): Add,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        state.persist(value).await
+    }
+}
+
+impl Handler<Snapshot> for Counter {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        Snapshot: Snapshot,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        state.snapshot().await
+    }
+}
+
+impl Handler<Call<Total, (u64, u64)>> for Counter {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        call: Call<Total, (u64, u64)>,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        call.reply((**state, state.sequence())).ok();
+        Ok(())
+    }
+}
+
+#[compio_macros::test]
+async fn persistent_actors_recover_their_events_and_snapshots() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("counter.journal");
+    let cluster = cluster();
+
+    let (counter, handle) = cluster.spawn(|| Counter, path.clone()).await.unwrap();
+    counter.send(Add(1)).unwrap();
+    counter.send(Add(2)).unwrap();
+    assert_eq!(counter.call(Total).await.unwrap(), (3, 2));
+    counter.send(Snapshot).unwrap();
+    counter.send(Add(4)).unwrap();
+    assert_eq!(counter.call(Total).await.unwrap(), (7, 3));
+    counter.stop();
+    assert!(matches!(handle.await.unwrap(), ActorExit::Stopped));
+
+    let (counter, handle) = cluster.spawn(|| Counter, path.clone()).await.unwrap();
+    assert_eq!(counter.call(Total).await.unwrap(), (7, 3));
+    counter.send(Add(5)).unwrap();
+    assert_eq!(counter.call(Total).await.unwrap(), (12, 4));
+    counter.stop();
+    assert!(matches!(handle.await.unwrap(), ActorExit::Stopped));
+    cluster.join().await.unwrap();
+
+    let mut journal = FileJournal::open(&path, SerdeJsonCodec::new())
+        .await
+        .unwrap();
+    let recovery: Recovery<u64, u64> = journal.recover().await.unwrap();
+    assert_eq!(
+        recovery,
+        Recovery {
+            snapshot: Some((2, 3)),
+            events: vec![4, 5],
+        }
+    );
+}
+
+#[compio_macros::test]
+async fn incomplete_records_are_dropped_when_a_journal_opens() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("torn.journal");
+
+    let mut journal = FileJournal::open(&path, SerdeJsonCodec::new())
+        .await
+        .unwrap();
+    assert!(journal.is_empty());
+    Journal::<u64, u64>::append(&mut journal, 1, &[10, 20])
+        .await
+        .unwrap();
+    let len = journal.len();
+    drop(journal);
+
+    // A record cut short by a crash: a header promising more than was written.
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap();
+    file.write_all(&[0, 3, 0, 0, 0, 0, 0, 0, 0, 100, 0, 0, 0, 0, 0, 0, 0, b'3'])
+        .unwrap();
+    drop(file);
+
+    let journal = FileJournal::open(&path, SerdeJsonCodec::new())
+        .await
+        .unwrap();
+    assert_eq!(journal.len(), len);
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), len);
+    let mut counter = Persistent::<Counter, _>::recover(journal, 0).await.unwrap();
+    assert_eq!((*counter, counter.sequence()), (30, 2));
+    counter.persist(30).await.unwrap();
+    drop(counter);
+
+    let mut journal = FileJournal::open(&path, SerdeJsonCodec::new())
+        .await
+        .unwrap();
+    let recovery: Recovery<u64, u64> = journal.recover().await.unwrap();
+    assert_eq!(recovery.snapshot, None);
+    assert_eq!(recovery.events, [10, 20, 30]);
+}
+
+#[compio_macros::test]
+async fn zeroed_tails_fail_their_checksum_when_a_journal_opens() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("zeroed.journal");
+
+    let mut journal = FileJournal::open(&path, SerdeJsonCodec::new())
+        .await
+        .unwrap();
+    Journal::<u64, u64>::append(&mut journal, 1, &[10, 20])
+        .await
+        .unwrap();
+    let len = journal.len();
+    drop(journal);
+
+    // Space the file system allocated for an append that never reached the
+    // disk, which reads back as zeros: a header for an empty event.
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap();
+    file.write_all(&[0; 64]).unwrap();
+    drop(file);
+
+    let mut journal = FileJournal::open(&path, SerdeJsonCodec::new())
+        .await
+        .unwrap();
+    assert_eq!(journal.len(), len);
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), len);
+    let recovery: Recovery<u64, u64> = journal.recover().await.unwrap();
+    assert_eq!(recovery.events, [10, 20]);
+}
@@ -0,0 +1,203 @@
+use std::{
+    convert::Infallible,
+    num::NonZeroUsize,
+    sync::{Arc, Barrier, mpsc},
+    time::Duration,
+};
+
+use compio_actor::{
+    Actor, ActorExit, Cluster, Handler, Mailbox,
+    mailbox::{DeliverError, Down, Priority},
+};
+use compio_dispatcher::Dispatcher;
+
+fn cluster() -> Cluster {
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(1).unwrap())
+        .build()
+        .unwrap();
+    Cluster::from_dispatcher(dispatcher)
+}
+
+#[derive(Debug)]
+struct Block;
+
+#[derive(Debug, PartialEq, Eq)]
+struct Log(&'static str);
+
+#[derive(Debug)]
+struct Urgent(&'static str);
+
+/// Reports the messages it handles, in order.
+struct Recorder;
+
+impl Actor for Recorder {
+    type Arguments = (mpsc::Sender<&'static str>, Arc<Barrier>);
+    type Error = Infallible;
+    type State = (mpsc::Sender<&'static str>, Arc<Barrier>);
+
+    async fn pre_start(
+        &self,
+        _myself: &Mailbox<Self>,
+        arguments: Self::Arguments,
+    ) -> Result<Self::State, Self::Error> {
+        Ok(arguments)
+    }
+}
+
+impl Handler<Block> for Recorder {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        Block: Block,
+        (log, release): &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        log.send("blocked").unwrap();
+        release.wait();
+        Ok(())
+    }
+}
+
+impl Handler<Log> for Recorder {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        Log(name): Log,
+        (log, _): &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        log.send(name).unwrap();
+        Ok(())
+    }
+}
+
+impl Handler<Urgent> for Recorder {
+    const PRIORITY: Priority = Priority::High;
+
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        Urgent(name): Urgent,
+        (log, _): &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        log.send(name).unwrap();
+        Ok(())
+    }
+}
+
+impl Handler<Down> for Recorder {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        _down: Down,
+        (log, _): &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        log.send("down").unwrap();
+        Ok(())
+    }
+}
+
+struct Idle;
+
+impl Actor for Idle {
+    type Arguments = ();
+    type Error = Infallible;
+    type State = ();
+
+    async fn pre_start(
+        &self,
+        _myself: &Mailbox<Self>,
+        (): Self::Arguments,
+    ) -> Result<Self::State, Self::Error> {
+        Ok(())
+    }
+}
+
+fn received(log: &mpsc::Receiver<&'static str>, count: usize) -> Vec<&'static str> {
+    (0..count)
+        .map(|_| log.recv_timeout(Duration::from_secs(2)).unwrap())
+        .collect()
+}
+
+#[compio_macros::test]
+async fn higher_priorities_skip_the_backlog_and_share_the_capacity() {
+    let cluster = cluster();
+    let (log_tx, log) = mpsc::channel();
+    let release = Arc::new(Barrier::new(2));
+    let (recorder, handle) = cluster
+        .spawn(|| Recorder, (log_tx, release.clone()))
+        .with_capacity(NonZeroUsize::new(5).unwrap())
+        .await
+        .unwrap();
+    let broker = recorder.broker::<Log>();
+
+    recorder.send(Block).unwrap();
+    assert_eq!(received(&log, 1), ["blocked"]);
+    recorder.send(Log("normal")).unwrap();
+    recorder
+        .send_with_priority(Log("low"), Priority::Low)
+        .unwrap();
+    recorder.send(Urgent("urgent")).unwrap();
+    broker
+        .send_with_priority(Log("high"), Priority::High)
+        .unwrap();
+    broker.send(Log("later")).unwrap();
+    assert_eq!(recorder.queued(), 5);
+    assert_eq!(
+        recorder.send_with_priority(Log("full"), Priority::High),
+        Err(DeliverError::Full(Log("full")))
+    );
+
+    release.wait();
+    assert_eq!(
+        received(&log, 5),
+        ["urgent", "high", "normal", "later", "low"]
+    );
+
+    recorder.stop();
+    assert_eq!(handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn control_messages_bypass_a_full_mailbox() {
+    // The recorder blocks its only worker, so the monitored actor runs apart.
+    let elsewhere = cluster();
+    let cluster = cluster();
+    let (log_tx, log) = mpsc::channel();
+    let release = Arc::new(Barrier::new(2));
+    let (recorder, handle) = cluster
+        .spawn(|| Recorder, (log_tx, release.clone()))
+        .with_capacity(NonZeroUsize::new(1).unwrap())
+        .await
+        .unwrap();
+    let (idle, idle_handle) = elsewhere.spawn(|| Idle, ()).await.unwrap();
+    recorder.monitor(&idle);
+
+    recorder.send(Block).unwrap();
+    assert_eq!(received(&log, 1), ["blocked"]);
+    recorder.send(Log("queued")).unwrap();
+    assert_eq!(
+        recorder.send(Log("full")),
+        Err(DeliverError::Full(Log("full")))
+    );
+    idle.stop();
+    assert_eq!(idle_handle.await.unwrap(), ActorExit::Stopped);
+
+    release.wait();
+    assert_eq!(received(&log, 2), ["down", "queued"]);
+
+    recorder.stop();
+    assert_eq!(handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+    elsewhere.join().await.unwrap();
+}
+
+#[test]
+fn priorities_sort_from_the_highest() {
+    let mut priorities = [Priority::Low, Priority::High, Priority::Normal];
+    priorities.sort();
+    assert_eq!(
+        priorities,
+        [Priority::High, Priority::Normal, Priority::Low]
+    );
+}
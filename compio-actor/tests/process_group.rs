@@ -10,9 +10,9 @@ use std::{
 };
 
 use compio_actor::{
-    Actor, ActorExit, Call, Cluster, Handler, Mailbox,
-    mailbox::DeliverError,
-    process_group::{ProcessGroup, Strategy},
+    Actor, ActorExit, ActorHandle, Call, Cluster, Handler, Mailbox,
+    mailbox::{ActorId, CallError, DeliverError},
+    process_group::{GroupEvent, Membership, ProcessGroup, Strategy},
 };
 use compio_dispatcher::Dispatcher;
 use futures_channel::oneshot;
@@ -227,3 +227,416 @@ async fn skips_full_members_without_allocating_a_backlog() {
     assert_eq!(observed.load(Ordering::Relaxed), 7);
     cluster.join().await.unwrap();
 }
+
+impl Handler<Call<Read, usize>> for BlockedWorker {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        call: Call<Read, usize>,
+        _state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        call.reply(0).ok();
+        Ok(())
+    }
+}
+
+async fn worker(cluster: &Cluster) -> (Mailbox<Worker>, ActorHandle<Infallible>) {
+    cluster
+        .spawn(
+            || Worker {
+                observed: Arc::new(AtomicUsize::new(0)),
+            },
+            0,
+        )
+        .await
+        .unwrap()
+}
+
+/// Spawns a worker blocked in a handler until the returned sender is used.
+async fn blocked_worker(
+    cluster: &Cluster,
+    capacity: usize,
+) -> (
+    Mailbox<BlockedWorker>,
+    ActorHandle<Infallible>,
+    oneshot::Sender<()>,
+) {
+    let (entered_tx, entered_rx) = mpsc::channel();
+    let (release_tx, release_rx) = oneshot::channel();
+    let (blocked, blocked_handle) = cluster
+        .spawn(|| BlockedWorker, (entered_tx, Some(release_rx)))
+        .with_capacity(NonZeroUsize::new(capacity).unwrap())
+        .await
+        .unwrap();
+    blocked.send(Block).unwrap();
+    entered_rx.recv_timeout(Duration::from_secs(2)).unwrap();
+    (blocked, blocked_handle, release_tx)
+}
+
+async fn reads(workers: &[&Mailbox<Worker>]) -> Vec<usize> {
+    let mut reads = Vec::new();
+    for worker in workers {
+        reads.push(worker.call(Read).await.unwrap());
+    }
+    reads
+}
+
+#[compio_macros::test]
+async fn least_loaded_routes_around_a_busy_member() {
+    let cluster = cluster();
+    let (blocked, blocked_handle, release) = blocked_worker(&cluster, 4).await;
+    blocked.send(Work(1)).unwrap();
+    blocked.send(Work(1)).unwrap();
+    let (available, available_handle) = worker(&cluster).await;
+
+    let group = ProcessGroup::with_strategy(Strategy::LeastLoaded);
+    let _blocked = group.join(blocked.broker());
+    let _available = group.join(available.broker());
+    group.send(Work(1)).unwrap();
+    group.send(Work(1)).unwrap();
+    assert_eq!(blocked.queued(), 2);
+    assert_eq!(reads(&[&available]).await, [2]);
+
+    available.stop();
+    blocked.stop();
+    release.send(()).ok();
+    assert_eq!(available_handle.await.unwrap(), ActorExit::Stopped);
+    assert_eq!(blocked_handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn random_and_consistent_hash_routing() {
+    let cluster = cluster();
+    let (first, first_handle) = worker(&cluster).await;
+    let (second, second_handle) = worker(&cluster).await;
+    let (third, third_handle) = worker(&cluster).await;
+
+    let random = ProcessGroup::with_strategy(Strategy::Random);
+    let hashed = ProcessGroup::consistent_hash(|Work(key)| *key);
+    let mut memberships = Vec::new();
+    for worker in [&first, &second, &third] {
+        memberships.push(random.join(worker.broker()));
+        memberships.push(hashed.join(worker.broker()));
+    }
+
+    for _ in 0..60 {
+        random.send(Work(1)).unwrap();
+    }
+    let spread = reads(&[&first, &second, &third]).await;
+    assert_eq!(spread.iter().sum::<usize>(), 60);
+    assert!(spread.iter().all(|&read| read > 0));
+
+    for _ in 0..10 {
+        hashed.send(Work(100)).unwrap();
+    }
+    let hashed_reads = reads(&[&first, &second, &third]).await;
+    let changed = spread
+        .iter()
+        .zip(&hashed_reads)
+        .filter(|(before, after)| before != after)
+        .map(|(before, after)| after - before)
+        .collect::<Vec<_>>();
+    assert_eq!(changed, [1000]);
+
+    // Without a key, a consistent hash group routes like round robin.
+    let keyless = ProcessGroup::with_strategy(Strategy::ConsistentHash);
+    for worker in [&first, &second, &third] {
+        memberships.push(keyless.join(worker.broker()));
+    }
+    for _ in 0..3 {
+        keyless.send(Work(1)).unwrap();
+    }
+    let keyless_reads = reads(&[&first, &second, &third]).await;
+    for (before, after) in hashed_reads.iter().zip(&keyless_reads) {
+        assert_eq!(after - before, 1);
+    }
+    keyless.set_hash_key(|Work(key)| *key);
+    for _ in 0..3 {
+        keyless.send(Work(10)).unwrap();
+    }
+    let keyed_reads = reads(&[&first, &second, &third]).await;
+    let changed = keyless_reads
+        .iter()
+        .zip(&keyed_reads)
+        .filter(|(before, after)| before != after)
+        .map(|(before, after)| after - before)
+        .collect::<Vec<_>>();
+    assert_eq!(changed, [30]);
+
+    drop(memberships);
+    first.stop();
+    second.stop();
+    third.stop();
+    assert_eq!(first_handle.await.unwrap(), ActorExit::Stopped);
+    assert_eq!(second_handle.await.unwrap(), ActorExit::Stopped);
+    assert_eq!(third_handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn sticky_moves_on_once_its_member_fails() {
+    let cluster = cluster();
+    let (first, first_handle) = worker(&cluster).await;
+    let (second, second_handle) = worker(&cluster).await;
+
+    let group = ProcessGroup::with_strategy(Strategy::Sticky);
+    let _first = group.join(first.broker());
+    let _second = group.join(second.broker());
+    for _ in 0..3 {
+        group.send(Work(1)).unwrap();
+    }
+    assert_eq!(reads(&[&first, &second]).await, [3, 0]);
+
+    first.stop();
+    assert_eq!(first_handle.await.unwrap(), ActorExit::Stopped);
+    for _ in 0..2 {
+        group.send(Work(1)).unwrap();
+    }
+    assert_eq!(reads(&[&second]).await, [2]);
+    assert_eq!(group.len(), 1);
+
+    second.stop();
+    assert_eq!(second_handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Tick;
+
+impl Handler<Tick> for Worker {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        Tick: Tick,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        *state += 1;
+        Ok(())
+    }
+}
+
+impl Handler<Tick> for BlockedWorker {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        Tick: Tick,
+        _state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[compio_macros::test]
+async fn broadcast_reports_rejecting_members() {
+    let cluster = cluster();
+    let (first, first_handle) = worker(&cluster).await;
+    let (second, second_handle) = worker(&cluster).await;
+    let (stopped, stopped_handle) = worker(&cluster).await;
+    let (blocked, blocked_handle, release) = blocked_worker(&cluster, 1).await;
+    blocked.send(Tick).unwrap();
+
+    let group = ProcessGroup::new();
+    let _first = group.join(first.broker());
+    let _second = group.join(second.broker());
+    let _stopped = group.join(stopped.broker());
+    let _blocked = group.join(blocked.broker());
+    stopped.stop();
+    assert_eq!(stopped_handle.await.unwrap(), ActorExit::Stopped);
+
+    let rejected = group.broadcast(Tick);
+    assert_eq!(rejected.len(), 2);
+    assert_eq!(rejected[0].1, DeliverError::Closed(Tick));
+    assert_eq!(rejected[1].1, DeliverError::Full(Tick));
+    assert_eq!(group.len(), 3);
+    assert_eq!(reads(&[&first, &second]).await, [1, 1]);
+
+    first.stop();
+    second.stop();
+    blocked.stop();
+    release.send(()).ok();
+    assert_eq!(first_handle.await.unwrap(), ActorExit::Stopped);
+    assert_eq!(second_handle.await.unwrap(), ActorExit::Stopped);
+    assert_eq!(blocked_handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+}
+
+#[derive(Clone, Debug)]
+struct Gather;
+
+impl Handler<Call<Gather, usize>> for Worker {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        call: Call<Gather, usize>,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        call.reply(*state).ok();
+        Ok(())
+    }
+}
+
+impl Handler<Call<Gather, usize>> for BlockedWorker {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        call: Call<Gather, usize>,
+        _state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        call.reply(usize::MAX).ok();
+        Ok(())
+    }
+}
+
+#[compio_macros::test]
+async fn call_all_gathers_the_replies_in_time() {
+    let cluster = cluster();
+    let (first, first_handle) = worker(&cluster).await;
+    let (second, second_handle) = worker(&cluster).await;
+    let (blocked, blocked_handle, release) = blocked_worker(&cluster, 4).await;
+    first.send(Work(1)).unwrap();
+    second.send(Work(2)).unwrap();
+
+    let group = ProcessGroup::new();
+    let _first = group.join(first.broker::<Call<Gather, usize>>());
+    let _blocked = group.join(blocked.broker::<Call<Gather, usize>>());
+    let _second = group.join(second.broker::<Call<Gather, usize>>());
+    let mut replies = group.call_all(Gather, Duration::from_millis(200)).await;
+    replies.sort_unstable();
+    assert_eq!(replies, [1, 2]);
+
+    let timeout = Duration::from_millis(50);
+    assert_eq!(group.call_timeout(Gather, timeout).await.unwrap(), 1);
+    let error = group.call_timeout(Gather, timeout).await.unwrap_err();
+    assert!(matches!(error, CallError::Timeout(Some(Gather))));
+    assert_eq!(group.call_timeout(Gather, timeout).await.unwrap(), 2);
+
+    first.stop();
+    second.stop();
+    blocked.stop();
+    release.send(()).ok();
+    assert_eq!(first_handle.await.unwrap(), ActorExit::Stopped);
+    assert_eq!(second_handle.await.unwrap(), ActorExit::Stopped);
+    assert_eq!(blocked_handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+}
+
+#[derive(Debug)]
+struct Whoami;
+
+/// Joins the "whoami" group of its cluster once started.
+struct Joiner;
+
+impl Actor for Joiner {
+    type Arguments = ();
+    type Error = Infallible;
+    type State = Option<Membership<Call<Whoami, ActorId>>>;
+
+    async fn pre_start(
+        &self,
+        _myself: &Mailbox<Self>,
+        (): Self::Arguments,
+    ) -> Result<Self::State, Self::Error> {
+        Ok(None)
+    }
+
+    async fn post_start(
+        &self,
+        myself: &Mailbox<Self>,
+        membership: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        let group = Cluster::current().group("whoami");
+        *membership = Some(group.join(myself.broker()));
+        Ok(())
+    }
+}
+
+impl Handler<Call<Whoami, ActorId>> for Joiner {
+    async fn handle(
+        &self,
+        myself: &Mailbox<Self>,
+        call: Call<Whoami, ActorId>,
+        _state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        call.reply(myself.id()).ok();
+        Ok(())
+    }
+}
+
+/// Forwards the membership changes of a group to the test.
+struct Watcher;
+
+impl Actor for Watcher {
+    type Arguments = mpsc::Sender<GroupEvent<Call<Whoami, ActorId>>>;
+    type Error = Infallible;
+    type State = Self::Arguments;
+
+    async fn pre_start(
+        &self,
+        _myself: &Mailbox<Self>,
+        events: Self::Arguments,
+    ) -> Result<Self::State, Self::Error> {
+        Ok(events)
+    }
+}
+
+impl Handler<GroupEvent<Call<Whoami, ActorId>>> for Watcher {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        event: GroupEvent<Call<Whoami, ActorId>>,
+        events: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        events.send(event).unwrap();
+        Ok(())
+    }
+}
+
+#[compio_macros::test]
+async fn named_groups_are_joined_from_actors_and_report_membership() {
+    let cluster = cluster();
+    let group = cluster.group::<Call<Whoami, ActorId>, _>("whoami");
+    assert!(group.is_empty());
+
+    // Answering a call means `post_start` has joined the group.
+    let (first, first_handle) = cluster.spawn(|| Joiner, ()).await.unwrap();
+    assert_eq!(first.call(Whoami).await.unwrap(), first.id());
+    assert_eq!(group.len(), 1);
+    assert_eq!(group.call(Whoami).await.unwrap(), first.id());
+    assert!(cluster.group::<Work, _>("whoami").is_empty());
+
+    let (events_tx, events) = mpsc::channel();
+    let (watcher, watcher_handle) = cluster.spawn(|| Watcher, events_tx).await.unwrap();
+    let subscription = group.subscribe(watcher.broker());
+    let timeout = Duration::from_secs(2);
+    match events.recv_timeout(timeout).unwrap() {
+        GroupEvent::Joined(broker) => assert_eq!(broker.id(), first.id()),
+        event => panic!("unexpected event {event:?}"),
+    }
+
+    let (second, second_handle) = cluster.spawn(|| Joiner, ()).await.unwrap();
+    match events.recv_timeout(timeout).unwrap() {
+        GroupEvent::Joined(broker) => assert_eq!(broker.id(), second.id()),
+        event => panic!("unexpected event {event:?}"),
+    }
+    let named = cluster.group::<Call<Whoami, ActorId>, _>(String::from("whoami"));
+    assert_eq!(named.len(), 2);
+
+    first.stop();
+    assert_eq!(first_handle.await.unwrap(), ActorExit::Stopped);
+    assert!(matches!(
+        events.recv_timeout(timeout).unwrap(),
+        GroupEvent::Left(id) if id == first.id()
+    ));
+    assert_eq!(group.call(Whoami).await.unwrap(), second.id());
+
+    subscription.unsubscribe();
+    second.stop();
+    assert_eq!(second_handle.await.unwrap(), ActorExit::Stopped);
+    assert!(group.is_empty());
+    assert!(events.recv_timeout(Duration::from_millis(50)).is_err());
+
+    watcher.stop();
+    assert_eq!(watcher_handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+}
@@ -0,0 +1,286 @@
+use std::{convert::Infallible, io, num::NonZeroUsize, panic::AssertUnwindSafe};
+
+use compio_actor::{
+    Actor, ActorExit, Call, Cluster, Handler, Mailbox,
+    mailbox::{CallError, DeliverError},
+    remote::RemoteNode,
+};
+use compio_dispatcher::Dispatcher;
+use compio_io::framed::codec::serde_json::SerdeJsonCodec;
+use compio_net::{TcpListener, TcpStream};
+use futures_util::FutureExt;
+use serde::{Deserialize, Serialize};
+
+fn cluster() -> Cluster {
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(2).unwrap())
+        .build()
+        .unwrap();
+    Cluster::from_dispatcher(dispatcher)
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Add(u32);
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Total;
+
+/// Asks for `text` repeated `times` times.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Repeat {
+    text: String,
+    times: usize,
+}
+
+struct Counter;
+
+impl Actor for Counter {
+    type Arguments = ();
+    type Error = Infallible;
+    type State = u32;
+
+    async fn pre_start(
+        &self,
+        _myself: &Mailbox<Self>,
+        (): Self::Arguments,
+    ) -> Result<Self::State, Self::Error> {
+        Ok(0)
+    }
+}
+
+impl Handler<Add> for Counter {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        Add(value): Add,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        *state += value;
+        Ok(())
+    }
+}
+
+impl Handler<Call<Total, u32>> for Counter {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        call: Call<Total, u32>,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        call.reply(*state).ok();
+        Ok(())
+    }
+}
+
+impl Handler<Call<Repeat, String>> for Counter {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        call: Call<Repeat, String>,
+        _state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        let Repeat { text, times } = call.message();
+        let reply = text.repeat(*times);
+        call.reply(reply).ok();
+        Ok(())
+    }
+}
+
+/// Spawns a counter and exposes it as `add` and `total`.
+async fn expose_counter(
+    cluster: &Cluster,
+) -> (Mailbox<Counter>, compio_actor::ActorHandle<Infallible>) {
+    let (counter, handle) = cluster.spawn(|| Counter, ()).await.unwrap();
+    assert!(cluster.expose("add", counter.broker::<Add>(), SerdeJsonCodec::new()));
+    assert!(cluster.expose_call(
+        "total",
+        counter.broker::<Call<Total, u32>>(),
+        SerdeJsonCodec::new()
+    ));
+    assert!(!cluster.expose("add", counter.broker::<Add>(), SerdeJsonCodec::new()));
+    (counter, handle)
+}
+
+#[compio_macros::test]
+async fn remote_brokers_send_and_call_over_tcp() {
+    let cluster = cluster();
+    let (counter, handle) = expose_counter(&cluster).await;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = cluster.clone();
+    let listening = compio_runtime::spawn(async move { server.listen(listener).await });
+
+    let node = RemoteNode::connect(TcpStream::connect(addr).await.unwrap());
+    let add = node
+        .lookup::<Add, _>("add", SerdeJsonCodec::new())
+        .await
+        .unwrap()
+        .unwrap();
+    let total = node
+        .lookup_call::<Total, u32, _>("total", SerdeJsonCodec::new())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(
+        node.lookup::<Add, _>("missing", SerdeJsonCodec::new())
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert_eq!(add.name(), "add");
+
+    // A name is only looked up as it's exposed.
+    let err = node
+        .lookup::<Total, _>("total", SerdeJsonCodec::new())
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let err = node
+        .lookup_call::<Add, u32, _>("add", SerdeJsonCodec::new())
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+    add.send(Add(2)).unwrap();
+    add.send(Add(3)).unwrap();
+    assert_eq!(total.call(Total).await.unwrap(), 5);
+
+    assert!(cluster.unexpose("total"));
+    assert_eq!(total.call(Total).await, Err(CallError::NoReply));
+    assert!(
+        node.lookup::<Add, _>("total", SerdeJsonCodec::new())
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    drop(listening);
+    counter.stop();
+    assert_eq!(handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn a_disconnection_closes_remote_brokers() {
+    let cluster = cluster();
+    let (counter, handle) = expose_counter(&cluster).await;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (stream, accepted) = futures_util::join!(TcpStream::connect(addr), listener.accept());
+    let (accepted, _) = accepted.unwrap();
+    let serving = compio_runtime::spawn(cluster.clone().serve(accepted));
+
+    let node = RemoteNode::connect(stream.unwrap());
+    let add = node
+        .lookup::<Add, _>("add", SerdeJsonCodec::new())
+        .await
+        .unwrap()
+        .unwrap();
+    let total = node
+        .lookup_call::<Total, u32, _>("total", SerdeJsonCodec::new())
+        .await
+        .unwrap()
+        .unwrap();
+    add.send(Add(1)).unwrap();
+    assert_eq!(total.call(Total).await.unwrap(), 1);
+
+    drop(serving);
+    match total.call(Total).await {
+        Err(CallError::NoReply | CallError::Closed(Total)) => {}
+        result => panic!("unexpected call result {result:?}"),
+    }
+    assert!(add.is_closed());
+    assert!(node.is_closed());
+    assert_eq!(add.send(Add(1)), Err(DeliverError::Closed(Add(1))));
+    assert!(
+        node.lookup::<Add, _>("add", SerdeJsonCodec::new())
+            .await
+            .is_err()
+    );
+
+    counter.stop();
+    assert_eq!(handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn oversized_messages_keep_the_connection_up() {
+    let cluster = cluster();
+    let (counter, handle) = expose_counter(&cluster).await;
+    assert!(cluster.expose_call(
+        "repeat",
+        counter.broker::<Call<Repeat, String>>(),
+        SerdeJsonCodec::new(),
+    ));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = cluster.clone();
+    let listening = compio_runtime::spawn(async move { server.listen(listener).await });
+
+    let node = RemoteNode::connect(TcpStream::connect(addr).await.unwrap());
+    let repeat = node
+        .lookup_call::<Repeat, String, _>("repeat", SerdeJsonCodec::new())
+        .await
+        .unwrap()
+        .unwrap();
+    let huge = 9 * 1024 * 1024;
+
+    let request = Repeat {
+        text: "a".repeat(huge),
+        times: 1,
+    };
+    let sent = AssertUnwindSafe(repeat.call(request)).catch_unwind().await;
+    assert!(sent.is_err());
+    let request = Repeat {
+        text: "a".into(),
+        times: huge,
+    };
+    assert_eq!(repeat.call(request).await, Err(CallError::NoReply));
+
+    let request = Repeat {
+        text: "ab".into(),
+        times: 2,
+    };
+    assert_eq!(repeat.call(request).await.unwrap(), "abab");
+    assert!(!node.is_closed());
+
+    drop(listening);
+    counter.stop();
+    assert_eq!(handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+}
+
+#[cfg(unix)]
+#[compio_macros::test]
+async fn remote_brokers_call_over_unix_sockets() {
+    use compio_net::{UnixListener, UnixStream};
+
+    let cluster = cluster();
+    let (counter, handle) = expose_counter(&cluster).await;
+    let path =
+        std::env::temp_dir().join(format!("compio-actor-remote-{}.sock", std::process::id()));
+    std::fs::remove_file(&path).ok();
+    let listener = UnixListener::bind(&path).await.unwrap();
+    let server = cluster.clone();
+    let listening = compio_runtime::spawn(async move { server.listen(listener).await });
+
+    let node = RemoteNode::connect(UnixStream::connect(&path).await.unwrap());
+    let add = node
+        .lookup::<Add, _>("add", SerdeJsonCodec::new())
+        .await
+        .unwrap()
+        .unwrap();
+    let total = node
+        .lookup_call::<Total, u32, _>("total", SerdeJsonCodec::new())
+        .await
+        .unwrap()
+        .unwrap();
+    add.send(Add(4)).unwrap();
+    assert_eq!(total.call(Total).await.unwrap(), 4);
+
+    drop(listening);
+    std::fs::remove_file(&path).ok();
+    counter.stop();
+    assert_eq!(handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+}
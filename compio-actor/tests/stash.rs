@@ -0,0 +1,181 @@
+use std::{convert::Infallible, num::NonZeroUsize, sync::mpsc, time::Duration};
+
+use compio_actor::{
+    Actor, ActorExit, Call, Cluster, Handler, Mailbox,
+    mailbox::{CallError, DeliverError},
+};
+use compio_dispatcher::Dispatcher;
+
+fn cluster() -> Cluster {
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(1).unwrap())
+        .build()
+        .unwrap();
+    Cluster::from_dispatcher(dispatcher)
+}
+
+#[derive(Debug)]
+struct Unlock;
+
+#[derive(Debug, PartialEq, Eq)]
+struct Enter(&'static str);
+
+#[derive(Debug, PartialEq, Eq)]
+struct Knock;
+
+#[derive(Debug, PartialEq, Eq)]
+struct Stashed;
+
+/// Lets visitors in once unlocked, stashing the ones that come earlier.
+struct Door;
+
+struct DoorState {
+    open: bool,
+    log: mpsc::Sender<&'static str>,
+}
+
+impl Actor for Door {
+    type Arguments = mpsc::Sender<&'static str>;
+    type Error = Infallible;
+    type State = DoorState;
+
+    async fn pre_start(
+        &self,
+        _myself: &Mailbox<Self>,
+        log: Self::Arguments,
+    ) -> Result<Self::State, Self::Error> {
+        Ok(DoorState { open: false, log })
+    }
+
+    async fn on_transition(
+        &self,
+        myself: &Mailbox<Self>,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        assert_eq!(myself.stashed(), 0);
+        state.log.send("transition").unwrap();
+        Ok(())
+    }
+}
+
+impl Handler<Unlock> for Door {
+    async fn handle(
+        &self,
+        myself: &Mailbox<Self>,
+        Unlock: Unlock,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        state.open = true;
+        myself.unstash_all();
+        Ok(())
+    }
+}
+
+impl Handler<Enter> for Door {
+    async fn handle(
+        &self,
+        myself: &Mailbox<Self>,
+        message: Enter,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        if state.open {
+            state.log.send(message.0).unwrap();
+        } else if let Err(DeliverError::Full(Enter(name))) = myself.stash(message) {
+            assert_eq!(name, "c");
+            state.log.send("full").unwrap();
+        }
+        Ok(())
+    }
+}
+
+impl Handler<Call<Knock, &'static str>> for Door {
+    async fn handle(
+        &self,
+        myself: &Mailbox<Self>,
+        call: Call<Knock, &'static str>,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        if state.open {
+            call.reply("come in").ok();
+        } else {
+            myself.stash(call).unwrap();
+        }
+        Ok(())
+    }
+}
+
+impl Handler<Call<Stashed, usize>> for Door {
+    async fn handle(
+        &self,
+        myself: &Mailbox<Self>,
+        call: Call<Stashed, usize>,
+        _state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        call.reply(myself.stashed()).ok();
+        Ok(())
+    }
+}
+
+fn received(log: &mpsc::Receiver<&'static str>, count: usize) -> Vec<&'static str> {
+    (0..count)
+        .map(|_| log.recv_timeout(Duration::from_secs(2)).unwrap())
+        .collect()
+}
+
+#[compio_macros::test]
+async fn unstashed_messages_run_after_the_transition_and_before_newer_ones() {
+    let cluster = cluster();
+    let (log_tx, log) = mpsc::channel();
+    let (door, handle) = cluster
+        .spawn(|| Door, log_tx)
+        .with_capacity(NonZeroUsize::new(2).unwrap())
+        .await
+        .unwrap();
+
+    door.send(Enter("a")).unwrap();
+    assert_eq!(door.call(Stashed).await.unwrap(), 1);
+    door.send(Enter("b")).unwrap();
+    assert_eq!(door.call(Stashed).await.unwrap(), 2);
+    door.send(Enter("c")).unwrap();
+    assert_eq!(door.call(Stashed).await.unwrap(), 2);
+    assert_eq!(door.queued(), 0);
+
+    door.send(Unlock).unwrap();
+    door.send(Enter("d")).unwrap();
+    assert_eq!(received(&log, 5), ["full", "transition", "a", "b", "d"]);
+    assert_eq!(door.call(Knock).await.unwrap(), "come in");
+
+    door.stop();
+    assert_eq!(handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn stashed_calls_are_answered_after_unstashing_or_dropped_on_stop() {
+    let cluster = cluster();
+    let (log_tx, _log) = mpsc::channel();
+    let (door, handle) = cluster.spawn(|| Door, log_tx).await.unwrap();
+
+    let (knocked, ()) = futures_util::join!(door.call(Knock), async {
+        assert_eq!(door.call(Stashed).await.unwrap(), 1);
+        door.send(Unlock).unwrap();
+    });
+    assert_eq!(knocked, Ok("come in"));
+
+    let (log_tx, _log) = mpsc::channel();
+    let (locked, locked_handle) = cluster.spawn(|| Door, log_tx).await.unwrap();
+    let (knocked, ()) = futures_util::join!(locked.call(Knock), async {
+        assert_eq!(locked.call(Stashed).await.unwrap(), 1);
+        locked.stop();
+    });
+    assert_eq!(knocked, Err(CallError::NoReply));
+    assert_eq!(
+        locked.stash(Enter("late")),
+        Err(DeliverError::Closed(Enter("late")))
+    );
+
+    door.stop();
+    assert_eq!(handle.await.unwrap(), ActorExit::Stopped);
+    assert_eq!(locked_handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+}
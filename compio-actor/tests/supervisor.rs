@@ -1,7 +1,19 @@
-use std::{convert::Infallible, num::NonZeroUsize};
+use std::{
+    convert::Infallible,
+    num::NonZeroUsize,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
 
 use compio_actor::{
-    Actor, ActorExit, ActorHandle, Call, Cluster, Handler, Mailbox, supervisor::SupervisionEvent,
+    Actor, ActorExit, ActorHandle, Call, Cluster, Handler, Mailbox,
+    cluster::SpawnError,
+    supervisor::{
+        ChildSpec, Restart, Strategy, SupervisionEvent, Supervisor, SupervisorError, SupervisorSpec,
+    },
 };
 use compio_dispatcher::Dispatcher;
 
@@ -345,3 +357,235 @@ async fn a_supervisor_can_respawn_a_failed_named_actor() {
     assert_eq!(parent_handle.await.unwrap(), ActorExit::Stopped);
     cluster.join().await.unwrap();
 }
+
+struct Worker;
+
+impl Actor for Worker {
+    type Arguments = Arc<AtomicUsize>;
+    type Error = &'static str;
+    type State = ();
+
+    async fn pre_start(
+        &self,
+        _myself: &Mailbox<Self>,
+        starts: Self::Arguments,
+    ) -> Result<Self::State, Self::Error> {
+        starts.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+impl Handler<Stop> for Worker {
+    async fn handle(
+        &self,
+        myself: &Mailbox<Self>,
+        _stop: Stop,
+        _state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        myself.stop();
+        Ok(())
+    }
+}
+
+impl Handler<Fail> for Worker {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        Fail: Fail,
+        _state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        Err("worker failed")
+    }
+}
+
+struct Refusing;
+
+impl Actor for Refusing {
+    type Arguments = ();
+    type Error = &'static str;
+    type State = ();
+
+    async fn pre_start(
+        &self,
+        _myself: &Mailbox<Self>,
+        (): Self::Arguments,
+    ) -> Result<Self::State, Self::Error> {
+        Err("refused")
+    }
+}
+
+fn worker(id: &'static str, starts: &Arc<AtomicUsize>) -> ChildSpec {
+    ChildSpec::new(id, || Worker, starts.clone()).with_name(id)
+}
+
+fn counters<const N: usize>() -> [Arc<AtomicUsize>; N] {
+    std::array::from_fn(|_| Arc::new(AtomicUsize::new(0)))
+}
+
+fn starts<const N: usize>(counters: &[Arc<AtomicUsize>; N]) -> [usize; N] {
+    counters
+        .each_ref()
+        .map(|counter| counter.load(Ordering::SeqCst))
+}
+
+async fn wait_until(mut condition: impl FnMut() -> bool) {
+    for _ in 0..500 {
+        if condition() {
+            return;
+        }
+        compio_runtime::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("condition not reached");
+}
+
+fn send_to_worker<M>(cluster: &Cluster, id: &str, message: M)
+where
+    Worker: Handler<M>,
+    M: Send + 'static,
+{
+    cluster
+        .lookup::<Worker, _>(id.to_owned())
+        .unwrap()
+        .send(message)
+        .ok()
+        .unwrap();
+}
+
+async fn restarts_with(strategy: Strategy, failing: &str, expected: [usize; 3]) {
+    let cluster = cluster();
+    let counters = counters::<3>();
+    let spec = SupervisorSpec::new(strategy)
+        .with_child(worker("a", &counters[0]))
+        .with_child(worker("b", &counters[1]))
+        .with_child(worker("c", &counters[2]));
+    let (supervisor, handle) = cluster.spawn(|| Supervisor, spec).await.unwrap();
+    assert_eq!(starts(&counters), [1, 1, 1]);
+
+    send_to_worker(&cluster, failing, Fail);
+    wait_until(|| starts(&counters) == expected).await;
+    wait_until(|| {
+        ["a", "b", "c"]
+            .iter()
+            .all(|id| cluster.lookup::<Worker, _>(*id).is_some())
+    })
+    .await;
+
+    supervisor.stop();
+    assert_eq!(handle.await.unwrap(), ActorExit::Stopped);
+    assert!(cluster.lookup::<Worker, _>("a").is_none());
+    assert_eq!(starts(&counters), expected);
+    cluster.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn one_for_one_restarts_the_failed_child() {
+    restarts_with(Strategy::OneForOne, "b", [1, 2, 1]).await;
+}
+
+#[compio_macros::test]
+async fn one_for_all_restarts_every_child() {
+    restarts_with(Strategy::OneForAll, "b", [2, 2, 2]).await;
+}
+
+#[compio_macros::test]
+async fn rest_for_one_restarts_the_later_children() {
+    restarts_with(Strategy::RestForOne, "b", [1, 2, 2]).await;
+}
+
+#[compio_macros::test]
+async fn restart_types_decide_whether_a_child_comes_back() {
+    let cluster = cluster();
+    let counters = counters::<3>();
+    let spec = SupervisorSpec::new(Strategy::OneForOne)
+        .with_intensity(10, Duration::from_secs(60))
+        .with_child(worker("permanent", &counters[0]))
+        .with_child(worker("transient", &counters[1]).with_restart(Restart::Transient))
+        .with_child(worker("temporary", &counters[2]).with_restart(Restart::Temporary));
+    let (supervisor, handle) = cluster.spawn(|| Supervisor, spec).await.unwrap();
+
+    send_to_worker(&cluster, "permanent", Stop);
+    wait_until(|| starts(&counters) == [2, 1, 1]).await;
+
+    send_to_worker(&cluster, "transient", Fail);
+    wait_until(|| starts(&counters) == [2, 2, 1]).await;
+    wait_until(|| cluster.lookup::<Worker, _>("transient").is_some()).await;
+    send_to_worker(&cluster, "transient", Stop);
+    wait_until(|| cluster.lookup::<Worker, _>("transient").is_none()).await;
+
+    send_to_worker(&cluster, "temporary", Fail);
+    wait_until(|| cluster.lookup::<Worker, _>("temporary").is_none()).await;
+
+    wait_until(|| cluster.lookup::<Worker, _>("permanent").is_some()).await;
+    send_to_worker(&cluster, "permanent", Stop);
+    wait_until(|| starts(&counters) == [3, 2, 1]).await;
+
+    supervisor.stop();
+    assert_eq!(handle.await.unwrap(), ActorExit::Stopped);
+    assert_eq!(starts(&counters), [3, 2, 1]);
+    cluster.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn too_many_restarts_fail_the_supervisor() {
+    let cluster = cluster();
+    let counters = counters::<2>();
+    let spec = SupervisorSpec::new(Strategy::OneForOne)
+        .with_intensity(1, Duration::from_secs(60))
+        .with_child(worker("a", &counters[0]))
+        .with_child(worker("b", &counters[1]));
+    let (_supervisor, handle) = cluster.spawn(|| Supervisor, spec).await.unwrap();
+
+    send_to_worker(&cluster, "a", Fail);
+    wait_until(|| cluster.lookup::<Worker, _>("a").is_some() && starts(&counters) == [2, 1]).await;
+    send_to_worker(&cluster, "a", Fail);
+
+    assert_eq!(
+        handle.await.unwrap(),
+        ActorExit::Failed(SupervisorError::TooManyRestarts)
+    );
+    assert!(cluster.lookup::<Worker, _>("a").is_none());
+    assert!(cluster.lookup::<Worker, _>("b").is_none());
+    assert_eq!(starts(&counters), [2, 1]);
+    cluster.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn a_failed_supervisor_is_restarted_by_its_parent() {
+    let cluster = cluster();
+    let counters = counters::<1>();
+    let inner = SupervisorSpec::new(Strategy::OneForOne)
+        .with_intensity(0, Duration::from_secs(60))
+        .with_child(worker("a", &counters[0]));
+    let outer = SupervisorSpec::new(Strategy::OneForOne).with_child(ChildSpec::new(
+        "inner",
+        || Supervisor,
+        inner,
+    ));
+    let (supervisor, handle) = cluster.spawn(|| Supervisor, outer).await.unwrap();
+
+    send_to_worker(&cluster, "a", Fail);
+    wait_until(|| cluster.lookup::<Worker, _>("a").is_some() && starts(&counters) == [2]).await;
+
+    supervisor.stop();
+    assert_eq!(handle.await.unwrap(), ActorExit::Stopped);
+    assert!(cluster.lookup::<Worker, _>("a").is_none());
+    cluster.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn a_child_failing_to_start_fails_the_supervisor() {
+    let cluster = cluster();
+    let counters = counters::<1>();
+    let spec = SupervisorSpec::new(Strategy::OneForOne)
+        .with_child(worker("a", &counters[0]))
+        .with_child(ChildSpec::new("refusing", || Refusing, ()));
+
+    let result = cluster.spawn(|| Supervisor, spec).await;
+    assert!(matches!(
+        result,
+        Err(SpawnError::Start(SupervisorError::Start(id))) if id == "refusing"
+    ));
+    assert!(cluster.lookup::<Worker, _>("a").is_none());
+    assert_eq!(starts(&counters), [1]);
+    cluster.join().await.unwrap();
+}
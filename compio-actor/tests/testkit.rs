@@ -0,0 +1,299 @@
+use std::{fmt, num::NonZeroUsize, time::Duration};
+
+use compio_actor::{
+    Actor, ActorExit, Call, Cluster, Handler, Mailbox,
+    mailbox::{Down, ExitReason, Priority},
+    supervisor::SupervisionEvent,
+    testkit::{TestActor, TestClock, TestProbe},
+};
+use compio_dispatcher::Dispatcher;
+
+#[derive(Debug, PartialEq, Eq)]
+struct Broken;
+
+impl fmt::Display for Broken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("broken")
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Total;
+
+#[derive(Debug, PartialEq, Eq)]
+struct Idled;
+
+#[derive(Debug, Default)]
+struct CounterState {
+    total: u32,
+    handled: Vec<u32>,
+    idle: u32,
+}
+
+/// Adds numbers, and fails on zero.
+struct Counter;
+
+impl Actor for Counter {
+    type Arguments = ();
+    type Error = Broken;
+    type State = CounterState;
+
+    async fn pre_start(
+        &self,
+        _myself: &Mailbox<Self>,
+        (): Self::Arguments,
+    ) -> Result<Self::State, Self::Error> {
+        Ok(CounterState::default())
+    }
+
+    fn idle_timeout(&self) -> Option<Duration> {
+        Some(Duration::from_millis(1))
+    }
+
+    async fn on_idle(
+        &self,
+        _myself: &Mailbox<Self>,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        state.idle += 1;
+        Ok(())
+    }
+}
+
+impl Handler<u32> for Counter {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        value: u32,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        if value == 0 {
+            return Err(Broken);
+        }
+        state.total += value;
+        state.handled.push(value);
+        Ok(())
+    }
+}
+
+impl Handler<Call<Total, u32>> for Counter {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        call: Call<Total, u32>,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        call.reply(state.total).ok();
+        Ok(())
+    }
+}
+
+impl Handler<Call<Idled, u32>> for Counter {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        call: Call<Idled, u32>,
+        state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        call.reply(state.idle).ok();
+        Ok(())
+    }
+}
+
+/// Counts the failures of its counters.
+struct Watcher;
+
+impl Actor for Watcher {
+    type Arguments = ();
+    type Error = Broken;
+    type State = u32;
+
+    async fn pre_start(
+        &self,
+        _myself: &Mailbox<Self>,
+        (): Self::Arguments,
+    ) -> Result<Self::State, Self::Error> {
+        Ok(0)
+    }
+}
+
+impl Handler<SupervisionEvent<Counter>> for Watcher {
+    async fn handle(
+        &self,
+        _myself: &Mailbox<Self>,
+        event: SupervisionEvent<Counter>,
+        failures: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        if let SupervisionEvent::ActorFailed(_) = event {
+            *failures += 1;
+        }
+        Ok(())
+    }
+}
+
+#[compio_macros::test]
+async fn test_actors_only_handle_messages_when_stepped() {
+    let mut counter = TestActor::spawn(Counter, ()).await.unwrap();
+    counter.mailbox().send(1).unwrap();
+    counter.mailbox().send(2).unwrap();
+    counter
+        .mailbox()
+        .send_with_priority(3, Priority::High)
+        .unwrap();
+    compio_runtime::time::sleep(Duration::from_millis(10)).await;
+    assert!(counter.state().handled.is_empty());
+    assert_eq!(counter.state().idle, 0);
+
+    assert_eq!(counter.step().await, Some(Ok(())));
+    assert_eq!(counter.state().handled, [3]);
+    assert_eq!(counter.run_until_idle().await, Ok(2));
+    assert_eq!(counter.state().handled, [3, 1, 2]);
+    assert_eq!(counter.step().await, None);
+
+    counter.handle(4).await.unwrap();
+    assert_eq!(counter.state().total, 10);
+    counter.idle().await.unwrap();
+    assert_eq!(counter.state().idle, 1);
+    counter.state_mut().total = 0;
+
+    counter.mailbox().send(5).unwrap();
+    counter.mailbox().send(0).unwrap();
+    counter.mailbox().send(6).unwrap();
+    assert_eq!(counter.run_until_idle().await, Err(Broken));
+    assert_eq!(counter.state().total, 5);
+    assert_eq!(counter.fail(Broken).await, ActorExit::Failed(Broken));
+}
+
+#[compio_macros::test]
+async fn probes_record_messages_calls_and_exits() {
+    let numbers = TestProbe::<u32>::new();
+    numbers.broker().send(1).unwrap();
+    assert_eq!(numbers.try_recv(), Some(1));
+    assert_eq!(numbers.try_recv(), None);
+    numbers.expect_no_msg(Duration::from_millis(10)).await;
+
+    let totals = TestProbe::<Call<Total, u32>>::new();
+    let broker = totals.broker();
+    let (total, ()) = futures_util::join!(broker.call(Total), async {
+        let call = totals.expect_msg(Duration::from_secs(1)).await;
+        assert_eq!(call.message(), &Total);
+        call.reply(7).unwrap();
+    });
+    assert_eq!(total, Ok(7));
+
+    let downs = TestProbe::<Down>::new();
+    let counter = TestActor::spawn(Counter, ()).await.unwrap();
+    downs.mailbox().monitor(counter.mailbox());
+    let id = counter.mailbox().id();
+    downs.expect_no_msg(Duration::from_millis(10)).await;
+    assert_eq!(counter.stop().await, ActorExit::Stopped);
+    assert_eq!(
+        downs.expect_msg(Duration::from_secs(1)).await,
+        Down {
+            id,
+            exit: ExitReason::Stopped
+        }
+    );
+}
+
+#[compio_macros::test]
+async fn supervision_events_can_be_observed_and_injected() {
+    let events = TestProbe::<SupervisionEvent<Counter>>::new();
+    let counter = TestActor::spawn(Counter, ())
+        .with_supervisor(events.mailbox())
+        .await
+        .unwrap();
+    let id = counter.mailbox().id();
+    match events.expect_msg(Duration::from_secs(1)).await {
+        SupervisionEvent::ActorStarted(actor) => assert_eq!(actor.id(), id),
+        event => panic!("unexpected event {event:?}"),
+    }
+
+    let mut watcher = TestActor::spawn(Watcher, ()).await.unwrap();
+    watcher
+        .handle(SupervisionEvent::ActorStarted(counter.mailbox().clone()))
+        .await
+        .unwrap();
+    watcher
+        .handle(SupervisionEvent::ActorFailed(counter.mailbox().clone()))
+        .await
+        .unwrap();
+    assert_eq!(*watcher.state(), 1);
+
+    assert_eq!(counter.fail(Broken).await, ActorExit::Failed(Broken));
+    let failed = events.expect_msg(Duration::from_secs(1)).await;
+    assert!(matches!(failed, SupervisionEvent::ActorFailed(_)));
+    watcher.handle(failed).await.unwrap();
+    assert_eq!(*watcher.state(), 2);
+    assert_eq!(watcher.stop().await, ActorExit::Stopped);
+}
+
+/// Waits until `count` timers are waiting on `clock`.
+async fn sleepers(clock: &TestClock, count: usize) {
+    while clock.sleepers() != count {
+        compio_runtime::time::sleep(Duration::from_millis(1)).await;
+    }
+}
+
+#[compio_macros::test]
+async fn timers_wait_for_the_test_clock() {
+    let clock = TestClock::new();
+    let mut counter = TestActor::spawn(Counter, ())
+        .with_clock(&clock)
+        .await
+        .unwrap();
+    counter
+        .mailbox()
+        .send_after(5, Duration::from_millis(50))
+        .detach();
+    let interval = counter
+        .mailbox()
+        .send_interval(|| 1, Duration::from_millis(20));
+    sleepers(&clock, 2).await;
+    compio_runtime::time::sleep(Duration::from_millis(60)).await;
+    assert_eq!(counter.run_until_idle().await, Ok(0));
+
+    // The tick at 20ms is missed, like with an `Interval`.
+    clock.advance(Duration::from_millis(40));
+    sleepers(&clock, 2).await;
+    assert_eq!(counter.run_until_idle().await, Ok(1));
+    clock.advance(Duration::from_millis(10));
+    sleepers(&clock, 1).await;
+    clock.advance(Duration::from_millis(10));
+    sleepers(&clock, 1).await;
+    assert_eq!(counter.run_until_idle().await, Ok(2));
+    assert_eq!(counter.state().handled, [1, 5, 1]);
+    assert_eq!(clock.elapsed(), Duration::from_millis(60));
+
+    interval.cancel();
+    sleepers(&clock, 0).await;
+    assert_eq!(counter.stop().await, ActorExit::Stopped);
+}
+
+#[compio_macros::test]
+async fn cluster_actors_idle_by_the_test_clock() {
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(1).unwrap())
+        .build()
+        .unwrap();
+    let cluster = Cluster::from_dispatcher(dispatcher);
+    let clock = TestClock::new();
+    let (counter, handle) = cluster
+        .spawn(|| Counter, ())
+        .with_clock(&clock)
+        .await
+        .unwrap();
+    sleepers(&clock, 1).await;
+    compio_runtime::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(counter.call(Idled).await, Ok(0));
+
+    sleepers(&clock, 1).await;
+    clock.advance(Duration::from_millis(1));
+    // The actor waits for its next idle timeout once `on_idle` is done.
+    sleepers(&clock, 1).await;
+    assert_eq!(counter.call(Idled).await, Ok(1));
+
+    counter.stop();
+    assert_eq!(handle.await.unwrap(), ActorExit::Stopped);
+    cluster.join().await.unwrap();
+}
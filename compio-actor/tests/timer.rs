@@ -0,0 +1,138 @@
+use std::{
+    convert::Infallible,
+    num::NonZeroUsize,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use compio_actor::{Actor, ActorExit, Cluster, Handler, Mailbox, mailbox::TimerHandle};
+use compio_dispatcher::Dispatcher;
+
+fn cluster() -> Cluster {
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(1).unwrap())
+        .build()
+        .unwrap();
+    Cluster::from_dispatcher(dispatcher)
+}
+
+#[derive(Debug)]
+struct Tick;
+
+/// How the ticker starts its timer.
+#[derive(Clone, Copy)]
+enum Start {
+    After,
+    Interval,
+    Dropped,
+}
+
+/// Counts ticks and stops after `until` of them, or when idle.
+struct Ticker {
+    ticks: Arc<AtomicUsize>,
+    idle: Arc<AtomicUsize>,
+    until: usize,
+}
+
+impl Actor for Ticker {
+    type Arguments = Start;
+    type Error = Infallible;
+    type State = Option<TimerHandle>;
+
+    async fn pre_start(
+        &self,
+        myself: &Mailbox<Self>,
+        start: Self::Arguments,
+    ) -> Result<Self::State, Self::Error> {
+        let delay = Duration::from_millis(20);
+        Ok(match start {
+            Start::After => Some(myself.send_after(Tick, delay)),
+            Start::Interval => Some(myself.send_interval(|| Tick, delay)),
+            Start::Dropped => {
+                myself.send_after(Tick, delay).cancel();
+                None
+            }
+        })
+    }
+
+    fn idle_timeout(&self) -> Option<Duration> {
+        Some(Duration::from_millis(100))
+    }
+
+    async fn on_idle(
+        &self,
+        myself: &Mailbox<Self>,
+        _state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        self.idle.fetch_add(1, Ordering::Relaxed);
+        myself.stop();
+        Ok(())
+    }
+}
+
+impl Handler<Tick> for Ticker {
+    async fn handle(
+        &self,
+        myself: &Mailbox<Self>,
+        Tick: Tick,
+        _state: &mut Self::State,
+    ) -> Result<(), Self::Error> {
+        if self.ticks.fetch_add(1, Ordering::Relaxed) + 1 == self.until {
+            myself.stop();
+        }
+        Ok(())
+    }
+}
+
+async fn run_ticker(start: Start, until: usize) -> (usize, usize, Duration) {
+    let cluster = cluster();
+    let ticks = Arc::new(AtomicUsize::new(0));
+    let idle = Arc::new(AtomicUsize::new(0));
+    let (actor_ticks, actor_idle) = (ticks.clone(), idle.clone());
+    let begin = Instant::now();
+    let (_mailbox, handle) = cluster
+        .spawn(
+            move || Ticker {
+                ticks: actor_ticks,
+                idle: actor_idle,
+                until,
+            },
+            start,
+        )
+        .await
+        .unwrap();
+    assert_eq!(handle.await.unwrap(), ActorExit::Stopped);
+    let elapsed = begin.elapsed();
+    cluster.join().await.unwrap();
+    (
+        ticks.load(Ordering::Relaxed),
+        idle.load(Ordering::Relaxed),
+        elapsed,
+    )
+}
+
+#[compio_macros::test]
+async fn send_after_delivers_once() {
+    let (ticks, idle, elapsed) = run_ticker(Start::After, 2).await;
+    assert_eq!(ticks, 1);
+    assert_eq!(idle, 1);
+    assert!(elapsed >= Duration::from_millis(120), "{elapsed:?}");
+}
+
+#[compio_macros::test]
+async fn send_interval_repeats_until_stopped() {
+    let (ticks, idle, elapsed) = run_ticker(Start::Interval, 3).await;
+    assert_eq!(ticks, 3);
+    assert_eq!(idle, 0);
+    assert!(elapsed >= Duration::from_millis(60), "{elapsed:?}");
+}
+
+#[compio_macros::test]
+async fn cancelled_timer_never_fires() {
+    let (ticks, idle, _) = run_ticker(Start::Dropped, 1).await;
+    assert_eq!(ticks, 0);
+    assert_eq!(idle, 1);
+}
@@ -11,28 +11,97 @@
 )]
 
 use std::{
+    cell::Cell,
     collections::HashSet,
-    future::Future,
+    fmt,
+    future::{Future, poll_fn},
+    hash::Hash,
     io,
     num::NonZeroUsize,
-    panic::resume_unwind,
+    panic::{AssertUnwindSafe, catch_unwind, resume_unwind},
+    pin::Pin,
+    sync::{Arc, Mutex, RwLock},
+    task::Poll,
     thread::{JoinHandle, available_parallelism},
+    time::Duration,
 };
 
 use compio_driver::{AsyncifyPool, DispatchError, Dispatchable, ProactorBuilder};
 use compio_runtime::{JoinHandle as CompioJoinHandle, Runtime, SpawnMeta};
-use flume::{Sender, unbounded};
+use flume::{Receiver, Sender, TrySendError, bounded, unbounded};
 use futures_channel::oneshot;
 
+mod stats;
+mod strategy;
+use stats::Counters;
+pub use stats::{DispatcherStats, WorkerStats};
+pub use strategy::Strategy;
+use strategy::{HashRing, Load, LoadGuard, Rng};
+
+/// An error returned by [`Dispatcher::try_dispatch`].
+///
+/// It contains the closure that failed to be sent.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum TryDispatchError<T> {
+    /// The queue the task was sent to is full.
+    Full(T),
+    /// The worker the task was sent to has panicked, or all of them have.
+    Disconnected(T),
+}
+
+impl<T> TryDispatchError<T> {
+    /// Consume the error, yielding the closure that failed to be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Full(f) | Self::Disconnected(f) => f,
+        }
+    }
+}
+
+impl<T> fmt::Debug for TryDispatchError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full(_) => "Full(..)".fmt(f),
+            Self::Disconnected(_) => "Disconnected(..)".fmt(f),
+        }
+    }
+}
+
+impl<T> fmt::Display for TryDispatchError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full(_) => "the queue is full".fmt(f),
+            Self::Disconnected(_) => "the worker has panicked".fmt(f),
+        }
+    }
+}
+
+impl<T> std::error::Error for TryDispatchError<T> {}
+
+impl<T> From<TryDispatchError<T>> for DispatchError<T> {
+    fn from(e: TryDispatchError<T>) -> Self {
+        DispatchError(e.into_inner())
+    }
+}
+
 /// A closure to spawn, and the [`SpawnMeta`] of the `dispatch` call it came
 /// from.
 struct Spawning {
     task: Box<dyn Spawnable + Send>,
     meta: SpawnMeta,
+    /// Set if the task is targeted at a worker, and already counted in its
+    /// load.
+    load: Option<LoadGuard>,
 }
 
 trait Spawnable {
-    fn spawn(self: Box<Self>, handle: &Runtime, meta: SpawnMeta) -> CompioJoinHandle<()>;
+    fn spawn(
+        self: Box<Self>,
+        handle: &Runtime,
+        meta: SpawnMeta,
+        load: LoadGuard,
+        counters: Arc<Counters>,
+    ) -> CompioJoinHandle<()>;
 }
 
 /// Concrete type for the closure we're sending to worker threads
@@ -54,11 +123,19 @@ where
     Fut: Future<Output = R>,
     R: Send + 'static,
 {
-    fn spawn(self: Box<Self>, handle: &Runtime, meta: SpawnMeta) -> CompioJoinHandle<()> {
+    fn spawn(
+        self: Box<Self>,
+        handle: &Runtime,
+        meta: SpawnMeta,
+        load: LoadGuard,
+        counters: Arc<Counters>,
+    ) -> CompioJoinHandle<()> {
         let Concrete { callback, func } = *self;
+        let task = counters.track(async move { func().await });
         handle.spawn_at(
             async move {
-                let res = func().await;
+                let res = task.await;
+                drop(load);
                 callback.send(res).ok();
             },
             meta,
@@ -78,12 +155,295 @@ where
     }
 }
 
+/// The queues a worker takes tasks from.
+struct Inbox {
+    /// In the order of preference: the queue for tasks pinned to the worker,
+    /// the one for tasks balanced onto it, and the one shared by all workers.
+    /// A queue is taken out once it's disconnected and empty.
+    queues: [Option<Receiver<Spawning>>; 3],
+    steal: Option<Steal>,
+    /// Set once the worker is retired by [`Dispatcher::resize`].
+    retired: bool,
+}
+
+/// Lets an idle worker take the tasks balanced onto busy ones, before they
+/// are started.
+struct Steal {
+    /// Signalled when a balanced task is queued on some worker.
+    signal: Receiver<()>,
+    notify: Sender<()>,
+    /// The queues of all workers for balanced tasks.
+    peers: Arc<RwLock<Vec<Receiver<Spawning>>>>,
+    load: Load,
+}
+
+/// What an [`Inbox`] is woken by.
+enum Event {
+    Task(Spawning),
+    Closed(usize),
+    Steal,
+}
+
+impl Inbox {
+    const PINNED: usize = 0;
+    const SHARED: usize = 2;
+
+    /// Receive the next task, stealing one from the other workers if there's
+    /// none for this one. Returns `None` once all the queues of this worker are
+    /// disconnected and empty.
+    async fn recv(&mut self) -> Option<Spawning> {
+        while self.queues.iter().any(Option::is_some) {
+            let event = {
+                let mut queues = self
+                    .queues
+                    .each_ref()
+                    .map(|rx| rx.as_ref().map(|rx| rx.recv_async()));
+                let mut signal = self.steal.as_ref().map(|steal| steal.signal.recv_async());
+                poll_fn(|cx| {
+                    for (index, fut) in queues.iter_mut().enumerate() {
+                        let Some(fut) = fut else { continue };
+                        match Pin::new(fut).poll(cx) {
+                            Poll::Ready(Ok(task)) => return Poll::Ready(Event::Task(task)),
+                            Poll::Ready(Err(_)) => return Poll::Ready(Event::Closed(index)),
+                            Poll::Pending => {}
+                        }
+                    }
+                    match signal.as_mut().map(|fut| Pin::new(fut).poll(cx)) {
+                        Some(Poll::Ready(_)) => Poll::Ready(Event::Steal),
+                        _ => Poll::Pending,
+                    }
+                })
+                .await
+            };
+            match event {
+                Event::Task(task) => return Some(task),
+                Event::Closed(index) => {
+                    self.queues[index] = None;
+                    // The worker is retired while the dispatcher is still
+                    // running: stop taking new tasks, and only drain the ones
+                    // already queued for it.
+                    if index == Self::PINNED
+                        && self.queues[Self::SHARED]
+                            .as_ref()
+                            .is_some_and(|rx| !rx.is_disconnected())
+                    {
+                        self.queues[Self::SHARED] = None;
+                        self.steal = None;
+                        self.retired = true;
+                    }
+                }
+                Event::Steal => {
+                    if let Some(task) = self.steal.as_ref().and_then(Steal::steal) {
+                        return Some(task);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Steal {
+    /// Take a task from the first worker that has one queued, and count it in
+    /// the load of this worker instead.
+    fn steal(&self) -> Option<Spawning> {
+        self.peers.read().unwrap().iter().find_map(|peer| {
+            let mut task = peer.try_recv().ok()?;
+            task.load = Some(self.load.enter());
+            // Pass the signal on, so that the rest of the backlog is taken too.
+            if !peer.is_empty() {
+                self.notify.try_send(()).ok();
+            }
+            Some(task)
+        })
+    }
+}
+
+/// Recover the closure from a task that failed to be sent.
+fn recover<Fn, R>(task: Spawning) -> Fn {
+    // SAFETY: We know the dispatchable we sent has type `Concrete<Fn, R>`
+    let recovered = unsafe { Box::from_raw(Box::into_raw(task.task) as *mut Concrete<Fn, R>) };
+    recovered.func
+}
+
+fn channel(capacity: Option<usize>) -> (Sender<Spawning>, Receiver<Spawning>) {
+    match capacity {
+        Some(cap) => bounded(cap),
+        None => unbounded(),
+    }
+}
+
+/// Take tasks from the inbox and spawn them, until the queues of the worker
+/// are closed. A retired worker also waits for its running tasks, while those
+/// of a joined dispatcher are left to [`Runtime::shutdown`].
+async fn serve(inbox: &mut Inbox, load: &Load, counters: &Arc<Counters>, concurrent: bool) {
+    while let Some(Spawning {
+        task: f,
+        meta,
+        load: guard,
+    }) = inbox.recv().await
+    {
+        let guard = guard.unwrap_or_else(|| load.enter());
+        let task = Runtime::with_current(|rt| f.spawn(rt, meta, guard, counters.clone()));
+        if concurrent {
+            task.detach()
+        } else {
+            task.await.ok();
+        }
+    }
+    if inbox.retired {
+        counters.idle().await;
+    }
+}
+
+thread_local! {
+    /// The index of the worker running on this thread.
+    static WORKER_INDEX: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// The handle of a worker thread.
+#[derive(Debug)]
+struct Worker {
+    sender: Sender<Spawning>,
+    balanced: Sender<Spawning>,
+    load: Load,
+    counters: Arc<Counters>,
+    thread: JoinHandle<()>,
+}
+
+/// Spawns the worker threads. It's kept by the dispatcher to spawn more of
+/// them on [`Dispatcher::resize`].
+struct Spawner {
+    concurrent: bool,
+    stack_size: Option<usize>,
+    thread_affinity: Option<Box<dyn FnMut(usize) -> HashSet<usize> + Send>>,
+    names: Option<Box<dyn FnMut(usize) -> String + Send>>,
+    proactor_builder: ProactorBuilder,
+    shutdown_timeout: Duration,
+    max_restarts: usize,
+    queue_capacity: Option<usize>,
+    meta: SpawnMeta,
+    shared: Receiver<Spawning>,
+    signal: Option<(Sender<()>, Receiver<()>)>,
+    balanced: Arc<RwLock<Vec<Receiver<Spawning>>>>,
+}
+
+impl Spawner {
+    fn spawn(&mut self, index: usize) -> io::Result<Worker> {
+        let (sender, pinned) = channel(self.queue_capacity);
+        let (balanced, stealable) = channel(self.queue_capacity);
+        // Only kept for the stealers, as the queue must disconnect when the
+        // worker dies otherwise.
+        if self.signal.is_some() {
+            self.balanced.write().unwrap().push(stealable.clone());
+        }
+        let load = Load::default();
+        let counters = Arc::new(Counters::default());
+        let mut inbox = Inbox {
+            queues: [Some(pinned), Some(stealable), Some(self.shared.clone())],
+            steal: self.signal.as_ref().map(|(notify, signal)| Steal {
+                signal: signal.clone(),
+                notify: notify.clone(),
+                peers: self.balanced.clone(),
+                load: load.clone(),
+            }),
+            retired: false,
+        };
+
+        let thread_builder = std::thread::Builder::new();
+        let thread_builder = if let Some(s) = self.stack_size {
+            thread_builder.stack_size(s)
+        } else {
+            thread_builder
+        };
+        let thread_builder = if let Some(f) = &mut self.names {
+            thread_builder.name(f(index))
+        } else {
+            thread_builder
+        };
+
+        let cpus = if let Some(f) = &mut self.thread_affinity {
+            f(index)
+        } else {
+            HashSet::new()
+        };
+        let proactor_builder = self.proactor_builder.clone();
+        let Self {
+            concurrent,
+            shutdown_timeout,
+            max_restarts,
+            meta,
+            ..
+        } = *self;
+        let thread = thread_builder.spawn({
+            let load = load.clone();
+            let counters = counters.clone();
+            move || {
+                WORKER_INDEX.set(Some(index));
+                let mut restarts = 0;
+                // A panic outside of the tasks brings down the runtime, but the
+                // queues are kept, to be served by a new one on this thread.
+                while let Err(e) = catch_unwind(AssertUnwindSafe(|| {
+                    let runtime = Runtime::builder()
+                        .with_proactor(proactor_builder.clone())
+                        .thread_affinity(cpus.clone())
+                        .build()
+                        .expect("cannot create compio runtime");
+                    runtime.block_on_at(serve(&mut inbox, &load, &counters, concurrent), meta);
+                    runtime.shutdown(shutdown_timeout);
+                })) {
+                    if restarts == max_restarts {
+                        counters.died();
+                        resume_unwind(e);
+                    }
+                    restarts += 1;
+                    counters.restarted();
+                }
+            }
+        })?;
+        Ok(Worker {
+            sender,
+            balanced,
+            load,
+            counters,
+            thread,
+        })
+    }
+}
+
+impl fmt::Debug for Spawner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Spawner")
+            .field("concurrent", &self.concurrent)
+            .field("max_restarts", &self.max_restarts)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Where a task is sent.
+#[derive(Debug, Clone, Copy)]
+enum Target {
+    /// The queue shared by all workers.
+    Shared,
+    /// A worker, which must run the task.
+    Pinned(usize),
+    /// A worker, which an idle one may steal the task from.
+    Balanced(usize),
+}
+
 /// The dispatcher. It manages the threads and dispatches the tasks.
 #[derive(Debug)]
 pub struct Dispatcher {
     sender: Sender<Spawning>,
-    threads: Vec<JoinHandle<()>>,
+    workers: Vec<Worker>,
+    /// The threads of the workers retired by [`Dispatcher::resize`].
+    retired: Vec<JoinHandle<()>>,
     pool: AsyncifyPool,
+    strategy: Strategy,
+    ring: HashRing,
+    rng: Rng,
+    signal: Option<Sender<()>>,
+    spawner: Mutex<Spawner>,
 }
 
 impl Dispatcher {
@@ -94,70 +454,52 @@ impl Dispatcher {
             nthreads,
             concurrent,
             stack_size,
-            mut thread_affinity,
-            mut names,
+            thread_affinity,
+            names,
             mut proactor_builder,
+            shutdown_timeout,
+            strategy,
+            work_stealing,
+            queue_capacity,
+            max_restarts,
         } = builder;
         proactor_builder.force_reuse_thread_pool();
+        if !shutdown_timeout.is_zero() {
+            proactor_builder.track_in_flight(true);
+        }
         let pool = proactor_builder.create_or_get_thread_pool();
-        let (sender, receiver) = unbounded::<Spawning>();
-        // Captured out here, since `#[track_caller]` does not reach into the
-        // closures the threads run, and every worker belongs to this call.
-        let meta = SpawnMeta::capture().named("dispatcher::worker");
-
-        let threads = (0..nthreads)
-            .map({
-                |index| {
-                    let proactor_builder = proactor_builder.clone();
-                    let receiver = receiver.clone();
-
-                    let thread_builder = std::thread::Builder::new();
-                    let thread_builder = if let Some(s) = stack_size {
-                        thread_builder.stack_size(s)
-                    } else {
-                        thread_builder
-                    };
-                    let thread_builder = if let Some(f) = &mut names {
-                        thread_builder.name(f(index))
-                    } else {
-                        thread_builder
-                    };
-
-                    let cpus = if let Some(f) = &mut thread_affinity {
-                        f(index)
-                    } else {
-                        HashSet::new()
-                    };
-                    thread_builder.spawn(move || {
-                        Runtime::builder()
-                            .with_proactor(proactor_builder)
-                            .thread_affinity(cpus)
-                            .build()
-                            .expect("cannot create compio runtime")
-                            .block_on_at(
-                                async move {
-                                    while let Ok(Spawning { task: f, meta }) =
-                                        receiver.recv_async().await
-                                    {
-                                        let task = Runtime::with_current(|rt| f.spawn(rt, meta));
-                                        if concurrent {
-                                            task.detach()
-                                        } else {
-                                            task.await.ok();
-                                        }
-                                    }
-                                },
-                                meta,
-                            );
-                    })
-                }
-            })
+        let (sender, receiver) = channel(queue_capacity);
+        let signal = work_stealing.then(|| flume::bounded::<()>(nthreads));
+        let mut spawner = Spawner {
+            concurrent,
+            stack_size,
+            thread_affinity,
+            names,
+            proactor_builder,
+            shutdown_timeout,
+            max_restarts,
+            queue_capacity,
+            // Captured out here, since `#[track_caller]` does not reach into the
+            // closures the threads run, and every worker belongs to this call.
+            meta: SpawnMeta::capture().named("dispatcher::worker"),
+            shared: receiver,
+            signal: signal.clone(),
+            balanced: Arc::default(),
+        };
+        let workers = (0..nthreads)
+            .map(|index| spawner.spawn(index))
             .collect::<io::Result<Vec<_>>>()?;
 
         Ok(Self {
             sender,
-            threads,
+            ring: HashRing::new(workers.len()),
+            workers,
+            retired: Vec::new(),
             pool,
+            strategy,
+            rng: Rng::new(),
+            signal: signal.map(|(notify, _)| notify),
+            spawner: Mutex::new(spawner),
         })
     }
 
@@ -172,16 +514,32 @@ impl Dispatcher {
         DispatcherBuilder::default()
     }
 
-    /// Dispatch a task to the threads
+    /// Dispatch a task to the threads, picking the worker with the
+    /// [`Strategy`] of the dispatcher.
     ///
     /// The provided `f` should be [`Send`] because it will be send to another
     /// thread before calling. The returned [`Future`] need not to be [`Send`]
     /// because it will be executed on only one thread.
     ///
+    /// Task-local values of the caller are not visible to the task. Wrap `f`
+    /// with [`LocalKey::inherit_async`] to carry them over.
+    ///
+    /// [`LocalKey::inherit_async`]: compio_runtime::LocalKey::inherit_async
+    ///
     /// # Error
     ///
-    /// If all threads have panicked, this method will return an error with the
-    /// sent closure.
+    /// If all threads have panicked, or the picked one has, or its queue is
+    /// full, this method will return an error with the sent closure. A worker
+    /// that panicked more than [`max_restarts`] times is no longer picked.
+    ///
+    /// This method never waits: with a [`queue_capacity`], a full queue fails
+    /// it right away with the same [`DispatchError`] as a panicked worker. Use
+    /// [`try_dispatch`](Self::try_dispatch) to tell these apart, or
+    /// [`dispatch_async`](Self::dispatch_async) to wait for the queue to have
+    /// room.
+    ///
+    /// [`max_restarts`]: DispatcherBuilder::max_restarts
+    /// [`queue_capacity`]: DispatcherBuilder::queue_capacity
     #[track_caller]
     pub fn dispatch<Fn, Fut, R>(&self, f: Fn) -> Result<oneshot::Receiver<R>, DispatchError<Fn>>
     where
@@ -189,23 +547,343 @@ impl Dispatcher {
         Fut: Future<Output = R> + 'static,
         R: Send + 'static,
     {
-        let (concrete, rx) = Concrete::new(f);
-
         let meta = SpawnMeta::capture().named("dispatch");
-        match self.sender.send(Spawning {
+        Ok(self.send(self.pick(None), f, meta)?)
+    }
+
+    /// Dispatch a task to the threads like [`dispatch`](Self::dispatch),
+    /// telling whether it failed because the queue is full.
+    ///
+    /// # Error
+    ///
+    /// If the queue is full, this method will return
+    /// [`TryDispatchError::Full`]. If all threads have panicked, or the picked
+    /// one has, it will return [`TryDispatchError::Disconnected`].
+    #[track_caller]
+    pub fn try_dispatch<Fn, Fut, R>(
+        &self,
+        f: Fn,
+    ) -> Result<oneshot::Receiver<R>, TryDispatchError<Fn>>
+    where
+        Fn: (FnOnce() -> Fut) + Send + 'static,
+        Fut: Future<Output = R> + 'static,
+        R: Send + 'static,
+    {
+        let meta = SpawnMeta::capture().named("try_dispatch");
+        self.send(self.pick(None), f, meta)
+    }
+
+    /// Dispatch a task to the threads like [`dispatch`](Self::dispatch), but
+    /// wait for the queue to have room instead of failing when it is full.
+    ///
+    /// # Error
+    ///
+    /// If all threads have panicked, or the picked one has, this method will
+    /// return an error with the sent closure.
+    #[track_caller]
+    pub fn dispatch_async<Fn, Fut, R>(
+        &self,
+        f: Fn,
+    ) -> impl Future<Output = Result<oneshot::Receiver<R>, DispatchError<Fn>>> + '_
+    where
+        Fn: (FnOnce() -> Fut) + Send + 'static,
+        Fut: Future<Output = R> + 'static,
+        R: Send + 'static,
+    {
+        // Captured out here, since `#[track_caller]` does not reach into async
+        // blocks.
+        let meta = SpawnMeta::capture().named("dispatch_async");
+        let target = self.pick(None);
+        let unserved = self.unserved(target);
+        let (sender, task, rx) = self.prepare(target, f, meta);
+        async move {
+            if unserved {
+                return Err(DispatchError(recover::<Fn, R>(task)));
+            }
+            match sender.send_async(task).await {
+                Ok(()) => {
+                    self.notify_stealers(target, sender);
+                    Ok(rx)
+                }
+                Err(err) => Err(DispatchError(recover::<Fn, R>(err.0))),
+            }
+        }
+    }
+
+    /// Dispatch a task to the threads with a key, so that tasks with the same
+    /// key run on the same worker under [`Strategy::ConsistentHash`].
+    ///
+    /// With the other strategies, the key is ignored, and this is the same as
+    /// [`dispatch`](Self::dispatch).
+    ///
+    /// # Error
+    ///
+    /// If the picked worker has panicked, or its queue is full, this method
+    /// will return an error with the sent closure.
+    #[track_caller]
+    pub fn dispatch_keyed<K, Fn, Fut, R>(
+        &self,
+        key: &K,
+        f: Fn,
+    ) -> Result<oneshot::Receiver<R>, DispatchError<Fn>>
+    where
+        K: Hash + ?Sized,
+        Fn: (FnOnce() -> Fut) + Send + 'static,
+        Fut: Future<Output = R> + 'static,
+        R: Send + 'static,
+    {
+        let meta = SpawnMeta::capture().named("dispatch_keyed");
+        Ok(self.send(self.pick(Some(strategy::hash(key))), f, meta)?)
+    }
+
+    /// Dispatch a task to the worker with the given index, so that the state
+    /// it touches stays on one thread.
+    ///
+    /// Tasks targeted at a worker are taken before those in the shared queue.
+    ///
+    /// # Error
+    ///
+    /// If the worker has panicked, or its queue is full, this method will
+    /// return an error with the sent closure.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than [`worker_count`](Self::worker_count).
+    #[track_caller]
+    pub fn dispatch_to<Fn, Fut, R>(
+        &self,
+        index: usize,
+        f: Fn,
+    ) -> Result<oneshot::Receiver<R>, DispatchError<Fn>>
+    where
+        Fn: (FnOnce() -> Fut) + Send + 'static,
+        Fut: Future<Output = R> + 'static,
+        R: Send + 'static,
+    {
+        assert!(index < self.workers.len(), "worker index out of range");
+        let meta = SpawnMeta::capture().named("dispatch_to");
+        Ok(self.send(Target::Pinned(index), f, meta)?)
+    }
+
+    /// Dispatch a clone of the task to every worker, such as to warm up caches
+    /// or reload configuration.
+    ///
+    /// The returned receivers are in the order of the workers. The one of a
+    /// worker that has panicked, or whose queue is full, is cancelled.
+    ///
+    /// # Error
+    ///
+    /// If the task could not be sent to any worker, this method will return an
+    /// error with the sent closure.
+    #[track_caller]
+    pub fn broadcast<Fn, Fut, R>(
+        &self,
+        f: Fn,
+    ) -> Result<Vec<oneshot::Receiver<R>>, DispatchError<Fn>>
+    where
+        Fn: (FnOnce() -> Fut) + Clone + Send + 'static,
+        Fut: Future<Output = R> + 'static,
+        R: Send + 'static,
+    {
+        let meta = SpawnMeta::capture().named("broadcast");
+        let mut sent = false;
+        let receivers = (0..self.workers.len())
+            .map(
+                |index| match self.send(Target::Pinned(index), f.clone(), meta) {
+                    Ok(rx) => {
+                        sent = true;
+                        rx
+                    }
+                    Err(_) => oneshot::channel().1,
+                },
+            )
+            .collect();
+        if sent {
+            Ok(receivers)
+        } else {
+            Err(DispatchError(f))
+        }
+    }
+
+    /// Pick where to send a task by the strategy, skipping the workers that
+    /// died.
+    fn pick(&self, key: Option<u64>) -> Target {
+        let load = |index: usize| self.workers[index].load.get();
+        let alive = |index: usize| !self.workers[index].counters.is_dead();
+        let least_loaded = || {
+            (0..self.workers.len())
+                .filter(|&index| alive(index))
+                .min_by_key(|&index| load(index))
+                .map_or(Target::Shared, Target::Balanced)
+        };
+        match (self.strategy, key) {
+            (Strategy::Shared, _) | (Strategy::ConsistentHash, None) => Target::Shared,
+            (Strategy::ConsistentHash, Some(hash)) => Target::Pinned(self.ring.get(hash)),
+            (Strategy::LeastLoaded, _) => least_loaded(),
+            (Strategy::PowerOfTwoChoices, _) => {
+                let n = self.workers.len() as u64;
+                let r = self.rng.next();
+                let (a, b) = (((r >> 32) % n) as usize, ((r & 0xffff_ffff) % n) as usize);
+                match (alive(a), alive(b)) {
+                    (true, true) => Target::Balanced(if load(b) < load(a) { b } else { a }),
+                    (true, false) => Target::Balanced(a),
+                    (false, true) => Target::Balanced(b),
+                    (false, false) => least_loaded(),
+                }
+            }
+        }
+    }
+
+    /// Whether no worker is left to take tasks from the shared queue, which
+    /// the dispatcher keeps open to spawn new workers.
+    fn unserved(&self, target: Target) -> bool {
+        matches!(target, Target::Shared)
+            && self.workers.iter().all(|worker| worker.counters.is_dead())
+    }
+
+    /// Wrap a task for the target queue, and return the queue.
+    fn prepare<Fn, Fut, R>(
+        &self,
+        target: Target,
+        f: Fn,
+        meta: SpawnMeta,
+    ) -> (&Sender<Spawning>, Spawning, oneshot::Receiver<R>)
+    where
+        Fn: (FnOnce() -> Fut) + Send + 'static,
+        Fut: Future<Output = R> + 'static,
+        R: Send + 'static,
+    {
+        let (concrete, rx) = Concrete::new(f);
+        let (sender, load) = match target {
+            Target::Shared => (&self.sender, None),
+            Target::Pinned(index) => {
+                let worker = &self.workers[index];
+                (&worker.sender, Some(worker.load.enter()))
+            }
+            Target::Balanced(index) => {
+                let worker = &self.workers[index];
+                (&worker.balanced, Some(worker.load.enter()))
+            }
+        };
+        let task = Spawning {
             task: Box::new(concrete),
             meta,
-        }) {
-            Ok(_) => Ok(rx),
-            Err(err) => {
-                // SAFETY: We know the dispatchable we sent has type `Concrete<Fn, R>`
-                let recovered =
-                    unsafe { Box::from_raw(Box::into_raw(err.0.task) as *mut Concrete<Fn, R>) };
-                Err(DispatchError(recovered.func))
+            load,
+        };
+        (sender, task, rx)
+    }
+
+    /// Send a task to the target queue without waiting. Returns the closure
+    /// back if the queue is full or disconnected.
+    fn send<Fn, Fut, R>(
+        &self,
+        target: Target,
+        f: Fn,
+        meta: SpawnMeta,
+    ) -> Result<oneshot::Receiver<R>, TryDispatchError<Fn>>
+    where
+        Fn: (FnOnce() -> Fut) + Send + 'static,
+        Fut: Future<Output = R> + 'static,
+        R: Send + 'static,
+    {
+        if self.unserved(target) {
+            return Err(TryDispatchError::Disconnected(f));
+        }
+        let (sender, task, rx) = self.prepare(target, f, meta);
+        match sender.try_send(task) {
+            Ok(()) => {
+                self.notify_stealers(target, sender);
+                Ok(rx)
+            }
+            Err(TrySendError::Full(task)) => Err(TryDispatchError::Full(recover::<Fn, R>(task))),
+            Err(TrySendError::Disconnected(task)) => {
+                Err(TryDispatchError::Disconnected(recover::<Fn, R>(task)))
             }
         }
     }
 
+    /// Called after a task is sent to the target queue.
+    fn notify_stealers(&self, target: Target, sender: &Sender<Spawning>) {
+        // Let an idle worker steal the task if the target one doesn't take it
+        // first. Signalling only behind a backlog would leave the last task
+        // of a drained one stuck on a busy worker.
+        if let (Target::Balanced(_), Some(signal)) = (target, &self.signal)
+            && !sender.is_empty()
+        {
+            signal.try_send(()).ok();
+        }
+    }
+
+    /// A snapshot of the statistics of the dispatcher and its workers, such as
+    /// to alert on saturation.
+    pub fn stats(&self) -> DispatcherStats {
+        DispatcherStats {
+            queued: self.sender.len(),
+            workers: self
+                .workers
+                .iter()
+                .map(|worker| {
+                    let queued = worker.sender.len() + worker.balanced.len();
+                    worker.counters.snapshot(queued)
+                })
+                .collect(),
+        }
+    }
+    /// The number of tasks in flight on the worker with the given index,
+    /// either queued for it or running on it.
+    ///
+    /// Tasks still in the shared queue are not counted for any worker.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than [`worker_count`](Self::worker_count).
+    pub fn worker_load(&self, index: usize) -> usize {
+        self.workers[index].load.get()
+    }
+
+    /// The number of worker threads.
+    pub fn worker_count(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// The index of the worker running the current thread, or `None` outside
+    /// the workers of a dispatcher.
+    pub fn current_worker() -> Option<usize> {
+        WORKER_INDEX.get()
+    }
+
+    /// Change the number of worker threads, such as to shrink the pool
+    /// off-peak.
+    ///
+    /// New workers are spawned like the existing ones. The workers retired are
+    /// the ones with the largest indices: they stop taking tasks from the
+    /// shared queue, finish the tasks already queued for or running on them,
+    /// and exit. Their panics are resumed by [`join`](Self::join).
+    ///
+    /// # Error
+    ///
+    /// If a new thread could not be spawned, this method will return the
+    /// error, keeping the workers spawned before it.
+    pub fn resize(&mut self, nthreads: NonZeroUsize) -> io::Result<()> {
+        let nthreads = nthreads.get();
+        let spawner = self.spawner.get_mut().unwrap();
+        let res = (self.workers.len()..nthreads).try_for_each(|index| {
+            self.workers.push(spawner.spawn(index)?);
+            Ok(())
+        });
+        if self.workers.len() > nthreads {
+            self.retired
+                .extend(self.workers.drain(nthreads..).map(|worker| worker.thread));
+        }
+        spawner
+            .balanced
+            .write()
+            .unwrap()
+            .retain(|rx| !(rx.is_disconnected() && rx.is_empty()));
+        self.ring = HashRing::new(self.workers.len());
+        res
+    }
+
     /// Dispatch a blocking task to the threads.
     ///
     /// Blocking pool of the dispatcher will be obtained from the proactor
@@ -236,14 +914,16 @@ impl Dispatcher {
     /// thread panicked, this method will resume the panic.
     pub async fn join(self) -> io::Result<()> {
         drop(self.sender);
+        let threads = self
+            .workers
+            .into_iter()
+            .map(|worker| worker.thread)
+            .chain(self.retired)
+            .collect::<Vec<_>>();
         let (tx, rx) = oneshot::channel::<Vec<_>>();
         if let Err(f) = self.pool.dispatch({
             move || {
-                let results = self
-                    .threads
-                    .into_iter()
-                    .map(|thread| thread.join())
-                    .collect();
+                let results = threads.into_iter().map(|thread| thread.join()).collect();
                 tx.send(results).ok();
             }
         }) {
@@ -264,9 +944,14 @@ pub struct DispatcherBuilder {
     nthreads: usize,
     concurrent: bool,
     stack_size: Option<usize>,
-    thread_affinity: Option<Box<dyn FnMut(usize) -> HashSet<usize>>>,
-    names: Option<Box<dyn FnMut(usize) -> String>>,
+    thread_affinity: Option<Box<dyn FnMut(usize) -> HashSet<usize> + Send>>,
+    names: Option<Box<dyn FnMut(usize) -> String + Send>>,
     proactor_builder: ProactorBuilder,
+    shutdown_timeout: Duration,
+    strategy: Strategy,
+    work_stealing: bool,
+    queue_capacity: Option<usize>,
+    max_restarts: usize,
 }
 
 impl DispatcherBuilder {
@@ -279,6 +964,11 @@ impl DispatcherBuilder {
             thread_affinity: None,
             names: None,
             proactor_builder: ProactorBuilder::new(),
+            shutdown_timeout: Duration::ZERO,
+            strategy: Strategy::Shared,
+            work_stealing: false,
+            queue_capacity: None,
+            max_restarts: 0,
         }
     }
 
@@ -306,13 +996,24 @@ impl DispatcherBuilder {
     }
 
     /// Set the thread affinity for the dispatcher.
-    pub fn thread_affinity(mut self, f: impl FnMut(usize) -> HashSet<usize> + 'static) -> Self {
+    ///
+    /// It's called again with the index of each worker spawned by
+    /// [`Dispatcher::resize`]. The dispatcher keeps it for that, so unlike in
+    /// earlier versions, it must be `Send`.
+    pub fn thread_affinity(
+        mut self,
+        f: impl FnMut(usize) -> HashSet<usize> + Send + 'static,
+    ) -> Self {
         self.thread_affinity = Some(Box::new(f));
         self
     }
 
     /// Provide a function to assign names to the worker threads.
-    pub fn thread_names(mut self, f: impl (FnMut(usize) -> String) + 'static) -> Self {
+    ///
+    /// It's called again with the index of each worker spawned by
+    /// [`Dispatcher::resize`]. The dispatcher keeps it for that, so unlike in
+    /// earlier versions, it must be `Send`.
+    pub fn thread_names(mut self, f: impl (FnMut(usize) -> String) + Send + 'static) -> Self {
         self.names = Some(Box::new(f) as _);
         self
     }
@@ -323,6 +1024,59 @@ impl DispatcherBuilder {
         self
     }
 
+    /// Set how [`Dispatcher::dispatch`] picks the worker to run a task. The
+    /// default is [`Strategy::Shared`].
+    pub fn strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// If idle workers steal the tasks queued for busy ones. Default to be
+    /// `false`.
+    ///
+    /// Only tasks placed on a worker by [`Strategy::LeastLoaded`] or
+    /// [`Strategy::PowerOfTwoChoices`], and not started yet, are stolen. Tasks
+    /// targeted at a worker, or keyed by [`Strategy::ConsistentHash`], always
+    /// run on it, and a started task never moves.
+    pub fn work_stealing(mut self, work_stealing: bool) -> Self {
+        self.work_stealing = work_stealing;
+        self
+    }
+
+    /// Set the capacity of each queue of tasks: the one shared by all workers,
+    /// and those of each worker. The queues are unbounded by default.
+    ///
+    /// When a queue is full, [`Dispatcher::dispatch`] fails, and
+    /// [`Dispatcher::dispatch_async`] waits for it to have room.
+    pub fn queue_capacity(mut self, capacity: NonZeroUsize) -> Self {
+        self.queue_capacity = Some(capacity.get());
+        self
+    }
+
+    /// Set how long a worker waits for its detached tasks and their operations
+    /// to finish when the dispatcher is joined, before dropping them. The
+    /// default is zero.
+    ///
+    /// A non-zero timeout makes the workers track their operations, so that
+    /// they can be cancelled and waited for. See [`Runtime::shutdown`] for
+    /// details.
+    pub fn shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    /// Set how many times a worker is restarted after it panics outside of
+    /// its tasks, such as while building its runtime. The default is zero.
+    ///
+    /// The worker is restarted on the same thread, with the same proactor
+    /// builder and thread affinity, and keeps its queues. The tasks running on
+    /// it when it panicked are dropped. Once out of restarts, the panic is
+    /// resumed by [`Dispatcher::join`].
+    pub fn max_restarts(mut self, max_restarts: usize) -> Self {
+        self.max_restarts = max_restarts;
+        self
+    }
+
     /// Build the [`Dispatcher`].
     #[track_caller]
     pub fn build(self) -> io::Result<Dispatcher> {
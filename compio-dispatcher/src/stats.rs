@@ -0,0 +1,119 @@
+//! Statistics of the workers.
+
+use std::{
+    future::{Future, poll_fn},
+    pin::pin,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use compio_runtime::sync::Notify;
+
+/// A snapshot of the statistics of a [`Dispatcher`].
+///
+/// [`Dispatcher`]: crate::Dispatcher
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct DispatcherStats {
+    /// The number of tasks in the queue shared by all workers.
+    pub queued: usize,
+    /// The statistics of each worker, in the order of the workers.
+    pub workers: Vec<WorkerStats>,
+}
+
+/// A snapshot of the statistics of a worker.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct WorkerStats {
+    /// The number of tasks queued for the worker, not taken by it yet.
+    pub queued: usize,
+    /// The number of tasks the worker has started and not completed.
+    pub running: usize,
+    /// The number of tasks the worker has completed.
+    pub completed: u64,
+    /// The total time spent polling the tasks of the worker.
+    pub busy: Duration,
+    /// The number of times the worker has been restarted after panicking.
+    pub restarts: u64,
+}
+
+/// The counters of a worker, updated by the worker and read by the
+/// dispatcher.
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    running: AtomicUsize,
+    completed: AtomicU64,
+    /// In nanoseconds.
+    busy: AtomicU64,
+    restarts: AtomicU64,
+    /// Set once the worker is out of restarts and its thread exits.
+    dead: AtomicBool,
+    /// Notified when no task is running.
+    idle: Notify,
+}
+
+impl Counters {
+    /// Wrap a task, counting it as running from now until it completes or is
+    /// dropped, and adding the time spent polling it to the busy time.
+    pub fn track<F: Future>(self: Arc<Self>, fut: F) -> impl Future<Output = F::Output> {
+        struct Running(Arc<Counters>);
+
+        impl Drop for Running {
+            fn drop(&mut self) {
+                if self.0.running.fetch_sub(1, Ordering::Relaxed) == 1 {
+                    self.0.idle.notify_one();
+                }
+            }
+        }
+
+        self.running.fetch_add(1, Ordering::Relaxed);
+        let running = Running(self);
+        async move {
+            let counters = &running.0;
+            let mut fut = pin!(fut);
+            let res = poll_fn(|cx| {
+                let start = Instant::now();
+                let res = fut.as_mut().poll(cx);
+                counters
+                    .busy
+                    .fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+                res
+            })
+            .await;
+            counters.completed.fetch_add(1, Ordering::Relaxed);
+            res
+        }
+    }
+
+    /// Wait for all the running tasks to complete.
+    pub async fn idle(&self) {
+        while self.running.load(Ordering::Relaxed) > 0 {
+            self.idle.notified().await;
+        }
+    }
+
+    pub fn restarted(&self) {
+        self.restarts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn died(&self) {
+        self.dead.store(true, Ordering::Release);
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.dead.load(Ordering::Acquire)
+    }
+
+    pub fn snapshot(&self, queued: usize) -> WorkerStats {
+        WorkerStats {
+            queued,
+            running: self.running.load(Ordering::Relaxed),
+            completed: self.completed.load(Ordering::Relaxed),
+            busy: Duration::from_nanos(self.busy.load(Ordering::Relaxed)),
+            restarts: self.restarts.load(Ordering::Relaxed),
+        }
+    }
+}
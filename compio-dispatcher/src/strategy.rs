@@ -0,0 +1,116 @@
+//! Strategies to pick the worker a task is dispatched to.
+
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+};
+
+/// How [`Dispatcher::dispatch`] picks the worker to run a task.
+///
+/// [`Dispatcher::dispatch`]: crate::Dispatcher::dispatch
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Strategy {
+    /// Push tasks onto a queue shared by all workers, so that a task lands on
+    /// whichever worker takes it first.
+    #[default]
+    Shared,
+    /// Send each task to the worker with the fewest tasks in flight.
+    LeastLoaded,
+    /// Pick two workers at random, and send each task to the less loaded one.
+    ///
+    /// This is nearly as balanced as [`LeastLoaded`](Self::LeastLoaded), but
+    /// only looks at two workers per task.
+    PowerOfTwoChoices,
+    /// Send tasks with the same key, given to [`Dispatcher::dispatch_keyed`],
+    /// to the same worker. Tasks dispatched without a key go to the shared
+    /// queue.
+    ///
+    /// [`Dispatcher::dispatch_keyed`]: crate::Dispatcher::dispatch_keyed
+    ConsistentHash,
+}
+
+/// The number of tasks a worker has in flight, either queued or running.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Load(Arc<AtomicUsize>);
+
+impl Load {
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Count a task in, until the returned guard is dropped.
+    pub fn enter(&self) -> LoadGuard {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        LoadGuard(self.0.clone())
+    }
+}
+
+/// Counts a task in the [`Load`] of a worker while it's alive.
+#[derive(Debug)]
+pub(crate) struct LoadGuard(Arc<AtomicUsize>);
+
+impl Drop for LoadGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// A consistent hash ring over the workers.
+///
+/// Each worker owns a number of points on the ring, and a key belongs to the
+/// worker owning the first point after its hash.
+#[derive(Debug)]
+pub(crate) struct HashRing {
+    points: Vec<(u64, usize)>,
+}
+
+impl HashRing {
+    /// The number of points each worker owns.
+    const REPLICAS: usize = 64;
+
+    pub fn new(workers: usize) -> Self {
+        let mut points = (0..workers)
+            .flat_map(|worker| {
+                (0..Self::REPLICAS).map(move |replica| (hash(&(worker, replica)), worker))
+            })
+            .collect::<Vec<_>>();
+        points.sort_unstable();
+        Self { points }
+    }
+
+    pub fn get(&self, hash: u64) -> usize {
+        let index = self.points.partition_point(|(point, _)| *point < hash);
+        self.points[index % self.points.len()].1
+    }
+}
+
+pub(crate) fn hash<K: Hash + ?Sized>(key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A lock-free source of random numbers, good enough for picking workers.
+#[derive(Debug)]
+pub(crate) struct Rng(AtomicU64);
+
+impl Rng {
+    pub fn new() -> Self {
+        Self(AtomicU64::new(hash(&std::time::Instant::now())))
+    }
+
+    /// SplitMix64.
+    pub fn next(&self) -> u64 {
+        let mut z = self
+            .0
+            .fetch_add(0x9e3779b97f4a7c15, Ordering::Relaxed)
+            .wrapping_add(0x9e3779b97f4a7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
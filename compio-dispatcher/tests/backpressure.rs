@@ -0,0 +1,97 @@
+use std::{num::NonZeroUsize, pin::pin, sync::mpsc, thread, time::Duration};
+
+use compio_dispatcher::{Dispatcher, TryDispatchError};
+
+fn dispatcher(capacity: usize) -> Dispatcher {
+    Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(1).unwrap())
+        .concurrent(false)
+        .queue_capacity(NonZeroUsize::new(capacity).unwrap())
+        .build()
+        .unwrap()
+}
+
+/// Block the only worker, until the returned sender is used or dropped.
+fn block(dispatcher: &Dispatcher) -> mpsc::Sender<()> {
+    let (started_tx, started_rx) = mpsc::channel();
+    let (unblock_tx, unblock_rx) = mpsc::channel::<()>();
+    drop(
+        dispatcher
+            .dispatch(move || async move {
+                started_tx.send(()).unwrap();
+                unblock_rx.recv().ok();
+            })
+            .unwrap(),
+    );
+    started_rx.recv().unwrap();
+    unblock_tx
+}
+
+#[compio_macros::test]
+async fn try_dispatch_full() {
+    let dispatcher = dispatcher(2);
+    let unblock = block(&dispatcher);
+
+    let first = dispatcher.try_dispatch(|| async { 1 }).unwrap();
+    let second = dispatcher.try_dispatch(|| async { 2 }).unwrap();
+    let err = dispatcher.try_dispatch(|| async { 3 }).unwrap_err();
+    assert!(matches!(err, TryDispatchError::Full(_)));
+    assert!(dispatcher.dispatch(|| async { 3 }).is_err());
+
+    drop(unblock);
+    assert_eq!(first.await.unwrap(), 1);
+    assert_eq!(second.await.unwrap(), 2);
+    let third = err.into_inner();
+    assert_eq!(dispatcher.dispatch(third).unwrap().await.unwrap(), 3);
+
+    dispatcher.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn dispatch_async_waits() {
+    let dispatcher = dispatcher(1);
+    let unblock = block(&dispatcher);
+
+    let first = dispatcher.dispatch_async(|| async { 1 }).await.unwrap();
+    let second = {
+        let mut second = pin!(dispatcher.dispatch_async(|| async { 2 }));
+        assert!(futures_util::poll!(second.as_mut()).is_pending());
+        drop(unblock);
+        second.await.unwrap()
+    };
+    assert_eq!(first.await.unwrap(), 1);
+    assert_eq!(second.await.unwrap(), 2);
+
+    dispatcher.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn stats() {
+    let dispatcher = dispatcher(4);
+    let unblock = block(&dispatcher);
+
+    let tasks = (0..3)
+        .map(|_| {
+            dispatcher
+                .dispatch(|| async { thread::sleep(Duration::from_millis(10)) })
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let stats = dispatcher.stats();
+    assert_eq!(stats.queued, 3);
+    assert_eq!(stats.workers[0].running, 1);
+    assert_eq!(stats.workers[0].completed, 0);
+
+    drop(unblock);
+    for task in tasks {
+        task.await.unwrap();
+    }
+    let stats = dispatcher.stats();
+    assert_eq!(stats.queued, 0);
+    let worker = stats.workers[0];
+    assert_eq!(worker.completed, 4);
+    assert_eq!(worker.running, 0);
+    assert!(worker.busy >= Duration::from_millis(30), "{worker:?}");
+
+    dispatcher.join().await.unwrap();
+}
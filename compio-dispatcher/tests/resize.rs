@@ -0,0 +1,146 @@
+use std::{
+    num::NonZeroUsize,
+    pin::pin,
+    sync::Arc,
+    task::{Context, Wake, Waker},
+    thread,
+    time::{Duration, Instant},
+};
+
+use compio_dispatcher::{Dispatcher, Strategy};
+use futures_channel::oneshot;
+
+fn dispatcher(n: usize) -> Dispatcher {
+    Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(n).unwrap())
+        .thread_names(|index| format!("worker-{index}"))
+        .build()
+        .unwrap()
+}
+
+fn thread_name() -> String {
+    thread::current().name().unwrap().to_string()
+}
+
+#[compio_macros::test]
+async fn grow() {
+    let mut dispatcher = dispatcher(1);
+    dispatcher.resize(NonZeroUsize::new(3).unwrap()).unwrap();
+    assert_eq!(dispatcher.worker_count(), 3);
+
+    for index in 0..3 {
+        let name = dispatcher
+            .dispatch_to(index, || async { thread_name() })
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(name, format!("worker-{index}"));
+    }
+
+    dispatcher.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn shrink_drains_retired() {
+    let mut dispatcher = dispatcher(3);
+
+    let (release, rx) = oneshot::channel::<()>();
+    let pending = dispatcher
+        .dispatch_to(2, move || async move {
+            rx.await.ok();
+            thread_name()
+        })
+        .unwrap();
+    dispatcher.resize(NonZeroUsize::new(1).unwrap()).unwrap();
+    assert_eq!(dispatcher.worker_count(), 1);
+
+    for _ in 0..16 {
+        let name = dispatcher
+            .dispatch(|| async { thread_name() })
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(name, "worker-0");
+    }
+
+    release.send(()).unwrap();
+    assert_eq!(pending.await.unwrap(), "worker-2");
+
+    dispatcher.join().await.unwrap();
+}
+
+/// Bring down the runtime of a worker outside of its tasks, until it runs out
+/// of restarts.
+fn kill(dispatcher: &Dispatcher, index: usize) {
+    struct PanicWaker;
+
+    impl Wake for PanicWaker {
+        fn wake(self: Arc<Self>) {
+            panic!("woken");
+        }
+    }
+
+    // The runtime wakes an expired timer outside of the tasks, so that a
+    // panicking waker brings it down.
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while dispatcher
+        .dispatch_to(index, || async {
+            let mut sleep = pin!(compio_runtime::time::sleep(Duration::from_millis(1)));
+            let waker = Waker::from(Arc::new(PanicWaker));
+            assert!(
+                sleep
+                    .as_mut()
+                    .poll(&mut Context::from_waker(&waker))
+                    .is_pending()
+            );
+            std::future::pending::<()>().await;
+        })
+        .is_ok()
+    {
+        assert!(Instant::now() < deadline, "the worker should give up");
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[compio_macros::test]
+async fn restart_on_panic() {
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(1).unwrap())
+        .max_restarts(2)
+        .build()
+        .unwrap();
+    kill(&dispatcher, 0);
+    assert_eq!(dispatcher.stats().workers[0].restarts, 2);
+}
+
+#[compio_macros::test]
+async fn dispatch_skips_dead_workers() {
+    for strategy in [Strategy::LeastLoaded, Strategy::PowerOfTwoChoices] {
+        let dispatcher = Dispatcher::builder()
+            .worker_threads(NonZeroUsize::new(2).unwrap())
+            .thread_names(|index| format!("worker-{index}"))
+            .strategy(strategy)
+            .build()
+            .unwrap();
+        kill(&dispatcher, 0);
+        for _ in 0..16 {
+            let task = dispatcher.dispatch(|| async { thread_name() }).unwrap();
+            let name = compio_runtime::time::timeout(Duration::from_secs(5), task)
+                .await
+                .expect("the task should run on a live worker")
+                .unwrap();
+            assert_eq!(name, "worker-1");
+        }
+    }
+
+    for strategy in [Strategy::Shared, Strategy::LeastLoaded] {
+        let dispatcher = Dispatcher::builder()
+            .worker_threads(NonZeroUsize::new(1).unwrap())
+            .strategy(strategy)
+            .build()
+            .unwrap();
+        kill(&dispatcher, 0);
+        assert!(dispatcher.dispatch(|| async {}).is_err());
+        assert!(dispatcher.dispatch_async(|| async {}).await.is_err());
+    }
+}
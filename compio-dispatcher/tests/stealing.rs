@@ -0,0 +1,63 @@
+use std::{num::NonZeroUsize, sync::mpsc, thread};
+
+use compio_dispatcher::{Dispatcher, Strategy};
+use futures_channel::oneshot;
+
+fn thread_name() -> String {
+    thread::current().name().unwrap().to_string()
+}
+
+#[compio_macros::test]
+async fn steal_from_busy_worker() {
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(2).unwrap())
+        .thread_names(|index| format!("worker-{index}"))
+        .strategy(Strategy::LeastLoaded)
+        .work_stealing(true)
+        .build()
+        .unwrap();
+
+    // Block the thread of worker 0.
+    let (started_tx, started_rx) = mpsc::channel();
+    let (unblock_tx, unblock_rx) = mpsc::channel::<()>();
+    let blocker = dispatcher
+        .dispatch_to(0, move || async move {
+            started_tx.send(()).unwrap();
+            unblock_rx.recv().ok();
+        })
+        .unwrap();
+    started_rx.recv().unwrap();
+
+    // Make worker 1 look busier, while leaving its thread idle.
+    let (releases, waiting): (Vec<_>, Vec<_>) = (0..8)
+        .map(|_| {
+            let (tx, rx) = oneshot::channel::<()>();
+            let task = dispatcher
+                .dispatch_to(1, move || async move { rx.await.ok() })
+                .unwrap();
+            (tx, task)
+        })
+        .unzip();
+
+    // These are balanced onto worker 0, and stolen by worker 1.
+    let pinned = dispatcher
+        .dispatch_to(0, || async { thread_name() })
+        .unwrap();
+    let tasks = (0..4)
+        .map(|_| dispatcher.dispatch(|| async { thread_name() }).unwrap())
+        .collect::<Vec<_>>();
+    for task in tasks {
+        assert_eq!(task.await.unwrap(), "worker-1");
+    }
+    assert_eq!(dispatcher.worker_load(0), 2);
+
+    unblock_tx.send(()).unwrap();
+    blocker.await.unwrap();
+    assert_eq!(pinned.await.unwrap(), "worker-0");
+    drop(releases);
+    for task in waiting {
+        task.await.unwrap();
+    }
+
+    dispatcher.join().await.unwrap();
+}
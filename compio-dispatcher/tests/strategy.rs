@@ -0,0 +1,101 @@
+use std::{collections::HashSet, num::NonZeroUsize, thread};
+
+use compio_dispatcher::{Dispatcher, Strategy};
+use futures_channel::oneshot;
+
+fn dispatcher(n: usize, strategy: Strategy) -> Dispatcher {
+    Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(n).unwrap())
+        .thread_names(|index| format!("worker-{index}"))
+        .strategy(strategy)
+        .build()
+        .unwrap()
+}
+
+fn thread_name() -> String {
+    thread::current().name().unwrap().to_string()
+}
+
+#[compio_macros::test]
+async fn consistent_hash() {
+    let dispatcher = dispatcher(4, Strategy::ConsistentHash);
+
+    let mut owners = HashSet::new();
+    for key in 0..64 {
+        let first = dispatcher
+            .dispatch_keyed(&key, || async { thread_name() })
+            .unwrap()
+            .await
+            .unwrap();
+        for _ in 0..3 {
+            let again = dispatcher
+                .dispatch_keyed(&key, || async { thread_name() })
+                .unwrap()
+                .await
+                .unwrap();
+            assert_eq!(first, again);
+        }
+        owners.insert(first);
+    }
+    assert!(owners.len() > 1);
+
+    dispatcher.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn least_loaded() {
+    let dispatcher = dispatcher(4, Strategy::LeastLoaded);
+
+    let (releases, tasks): (Vec<_>, Vec<_>) = (0..8)
+        .map(|_| {
+            let (tx, rx) = oneshot::channel::<()>();
+            let task = dispatcher
+                .dispatch(move || async move { rx.await.ok() })
+                .unwrap();
+            (tx, task)
+        })
+        .unzip();
+    for index in 0..4 {
+        assert_eq!(dispatcher.worker_load(index), 2);
+    }
+
+    drop(releases);
+    for task in tasks {
+        task.await.unwrap();
+    }
+    for index in 0..4 {
+        assert_eq!(dispatcher.worker_load(index), 0);
+    }
+
+    dispatcher.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn power_of_two_choices() {
+    let dispatcher = dispatcher(4, Strategy::PowerOfTwoChoices);
+
+    let (releases, tasks): (Vec<_>, Vec<_>) = (0..64)
+        .map(|i| {
+            let (tx, rx) = oneshot::channel::<()>();
+            let task = dispatcher
+                .dispatch(move || async move {
+                    rx.await.ok();
+                    i
+                })
+                .unwrap();
+            (tx, task)
+        })
+        .unzip();
+    let loads = (0..4)
+        .map(|index| dispatcher.worker_load(index))
+        .collect::<Vec<_>>();
+    assert_eq!(loads.iter().sum::<usize>(), 64);
+    assert!(loads.iter().all(|&load| load > 0), "{loads:?}");
+
+    drop(releases);
+    for (i, task) in tasks.into_iter().enumerate() {
+        assert_eq!(task.await.unwrap(), i);
+    }
+
+    dispatcher.join().await.unwrap();
+}
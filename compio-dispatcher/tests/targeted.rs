@@ -0,0 +1,69 @@
+use std::{collections::HashSet, num::NonZeroUsize, thread};
+
+use compio_dispatcher::Dispatcher;
+
+fn dispatcher(n: usize) -> Dispatcher {
+    Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(n).unwrap())
+        .thread_names(|index| format!("worker-{index}"))
+        .build()
+        .unwrap()
+}
+
+fn thread_name() -> String {
+    thread::current().name().unwrap().to_string()
+}
+
+#[compio_macros::test]
+async fn dispatch_to() {
+    let dispatcher = dispatcher(3);
+    assert_eq!(dispatcher.worker_count(), 3);
+
+    for index in [2, 0, 1, 2] {
+        let name = dispatcher
+            .dispatch_to(index, || async { thread_name() })
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(name, format!("worker-{index}"));
+        let current = dispatcher
+            .dispatch_to(index, || async { Dispatcher::current_worker() })
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(current, Some(index));
+    }
+    assert_eq!(Dispatcher::current_worker(), None);
+
+    dispatcher.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn broadcast() {
+    let dispatcher = dispatcher(4);
+
+    let receivers = dispatcher.broadcast(|| async { thread_name() }).unwrap();
+    let mut names = HashSet::new();
+    for (index, rx) in receivers.into_iter().enumerate() {
+        let name = rx.await.unwrap();
+        assert_eq!(name, format!("worker-{index}"));
+        names.insert(name);
+    }
+    assert_eq!(names.len(), 4);
+
+    dispatcher.join().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn shared_queue_still_works() {
+    let dispatcher = dispatcher(2);
+
+    let receivers = (0..16)
+        .map(|i| dispatcher.dispatch(move || async move { i * 2 }).unwrap())
+        .collect::<Vec<_>>();
+    for (i, rx) in receivers.into_iter().enumerate() {
+        assert_eq!(rx.await.unwrap(), i * 2);
+    }
+
+    dispatcher.join().await.unwrap();
+}
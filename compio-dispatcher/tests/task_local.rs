@@ -0,0 +1,28 @@
+use std::num::NonZeroUsize;
+
+use compio_dispatcher::Dispatcher;
+
+compio_runtime::task_local! {
+    static TENANT: &'static str;
+}
+
+#[compio_macros::test]
+async fn dispatch_inherit() {
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(2).unwrap())
+        .build()
+        .unwrap();
+
+    let res = TENANT
+        .scope("alice", async {
+            dispatcher
+                .dispatch(TENANT.inherit_async(|| async { TENANT.get() }))
+                .unwrap()
+                .await
+                .unwrap()
+        })
+        .await;
+    assert_eq!(res, "alice");
+
+    dispatcher.join().await.unwrap();
+}
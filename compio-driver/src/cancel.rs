@@ -22,3 +22,42 @@ impl Cancel {
         self.0.upgrade()
     }
 }
+
+/// Weak references to the operations pushed into a [`Proactor`], so that they
+/// can be counted and cancelled all at once.
+///
+/// Finished operations are pruned lazily, whenever the list has doubled in
+/// size since the last pruning.
+///
+/// [`Proactor`]: crate::Proactor
+#[derive(Debug)]
+pub(crate) struct InFlight {
+    tokens: Vec<Cancel>,
+    prune_at: usize,
+}
+
+impl InFlight {
+    const MIN_PRUNE_AT: usize = 64;
+
+    pub fn new() -> Self {
+        Self {
+            tokens: Vec::new(),
+            prune_at: Self::MIN_PRUNE_AT,
+        }
+    }
+
+    pub fn track<T: OpCode>(&mut self, key: &Key<T>) {
+        if self.tokens.len() >= self.prune_at {
+            self.prune();
+            self.prune_at = (self.tokens.len() * 2).max(Self::MIN_PRUNE_AT);
+        }
+        self.tokens.push(Cancel::new(key));
+    }
+
+    /// Drop the tokens of the finished operations, and return the live ones.
+    pub fn prune(&mut self) -> &[Cancel] {
+        self.tokens
+            .retain(|token| token.upgrade().is_some_and(|key| !key.has_result()));
+        &self.tokens
+    }
+}
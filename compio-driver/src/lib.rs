@@ -51,6 +51,7 @@ pub use buffer_pool::{BoxAllocator, BufferAllocator, BufferPool, BufferRef};
 
 use crate::{
     buffer_pool::{BufferAlloc, BufferPoolRoot},
+    cancel::InFlight,
     key::ErasedKey,
     panic::resume_unwind_io,
     sys::op::OpCodeFlag,
@@ -101,6 +102,8 @@ impl<K, R> PushEntry<K, R> {
 pub struct Proactor {
     driver: Driver,
     buffer_pool: BufferPoolState,
+    /// Only kept when [`ProactorBuilder::track_in_flight`] is enabled.
+    in_flight: Option<InFlight>,
 }
 
 enum BufferPoolState {
@@ -170,6 +173,7 @@ impl Proactor {
                 buffer_len: builder.buffer_pool_buffer_len,
                 flags: builder.buffer_pool_flag,
             },
+            in_flight: builder.track_in_flight.then(InFlight::new),
         })
     }
 
@@ -234,6 +238,39 @@ impl Proactor {
         true
     }
 
+    /// Cancel all operations that have been pushed and not completed yet,
+    /// including those whose [`Key`] has been dropped.
+    ///
+    /// Returns the number of cancellations issued. As with
+    /// [`Proactor::cancel_token`], the cancellation is not reliable, and the
+    /// operations stay in flight until the driver reports them completed. Their
+    /// buffers are kept alive till then.
+    ///
+    /// Nothing is cancelled unless the proactor was built with
+    /// [`ProactorBuilder::track_in_flight`].
+    pub fn cancel_all(&mut self) -> usize {
+        instrument!(compio_log::Level::DEBUG, "cancel_all");
+        let Some(in_flight) = &mut self.in_flight else {
+            return 0;
+        };
+        let tokens = in_flight.prune().to_vec();
+        tokens
+            .into_iter()
+            .filter(|token| self.cancel_token(token.clone()))
+            .count()
+    }
+
+    /// The number of operations that have been pushed and not completed yet,
+    /// including those whose [`Key`] has been dropped.
+    ///
+    /// This is always zero unless the proactor was built with
+    /// [`ProactorBuilder::track_in_flight`].
+    pub fn in_flight(&mut self) -> usize {
+        self.in_flight
+            .as_mut()
+            .map_or(0, |in_flight| in_flight.prune().len())
+    }
+
     /// Create a [`Cancel`] that can be used to cancel the operation even
     /// without the key.
     ///
@@ -264,7 +301,12 @@ impl Proactor {
     ) -> PushEntry<Key<T>, BufResult<usize, T>> {
         let key = Key::new(op, extra, self.driver_type());
         match self.driver.push(key.clone().erase()) {
-            Poll::Pending => PushEntry::Pending(key),
+            Poll::Pending => {
+                if let Some(in_flight) = &mut self.in_flight {
+                    in_flight.track(&key);
+                }
+                PushEntry::Pending(key)
+            }
             Poll::Ready(res) => {
                 key.set_result(res);
                 PushEntry::Ready(key.take_result())
@@ -574,6 +616,7 @@ pub struct ProactorBuilder {
     buffer_pool_flag: u16,
     buffer_pool_buffer_len: usize,
     buffer_pool_allocator: BufferAlloc,
+    track_in_flight: bool,
 }
 
 // SAFETY: `RawFd` is thread safe.
@@ -606,6 +649,7 @@ impl ProactorBuilder {
             buffer_pool_flag: 0,
             buffer_pool_buffer_len: 8192,
             buffer_pool_allocator: BufferAlloc::new::<BoxAllocator>(),
+            track_in_flight: false,
         }
     }
 
@@ -785,6 +829,17 @@ impl ProactorBuilder {
         self
     }
 
+    /// Keep track of the operations in flight, so that
+    /// [`Proactor::cancel_all`] can cancel them and [`Proactor::in_flight`]
+    /// can count them, such as to shut a runtime down.
+    ///
+    /// This keeps a weak reference to each pending operation, so it's off by
+    /// default.
+    pub fn track_in_flight(&mut self, enable: bool) -> &mut Self {
+        self.track_in_flight = enable;
+        self
+    }
+
     /// Force a driver type to use.
     ///
     /// It is ignored if the fusion driver is disabled.
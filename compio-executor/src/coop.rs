@@ -0,0 +1,102 @@
+//! Cooperative scheduling.
+//!
+//! A task looping on a resource that is always ready, such as a socket with
+//! completions already queued, never returns [`Poll::Pending`] by itself, and
+//! keeps the thread from running anything else. To prevent that, each poll of
+//! a task is given a budget. Leaf futures call [`poll_proceed`] before doing
+//! any work, which spends one unit of it; once the budget is spent, they
+//! return [`Poll::Pending`] and wake the task, so that it is rescheduled after
+//! the others.
+//!
+//! Outside of a budgeted poll, such as in a future polled by a foreign
+//! executor, [`poll_proceed`] always succeeds.
+
+use std::{
+    cell::Cell,
+    future::poll_fn,
+    task::{Context, Poll},
+};
+
+std::thread_local! {
+    static BUDGET: Cell<Option<u32>> = const { Cell::new(None) };
+}
+
+/// Run `f` with the given budget, restoring the previous one afterwards.
+///
+/// A budget of `None` is unconstrained.
+pub fn with_budget<R>(budget: Option<u32>, f: impl FnOnce() -> R) -> R {
+    struct ResetGuard(Option<u32>);
+
+    impl Drop for ResetGuard {
+        fn drop(&mut self) {
+            BUDGET.set(self.0);
+        }
+    }
+
+    let _guard = ResetGuard(BUDGET.replace(budget));
+    f()
+}
+
+/// Whether the current budget is not spent yet.
+pub fn has_budget_remaining() -> bool {
+    BUDGET.get() != Some(0)
+}
+
+/// Spend one unit of the current budget.
+///
+/// If the budget is spent, the task is woken and [`Poll::Pending`] is
+/// returned. Otherwise, the returned [`RestoreOnPending`] should be kept until
+/// the caller knows whether it made progress: if it is dropped without
+/// [`RestoreOnPending::made_progress`] being called, the unit is refunded.
+pub fn poll_proceed(cx: &mut Context<'_>) -> Poll<RestoreOnPending> {
+    match BUDGET.get() {
+        None => Poll::Ready(RestoreOnPending(None)),
+        Some(0) => {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+        Some(n) => {
+            BUDGET.set(Some(n - 1));
+            Poll::Ready(RestoreOnPending(Some(n)))
+        }
+    }
+}
+
+/// Refunds a unit of budget spent by [`poll_proceed`] when dropped, unless
+/// [`made_progress`] is called.
+///
+/// [`made_progress`]: RestoreOnPending::made_progress
+#[derive(Debug)]
+#[must_use = "dropping the guard refunds the budget"]
+pub struct RestoreOnPending(Option<u32>);
+
+impl RestoreOnPending {
+    /// Keep the unit spent.
+    pub fn made_progress(&mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for RestoreOnPending {
+    fn drop(&mut self) {
+        if let Some(budget) = self.0 {
+            BUDGET.set(Some(budget));
+        }
+    }
+}
+
+/// Yield execution back to the executor, so that other tasks get a chance to
+/// run before the current one is polled again.
+pub async fn yield_now() {
+    let mut yielded = false;
+    poll_fn(|cx| {
+        if yielded {
+            Poll::Ready(())
+        } else {
+            yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+    .await
+}
@@ -11,12 +11,17 @@
     html_favicon_url = "https://github.com/compio-rs/compio-logo/raw/refs/heads/master/generated/colored-bold.svg"
 )]
 
-use std::{any::Any, fmt::Debug, ptr::NonNull, task::Waker};
+use std::{any::Any, cell::Cell, fmt::Debug, ptr::NonNull, task::Waker, time::Instant};
 
-use crate::queue::{TaskId, TaskQueue};
+use crate::{
+    priority::Accounting,
+    queue::{TaskId, TaskQueue},
+};
 
 pub mod console;
+pub mod coop;
 mod join_handle;
+mod priority;
 mod queue;
 mod task;
 mod util;
@@ -27,6 +32,8 @@ use compio_send_wrapper::SendWrapper;
 pub use console::SpawnMeta;
 use crossbeam_queue::ArrayQueue;
 pub use join_handle::{JoinError, JoinHandle, ResumeUnwind};
+pub use priority::{Priority, PriorityStats};
+pub use task::task_local::{AccessError, LocalKey, TaskLocalFuture};
 use util::panic_guard;
 
 cfg_select! {
@@ -84,10 +91,16 @@ pub(crate) type Panic = Box<dyn Any + Send + 'static>;
 pub struct Executor {
     ptr: NonNull<Shared>,
     config: ExecutorConfig,
+    accounting: Accounting,
+    closed: Cell<bool>,
 }
 
 /// Configuration for [`Executor`].
+///
+/// Start from [`ExecutorConfig::default`] and change it with the setters or
+/// the fields, as more fields may be added.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct ExecutorConfig {
     /// The size of the sync queue, which holds task id's for cross-thread
     /// wakes.
@@ -104,6 +117,23 @@ pub struct ExecutorConfig {
     /// The maximum number of hot tasks to run in each tick.
     pub max_interval: u32,
 
+    /// The number of hot tasks to take from each [`Priority`] class in turn,
+    /// indexed in the order of [`Priority::ALL`].
+    ///
+    /// A weight of 0 is treated as 1, and a class never takes up a whole tick
+    /// while lower classes have ready tasks, so that no class is starved.
+    pub priority_weights: [u32; Priority::COUNT],
+
+    /// The [`coop`] budget each poll of a task is given, or `None` to leave
+    /// tasks unconstrained.
+    pub coop_budget: Option<u32>,
+
+    /// Whether to measure the time spent polling tasks, reported in
+    /// [`PriorityStats::busy`].
+    ///
+    /// This reads the clock twice per poll, so it's off by default.
+    pub track_busy_time: bool,
+
     /// A waker to be woken when a task is scheduled.
     ///
     /// This is useful for waking up drivers that switch to kernel state when
@@ -117,11 +147,58 @@ impl Default for ExecutorConfig {
             sync_queue_size: 64,
             local_queue_size: 64,
             max_interval: 61,
+            priority_weights: [4, 2, 1],
+            coop_budget: Some(128),
+            track_busy_time: false,
             waker: None,
         }
     }
 }
 
+impl ExecutorConfig {
+    /// Sets [`sync_queue_size`](Self::sync_queue_size).
+    pub fn with_sync_queue_size(mut self, sync_queue_size: usize) -> Self {
+        self.sync_queue_size = sync_queue_size;
+        self
+    }
+
+    /// Sets [`local_queue_size`](Self::local_queue_size).
+    pub fn with_local_queue_size(mut self, local_queue_size: usize) -> Self {
+        self.local_queue_size = local_queue_size;
+        self
+    }
+
+    /// Sets [`max_interval`](Self::max_interval).
+    pub fn with_max_interval(mut self, max_interval: u32) -> Self {
+        self.max_interval = max_interval;
+        self
+    }
+
+    /// Sets [`priority_weights`](Self::priority_weights).
+    pub fn with_priority_weights(mut self, priority_weights: [u32; Priority::COUNT]) -> Self {
+        self.priority_weights = priority_weights;
+        self
+    }
+
+    /// Sets [`coop_budget`](Self::coop_budget).
+    pub fn with_coop_budget(mut self, coop_budget: Option<u32>) -> Self {
+        self.coop_budget = coop_budget;
+        self
+    }
+
+    /// Sets [`track_busy_time`](Self::track_busy_time).
+    pub fn with_track_busy_time(mut self, track_busy_time: bool) -> Self {
+        self.track_busy_time = track_busy_time;
+        self
+    }
+
+    /// Sets [`waker`](Self::waker).
+    pub fn with_waker(mut self, waker: Option<Waker>) -> Self {
+        self.waker = waker;
+        self
+    }
+}
+
 pub(crate) struct Shared {
     waker: Option<Waker>,
     sync: ArrayQueue<TaskId>,
@@ -171,6 +248,8 @@ impl Executor {
         Self {
             config,
             ptr: unsafe { NonNull::new_unchecked(ptr) },
+            accounting: Accounting::default(),
+            closed: Cell::new(false),
         }
     }
 
@@ -189,11 +268,25 @@ impl Executor {
     /// [`spawn`]: Self::spawn
     /// [`tokio-console`]: crate::console
     pub fn spawn_at<F: Future + 'static>(&self, fut: F, meta: SpawnMeta) -> JoinHandle<F::Output> {
+        self.spawn_with(fut, Priority::default(), meta)
+    }
+
+    /// Spawn a future onto the executor in the given [`Priority`] class,
+    /// attributing it to `meta`.
+    pub fn spawn_with<F: Future + 'static>(
+        &self,
+        fut: F,
+        priority: Priority,
+        meta: SpawnMeta,
+    ) -> JoinHandle<F::Output> {
         let shared = self.shared();
         let tracker = shared.queue.tracker();
         // SAFETY: Executor cannot be sent to ther thread
         let queue = unsafe { shared.queue.get_unchecked() };
-        let task = queue.insert(self.ptr, tracker, fut, meta);
+        let task = queue.insert(self.ptr, tracker, fut, priority, meta);
+        if self.closed.get() {
+            task.cancel(false);
+        }
 
         JoinHandle::new(task)
     }
@@ -201,39 +294,98 @@ impl Executor {
     /// Retrieve all sync tasks, schedule those to the tail of `hot` queue
     /// and run at most [`max_interval`] tasks.
     ///
-    /// Running start with `hot` tasks, then `cold` ones. Finished tasks will
-    /// be pushed back to tail of `cold` queue.
+    /// Hot tasks are taken from the [`Priority`] classes in turn, according to
+    /// [`priority_weights`]. Tasks still pending after running will be pushed
+    /// back to tail of `cold` queue.
     ///
     /// Return whether there are still hot tasks after the tick.
     ///
     /// [`max_interval`]: ExecutorConfig::max_interval
+    /// [`priority_weights`]: ExecutorConfig::priority_weights
     pub fn tick(&self) -> bool {
         let queue = self.queue();
 
         self.shared().drain_sync(queue);
 
-        for id in queue.iter_hot().take(self.config.max_interval as _) {
-            queue.make_cold(id);
-            let task = queue.take(id).expect("Task was not reset back");
-            let res = unsafe { task.run() };
-            if res.is_ready() {
-                // SAFETY: We're removing it soon, so drop will only be called once.
-                // The shared pointer is kept valid until the Executor is dropped,
-                // to avoid use-after-free issues with concurrent wakers.
-                unsafe { task.drop() };
-                queue.remove(id);
-            } else {
-                queue.reset(id, task);
+        // Only tasks already hot when the tick starts are run, so a task waking
+        // itself runs again in the next tick rather than in this one.
+        let mut last = Priority::ALL.map(|priority| queue.hot_tail(priority));
+        let mut budget = self.config.max_interval;
+        while budget > 0 && last.iter().any(Option::is_some) {
+            for (priority, weight) in Priority::ALL.into_iter().zip(self.config.priority_weights) {
+                // Leave room for the lower classes with ready tasks, so that a large weight
+                // cannot take up the whole tick.
+                let reserved = last[priority.index() + 1..]
+                    .iter()
+                    .filter(|last| last.is_some())
+                    .count() as u32;
+                let quota = weight.clamp(1, budget.saturating_sub(reserved).max(1));
+                for _ in 0..quota {
+                    let Some(tail) = last[priority.index()] else {
+                        break;
+                    };
+                    let Some(id) = queue.hot_head(priority) else {
+                        break;
+                    };
+                    self.run(queue, id, priority);
+                    budget -= 1;
+                    if id == tail {
+                        last[priority.index()] = None;
+                    }
+                }
+                if budget == 0 {
+                    break;
+                }
             }
         }
 
         queue.has_hot()
     }
 
+    fn run(&self, queue: &TaskQueue, id: TaskId, priority: Priority) {
+        queue.make_cold(id);
+        let task = queue.take(id).expect("Task was not reset back");
+        let start = self.config.track_busy_time.then(Instant::now);
+        let res = coop::with_budget(self.config.coop_budget, || unsafe { task.run() });
+        self.accounting
+            .record(priority, start.map(|start| start.elapsed()));
+        if res.is_ready() {
+            // SAFETY: We're removing it soon, so drop will only be called once.
+            // The shared pointer is kept valid until the Executor is dropped,
+            // to avoid use-after-free issues with concurrent wakers.
+            unsafe { task.drop() };
+            queue.remove(id);
+        } else {
+            queue.reset(id, task);
+        }
+    }
+
+    /// Run-time statistics of the tasks in the given [`Priority`] class, since
+    /// the executor was created.
+    pub fn priority_stats(&self, priority: Priority) -> PriorityStats {
+        self.accounting.get(priority)
+    }
+
+    /// Stop accepting new tasks. Tasks spawned from now on are cancelled
+    /// before their first poll.
+    pub fn close(&self) {
+        self.closed.set(true);
+    }
+
+    /// Whether [`close`](Self::close) has been called.
+    pub fn is_closed(&self) -> bool {
+        self.closed.get()
+    }
+
+    /// The number of tasks not finished yet.
+    pub fn task_count(&self) -> usize {
+        self.queue().len()
+    }
+
     /// Check if there's still scheduled task that needs to be ran.
     #[doc(hidden)]
     pub fn has_task(&self) -> bool {
-        self.queue().hot_head().is_some()
+        self.queue().has_hot()
     }
 
     /// Clear the executor, drop all tasks.
@@ -0,0 +1,78 @@
+//! Priority classes of tasks.
+
+use std::{cell::Cell, time::Duration};
+
+/// The priority class of a task.
+///
+/// Each class has its own hot queue. Every tick, the [`Executor`] takes tasks
+/// from the classes in turn, as many from each as its weight in
+/// [`ExecutorConfig::priority_weights`], from [`High`] to [`Low`], until
+/// [`max_interval`] tasks have run. A busy class therefore gets a larger share
+/// of the thread, but never starves the others.
+///
+/// [`Executor`]: crate::Executor
+/// [`ExecutorConfig::priority_weights`]: crate::ExecutorConfig::priority_weights
+/// [`max_interval`]: crate::ExecutorConfig::max_interval
+/// [`High`]: Priority::High
+/// [`Low`]: Priority::Low
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Latency-sensitive work, such as request handling.
+    High,
+    /// The default class.
+    #[default]
+    Normal,
+    /// Background work, such as compaction.
+    Low,
+}
+
+impl Priority {
+    /// The number of priority classes.
+    pub const COUNT: usize = 3;
+
+    /// All priority classes, from the highest to the lowest.
+    pub const ALL: [Priority; Self::COUNT] = [Priority::High, Priority::Normal, Priority::Low];
+
+    #[inline(always)]
+    pub(crate) const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Run-time statistics of a priority class, returned by
+/// [`Executor::priority_stats`].
+///
+/// [`Executor::priority_stats`]: crate::Executor::priority_stats
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PriorityStats {
+    /// The number of times tasks of the class have been polled.
+    pub polls: u64,
+    /// The total time spent polling tasks of the class, or zero unless
+    /// [`ExecutorConfig::track_busy_time`] is set.
+    ///
+    /// [`ExecutorConfig::track_busy_time`]: crate::ExecutorConfig::track_busy_time
+    pub busy: Duration,
+}
+
+/// Per-class accounting, only touched on the executor thread.
+#[derive(Debug, Default)]
+pub(crate) struct Accounting {
+    stats: [Cell<PriorityStats>; Priority::COUNT],
+}
+
+impl Accounting {
+    pub fn record(&self, priority: Priority, busy: Option<Duration>) {
+        let cell = &self.stats[priority.index()];
+        let mut stats = cell.get();
+        stats.polls += 1;
+        if let Some(busy) = busy {
+            stats.busy += busy;
+        }
+        cell.set(stats);
+    }
+
+    pub fn get(&self, priority: Priority) -> PriorityStats {
+        self.stats[priority.index()].get()
+    }
+}
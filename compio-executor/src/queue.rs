@@ -3,7 +3,7 @@ use std::{fmt::Debug, ptr::NonNull};
 use compio_send_wrapper::SendWrapper;
 use slotmap::new_key_type;
 
-use crate::{Shared, console::SpawnMeta, task::Task, util::assert_not_impl};
+use crate::{Priority, Shared, console::SpawnMeta, task::Task, util::assert_not_impl};
 
 new_key_type! { pub struct TaskId; }
 
@@ -13,6 +13,9 @@ use slotmap::SlotMap;
 use crate::UnsafeCell;
 
 /// A single-threaded dual queue (hot and cold) for scheduling tasks.
+///
+/// The hot queue is split by [`Priority`], while all cold tasks share a single
+/// queue.
 pub struct TaskQueue {
     inner: UnsafeCell<Inner>,
 }
@@ -37,7 +40,7 @@ impl Debug for TaskQueue {
 #[derive(Debug)]
 struct Inner {
     map: SlotMap<TaskId, Item>,
-    hot: List,
+    hot: [List; Priority::COUNT],
     cold: List,
 }
 
@@ -53,12 +56,7 @@ struct Item {
     next: Option<TaskId>,
     task: Option<Task>,
     is_hot: bool,
-}
-
-#[derive(Debug)]
-pub struct Iter<'a> {
-    queue: &'a TaskQueue,
-    curr: Option<TaskId>,
+    priority: Priority,
 }
 
 type QueueMarker = bool;
@@ -89,10 +87,8 @@ impl TaskQueue {
                     return;
                 }
 
-                inner.hot.head = None;
-                inner.hot.tail = None;
-                inner.cold.head = None;
-                inner.cold.tail = None;
+                inner.hot = Default::default();
+                inner.cold = List::default();
 
                 for task in inner.map.drain().filter_map(|(_, i)| i.task) {
                     trace!(?task, "Dropping task");
@@ -106,8 +102,12 @@ impl TaskQueue {
         }
     }
 
+    pub fn len(&self) -> usize {
+        unsafe { self.with_inner(|inner| inner.map.len()) }
+    }
+
     pub fn has_hot(&self) -> bool {
-        self.hot_head().is_some()
+        unsafe { self.with_inner(|inner| inner.hot.iter().any(|list| list.head.is_some())) }
     }
 
     pub fn take(&self, key: TaskId) -> Option<Task> {
@@ -136,6 +136,7 @@ impl TaskQueue {
         shared: NonNull<Shared>,
         tracker: SendWrapper<()>,
         future: F,
+        priority: Priority,
         meta: SpawnMeta,
     ) -> Task {
         unsafe {
@@ -149,6 +150,7 @@ impl TaskQueue {
                         next: None,
                         task: Some(ptr),
                         is_hot: true,
+                        priority,
                     }
                 });
                 inner.link_tail::<HOT>(key);
@@ -165,26 +167,12 @@ impl TaskQueue {
         unsafe { self.with_inner(|inner| inner.make_cold(key)) }
     }
 
-    pub fn next_hot(&self, key: TaskId) -> Option<TaskId> {
-        unsafe {
-            self.with_inner(|inner| {
-                inner.map.get(key).and_then(|item| {
-                    debug_assert!(item.is_hot);
-                    item.next
-                })
-            })
-        }
+    pub fn hot_head(&self, priority: Priority) -> Option<TaskId> {
+        unsafe { self.with_inner(|inner| inner.hot[priority.index()].head) }
     }
 
-    pub fn hot_head(&self) -> Option<TaskId> {
-        unsafe { self.with_inner(|inner| inner.hot.head) }
-    }
-
-    pub fn iter_hot(&self) -> Iter<'_> {
-        Iter {
-            queue: self,
-            curr: self.hot_head(),
-        }
+    pub fn hot_tail(&self, priority: Priority) -> Option<TaskId> {
+        unsafe { self.with_inner(|inner| inner.hot[priority.index()].tail) }
     }
 
     pub fn remove(&self, key: TaskId) -> Option<Task> {
@@ -218,14 +206,24 @@ impl Inner {
     fn new(size: usize) -> Self {
         Self {
             map: SlotMap::with_capacity_and_key(size),
-            hot: List::default(),
+            hot: Default::default(),
             cold: List::default(),
         }
     }
 
+    /// The queue a task is linked to, or is to be linked to.
+    fn list<const HOT: QueueMarker>(&mut self, key: TaskId) -> &mut List {
+        if HOT {
+            let priority = self.map.get(key).expect("item exists").priority;
+            &mut self.hot[priority.index()]
+        } else {
+            &mut self.cold
+        }
+    }
+
     /// Link a task to the end of a queue
     fn link_tail<const HOT: QueueMarker>(&mut self, key: TaskId) {
-        let list = if HOT { &mut self.hot } else { &mut self.cold };
+        let list = self.list::<HOT>(key);
         let old_tail = list.tail;
 
         list.tail = Some(key);
@@ -246,14 +244,14 @@ impl Inner {
     }
 
     fn unlink<const HOT: QueueMarker>(&mut self, key: TaskId) {
-        let list = if HOT { &mut self.hot } else { &mut self.cold };
-
         let (prev, next) = {
             let item = self.map.get(key).expect("item exists");
             debug_assert_eq!(item.is_hot, HOT);
             (item.prev, item.next)
         };
 
+        let list = self.list::<HOT>(key);
+
         if list.head == Some(key) {
             list.head = next;
         }
@@ -297,13 +295,3 @@ impl Inner {
         self.link_tail::<COLD>(key);
     }
 }
-
-impl<'a> Iterator for Iter<'a> {
-    type Item = TaskId;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let curr = self.curr?;
-        self.curr = self.queue.next_hot(curr);
-        Some(curr)
-    }
-}
@@ -27,6 +27,7 @@ use crate::{
 mod local;
 mod remote;
 mod state;
+pub(crate) mod task_local;
 
 /// A reference counter pointer to the [`TaskAlloc`].
 #[repr(transparent)]
@@ -0,0 +1,274 @@
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt::{Debug, Display},
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Declare a new task-local storage key of type [`LocalKey`].
+///
+/// A task-local value is scoped to a future with [`LocalKey::scope`], and is
+/// visible to that future and everything it awaits while it is polled. It is
+/// not inherited by tasks spawned from within the scope; see
+/// [`LocalKey::inherit`] and [`LocalKey::inherit_async`] to hand it over
+/// explicitly.
+///
+/// ```
+/// compio_executor::task_local! {
+///     /// The id of the request being served.
+///     pub static REQUEST_ID: u64;
+/// }
+///
+/// let id = REQUEST_ID.sync_scope(42, || REQUEST_ID.get());
+/// assert_eq!(id, 42);
+/// assert!(REQUEST_ID.try_get().is_err());
+/// ```
+#[macro_export]
+macro_rules! task_local {
+    () => {};
+
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty; $($rest:tt)*) => {
+        $crate::__task_local_inner!($(#[$attr])* $vis $name, $t);
+        $crate::task_local!($($rest)*);
+    };
+
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty) => {
+        $crate::__task_local_inner!($(#[$attr])* $vis $name, $t);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __task_local_inner {
+    ($(#[$attr:meta])* $vis:vis $name:ident, $t:ty) => {
+        $(#[$attr])*
+        $vis static $name: $crate::LocalKey<$t> = {
+            ::std::thread_local! {
+                static __KEY: ::std::cell::RefCell<::std::option::Option<$t>> =
+                    const { ::std::cell::RefCell::new(::std::option::Option::None) };
+            }
+
+            $crate::LocalKey { inner: __KEY }
+        };
+    };
+}
+
+/// A key for task-local data, declared with [`task_local!`].
+///
+/// [`task_local!`]: crate::task_local
+pub struct LocalKey<T: 'static> {
+    #[doc(hidden)]
+    pub inner: std::thread::LocalKey<RefCell<Option<T>>>,
+}
+
+impl<T: 'static> LocalKey<T> {
+    /// Set the value of the key to `value` while polling `future`.
+    ///
+    /// The value is dropped along with the returned [`TaskLocalFuture`], after
+    /// the future has been dropped inside the scope.
+    pub fn scope<F: Future>(&'static self, value: T, future: F) -> TaskLocalFuture<T, F> {
+        TaskLocalFuture {
+            local: self,
+            slot: Some(value),
+            future: Some(future),
+        }
+    }
+
+    /// Set the value of the key to `value` while calling `f`.
+    pub fn sync_scope<F: FnOnce() -> R, R>(&'static self, value: T, f: F) -> R {
+        let mut slot = Some(value);
+        self.enter(&mut slot, f)
+    }
+
+    /// Access the current value of the key.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if it's not called within a scope of the key.
+    #[track_caller]
+    pub fn with<F: FnOnce(&T) -> R, R>(&'static self, f: F) -> R {
+        match self.try_with(f) {
+            Ok(res) => res,
+            Err(_) => panic!("cannot access a task-local storage value without setting it first"),
+        }
+    }
+
+    /// Access the current value of the key, or return an [`AccessError`] if
+    /// it's not called within a scope of the key.
+    pub fn try_with<F: FnOnce(&T) -> R, R>(&'static self, f: F) -> Result<R, AccessError> {
+        self.inner
+            .try_with(|cell| cell.borrow().as_ref().map(f))
+            .ok()
+            .flatten()
+            .ok_or(AccessError(()))
+    }
+
+    /// Clone the current value of the key.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if it's not called within a scope of the key.
+    #[track_caller]
+    pub fn get(&'static self) -> T
+    where
+        T: Clone,
+    {
+        self.with(T::clone)
+    }
+
+    /// Clone the current value of the key, or return an [`AccessError`] if
+    /// it's not called within a scope of the key.
+    pub fn try_get(&'static self) -> Result<T, AccessError>
+    where
+        T: Clone,
+    {
+        self.try_with(T::clone)
+    }
+
+    /// Capture the current value of the key, and wrap `f` to run with it set.
+    ///
+    /// This is how to carry the value over to a closure that runs on another
+    /// thread, such as the one passed to `spawn_blocking`. If the key is not
+    /// set now, it will not be set when `f` runs either.
+    pub fn inherit<F: FnOnce() -> R, R>(&'static self, f: F) -> impl FnOnce() -> R
+    where
+        T: Clone,
+    {
+        let mut slot = self.try_get().ok();
+        move || self.enter(&mut slot, f)
+    }
+
+    /// Capture the current value of the key, and wrap `f` so that the future
+    /// it returns is polled with it set.
+    ///
+    /// This is the counterpart of [`inherit`] for closures creating futures,
+    /// such as the one passed to `Dispatcher::dispatch`.
+    ///
+    /// [`inherit`]: Self::inherit
+    pub fn inherit_async<F: FnOnce() -> Fut, Fut: Future>(
+        &'static self,
+        f: F,
+    ) -> impl FnOnce() -> TaskLocalFuture<T, Fut>
+    where
+        T: Clone,
+    {
+        let mut slot = self.try_get().ok();
+        move || {
+            let future = self.enter(&mut slot, f);
+            TaskLocalFuture {
+                local: self,
+                slot,
+                future: Some(future),
+            }
+        }
+    }
+
+    /// Swap `slot` into the key while calling `f`, and swap it back afterwards,
+    /// even if `f` panics.
+    fn enter<F: FnOnce() -> R, R>(&'static self, slot: &mut Option<T>, f: F) -> R {
+        struct Guard<'a, T: 'static> {
+            local: &'static LocalKey<T>,
+            slot: &'a mut Option<T>,
+        }
+
+        impl<T: 'static> Drop for Guard<'_, T> {
+            fn drop(&mut self) {
+                self.local.inner.with(|cell| {
+                    mem::swap(self.slot, &mut *cell.borrow_mut());
+                });
+            }
+        }
+
+        self.inner.with(|cell| {
+            let mut value = cell
+                .try_borrow_mut()
+                .expect("cannot enter a task-local scope while the value is borrowed");
+            mem::swap(slot, &mut *value);
+        });
+
+        let _guard = Guard { local: self, slot };
+        f()
+    }
+}
+
+impl<T: 'static> Debug for LocalKey<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad("LocalKey { .. }")
+    }
+}
+
+/// A future that sets a task-local value while it's polled, created by
+/// [`LocalKey::scope`].
+pub struct TaskLocalFuture<T: 'static, F> {
+    local: &'static LocalKey<T>,
+    slot: Option<T>,
+    future: Option<F>,
+}
+
+impl<T: 'static, F: Unpin> Unpin for TaskLocalFuture<T, F> {}
+
+impl<T: 'static, F: Future> Future for TaskLocalFuture<T, F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: `future` is structurally pinned and never moved out; `slot` and
+        // `local` are not pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+        let local = this.local;
+        let future = &mut this.future;
+        local.enter(&mut this.slot, || {
+            // SAFETY: See above.
+            let mut future = unsafe { Pin::new_unchecked(future) };
+            let fut = future
+                .as_mut()
+                .as_pin_mut()
+                .expect("`TaskLocalFuture` polled after completion");
+            let res = fut.poll(cx);
+            if res.is_ready() {
+                // Drop the future inside the scope, so its destructor sees the value.
+                future.set(None);
+            }
+            res
+        })
+    }
+}
+
+impl<T: 'static, F> Drop for TaskLocalFuture<T, F> {
+    fn drop(&mut self) {
+        if self.future.is_none() {
+            return;
+        }
+        // The thread-local may already be destroyed if this is dropped in another
+        // thread-local destructor. Drop the future outside of the scope then.
+        if self.local.inner.try_with(|_| ()).is_err() {
+            return;
+        }
+        let future = &mut self.future;
+        // SAFETY: `future` is pinned and dropped in place.
+        self.local.enter(&mut self.slot, || unsafe {
+            Pin::new_unchecked(future).set(None)
+        });
+    }
+}
+
+impl<T: 'static + Debug, F> Debug for TaskLocalFuture<T, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskLocalFuture")
+            .field("value", &self.slot)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An error returned by [`LocalKey::try_with`] when the key is not set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessError(());
+
+impl Display for AccessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("task-local value not set")
+    }
+}
+
+impl Error for AccessError {}
@@ -0,0 +1,67 @@
+use std::{
+    future::poll_fn,
+    task::{Context, Poll, Waker},
+};
+
+use compio_executor::coop::{has_budget_remaining, poll_proceed, with_budget};
+
+#[test]
+fn test_coop_budget_spent() {
+    let cx = &mut Context::from_waker(Waker::noop());
+    with_budget(Some(2), || {
+        for _ in 0..2 {
+            let Poll::Ready(mut coop) = poll_proceed(cx) else {
+                panic!("budget should remain");
+            };
+            coop.made_progress();
+        }
+        assert!(!has_budget_remaining());
+        assert!(poll_proceed(cx).is_pending());
+    });
+    assert!(has_budget_remaining());
+}
+
+#[test]
+fn test_coop_budget_refunded() {
+    let cx = &mut Context::from_waker(Waker::noop());
+    with_budget(Some(1), || {
+        for _ in 0..3 {
+            let Poll::Ready(coop) = poll_proceed(cx) else {
+                panic!("budget should be refunded");
+            };
+            assert!(!has_budget_remaining());
+            drop(coop);
+        }
+        assert!(has_budget_remaining());
+    });
+}
+
+#[test]
+fn test_coop_unconstrained() {
+    let cx = &mut Context::from_waker(Waker::noop());
+    for _ in 0..1000 {
+        let Poll::Ready(mut coop) = poll_proceed(cx) else {
+            panic!("no budget is set");
+        };
+        coop.made_progress();
+    }
+}
+
+#[test]
+fn test_coop_executor_budget() {
+    let exe = compio_executor::Executor::with_config(
+        compio_executor::ExecutorConfig::default().with_coop_budget(Some(3)),
+    );
+    let task = exe.spawn(poll_fn(|cx| {
+        let mut spent = 0;
+        while let Poll::Ready(mut coop) = poll_proceed(cx) {
+            coop.made_progress();
+            spent += 1;
+        }
+        Poll::Ready(spent)
+    }));
+    exe.tick();
+    let mut task = std::pin::pin!(task);
+    let res = task.as_mut().poll(&mut Context::from_waker(Waker::noop()));
+    assert!(matches!(res, Poll::Ready(Ok(3))));
+}
@@ -0,0 +1,81 @@
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use compio_executor::{Executor, ExecutorConfig, Priority, SpawnMeta};
+
+fn executor(max_interval: u32, priority_weights: [u32; Priority::COUNT]) -> Executor {
+    Executor::with_config(
+        ExecutorConfig::default()
+            .with_max_interval(max_interval)
+            .with_priority_weights(priority_weights),
+    )
+}
+
+fn spawn_recording(exe: &Executor, log: &Rc<RefCell<Vec<Priority>>>, priority: Priority, n: usize) {
+    for _ in 0..n {
+        let log = log.clone();
+        exe.spawn_with(
+            async move { log.borrow_mut().push(priority) },
+            priority,
+            SpawnMeta::capture(),
+        )
+        .detach();
+    }
+}
+
+#[test]
+fn test_priority_weighted_order() {
+    use Priority::*;
+
+    let exe = executor(18, [2, 1, 1]);
+    let log = Rc::new(RefCell::new(vec![]));
+    spawn_recording(&exe, &log, Low, 6);
+    spawn_recording(&exe, &log, Normal, 6);
+    spawn_recording(&exe, &log, High, 6);
+
+    assert!(!exe.tick());
+    assert_eq!(
+        *log.borrow(),
+        [
+            High, High, Normal, Low, High, High, Normal, Low, High, High, Normal, Low, Normal, Low,
+            Normal, Low, Normal, Low,
+        ]
+    );
+}
+
+#[test]
+fn test_priority_no_starvation() {
+    let exe = executor(10, [u32::MAX, 0, 0]);
+    let log = Rc::new(RefCell::new(vec![]));
+    spawn_recording(&exe, &log, Priority::High, 100);
+    spawn_recording(&exe, &log, Priority::Low, 1);
+
+    assert!(exe.tick());
+    let log = log.borrow();
+    assert_eq!(log.len(), 10);
+    assert!(log.contains(&Priority::Low));
+}
+
+#[test]
+fn test_priority_stats() {
+    let exe = executor(61, [4, 2, 1]);
+    let log = Rc::new(RefCell::new(vec![]));
+    spawn_recording(&exe, &log, Priority::High, 3);
+    spawn_recording(&exe, &log, Priority::Low, 5);
+    exe.spawn(async {}).detach();
+
+    while exe.tick() {}
+
+    assert_eq!(exe.priority_stats(Priority::High).polls, 3);
+    assert_eq!(exe.priority_stats(Priority::Normal).polls, 1);
+    assert_eq!(exe.priority_stats(Priority::Low).polls, 5);
+    assert_eq!(exe.priority_stats(Priority::High).busy, Duration::ZERO);
+
+    let exe = Executor::with_config(ExecutorConfig::default().with_track_busy_time(true));
+    exe.spawn(async { std::thread::sleep(Duration::from_millis(1)) })
+        .detach();
+    while exe.tick() {}
+
+    let stats = exe.priority_stats(Priority::Normal);
+    assert_eq!(stats.polls, 1);
+    assert!(stats.busy >= Duration::from_millis(1));
+}
@@ -0,0 +1,168 @@
+use std::{
+    cell::Cell,
+    future::Future,
+    pin::pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use compio_executor::{Executor, JoinHandle};
+
+std::thread_local! {
+    static EXE: Executor = Executor::new();
+}
+
+compio_executor::task_local! {
+    static NUMBER: u32;
+    static NAME: String;
+}
+
+fn spawn<F: Future + 'static>(f: F) -> JoinHandle<F::Output> {
+    EXE.with(|exe| exe.spawn(f))
+}
+
+fn block_on<F: Future + 'static>(f: F) -> F::Output {
+    EXE.with(|exe| {
+        let cx = &mut Context::from_waker(Waker::noop());
+        let mut f = pin!(f);
+        loop {
+            if let Poll::Ready(res) = f.as_mut().poll(cx) {
+                return res;
+            }
+            exe.tick();
+        }
+    })
+}
+
+struct Yield(bool);
+
+impl Future for Yield {
+    type Output = ();
+
+    fn poll(mut self: std::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+async fn yield_now() {
+    Yield(false).await
+}
+
+#[test]
+fn test_task_local_nested_awaits() {
+    async fn inner() -> u32 {
+        yield_now().await;
+        NUMBER.get()
+    }
+
+    let res = block_on(NUMBER.scope(1, async {
+        yield_now().await;
+        let a = NUMBER.get();
+        let b = inner().await;
+        (a, b)
+    }));
+    assert_eq!(res, (1, 1));
+    assert!(NUMBER.try_get().is_err());
+}
+
+#[test]
+fn test_task_local_interleaved_tasks() {
+    let res = block_on(async {
+        let a = spawn(NUMBER.scope(1, async {
+            let mut seen = vec![];
+            for _ in 0..3 {
+                seen.push(NUMBER.get());
+                yield_now().await;
+            }
+            seen
+        }));
+        let b = spawn(NUMBER.scope(2, async {
+            let mut seen = vec![];
+            for _ in 0..3 {
+                seen.push(NUMBER.get());
+                yield_now().await;
+            }
+            seen
+        }));
+        (a.await.unwrap(), b.await.unwrap())
+    });
+    assert_eq!(res, (vec![1; 3], vec![2; 3]));
+}
+
+#[test]
+fn test_task_local_shadowing() {
+    NUMBER.sync_scope(1, || {
+        NUMBER.sync_scope(2, || assert_eq!(NUMBER.get(), 2));
+        assert_eq!(NUMBER.get(), 1);
+    });
+
+    NAME.sync_scope("outer".to_string(), || {
+        NAME.with(|name| assert_eq!(name, "outer"));
+    });
+}
+
+#[test]
+fn test_task_local_not_inherited_by_spawn() {
+    let res = block_on(NUMBER.scope(1, async {
+        spawn(async { NUMBER.try_get().ok() }).await.unwrap()
+    }));
+    assert_eq!(res, None);
+}
+
+#[test]
+fn test_task_local_inherit() {
+    let res = block_on(NUMBER.scope(1, async {
+        let sync = NUMBER.inherit(|| NUMBER.get());
+        let task = spawn(NUMBER.inherit_async(|| async {
+            yield_now().await;
+            NUMBER.get()
+        })());
+        (
+            std::thread::spawn(sync).join().unwrap(),
+            task.await.unwrap(),
+        )
+    }));
+    assert_eq!(res, (1, 1));
+
+    // Nothing to inherit outside of a scope.
+    let unset = NUMBER.inherit(|| NUMBER.try_get().is_err());
+    assert!(unset());
+}
+
+#[test]
+fn test_task_local_drop_in_scope() {
+    struct Probe(Rc<Cell<Option<u32>>>);
+
+    impl Drop for Probe {
+        fn drop(&mut self) {
+            self.0.set(NUMBER.try_get().ok());
+        }
+    }
+
+    let seen = Rc::new(Cell::new(None));
+
+    // Completed future is dropped inside the scope.
+    let probe = Probe(seen.clone());
+    block_on(NUMBER.scope(1, async move {
+        let _probe = probe;
+    }));
+    assert_eq!(seen.get(), Some(1));
+
+    // So is a pending one.
+    let probe = Probe(seen.clone());
+    let fut = NUMBER.scope(2, async move {
+        let _probe = probe;
+        std::future::pending::<()>().await;
+    });
+    let mut fut = Box::pin(fut);
+    let cx = &mut Context::from_waker(Waker::noop());
+    assert!(fut.as_mut().poll(cx).is_pending());
+    drop(fut);
+    assert_eq!(seen.get(), Some(2));
+}
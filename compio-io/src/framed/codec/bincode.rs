@@ -0,0 +1,158 @@
+//! [`Encoder`]/[`Decoder`] implementation with bincode
+//!
+//! This module provides a codec implementation for the binary format of
+//! bincode, through its serde support.
+//!
+//! # Examples
+//!
+//! ```
+//! use compio_buf::IoBufExt;
+//! use compio_io::framed::codec::{Decoder, Encoder, bincode::BincodeCodec};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Person {
+//!     name: String,
+//!     age: u32,
+//! }
+//!
+//! let mut codec = BincodeCodec::new();
+//! let person = Person {
+//!     name: "Alice".to_string(),
+//!     age: 30,
+//! };
+//!
+//! // Encoding
+//! let mut buffer = Vec::new();
+//! codec.encode(person, &mut buffer).unwrap();
+//!
+//! // Decoding
+//! let buf = buffer.slice(..);
+//! let decoded: Person = codec.decode(&buf).unwrap();
+//! assert_eq!(decoded.name, "Alice");
+//! assert_eq!(decoded.age, 30);
+//! ```
+//!
+//! [`Encoder`]: crate::framed::codec::Encoder
+//! [`Decoder`]: crate::framed::codec::Decoder
+
+use std::io;
+
+use bincode::{
+    config::{self, Config, Configuration},
+    error::{DecodeError, EncodeError},
+};
+use compio_buf::{IoBuf, IoBufMut, IoBufMutExt, Slice};
+use serde::{Serialize, de::DeserializeOwned};
+use thiserror::Error;
+
+use crate::framed::codec::{Decoder, Encoder};
+
+/// A codec for serialization and deserialization using bincode.
+///
+/// It uses the [standard] configuration of bincode, unless another one is
+/// given to [`with_config`](Self::with_config).
+///
+/// [standard]: bincode::config::standard
+#[derive(Debug, Clone, Copy)]
+pub struct BincodeCodec<C = Configuration> {
+    config: C,
+}
+
+impl BincodeCodec {
+    /// Creates a new `BincodeCodec` with the standard configuration.
+    pub fn new() -> Self {
+        Self::with_config(config::standard())
+    }
+}
+
+impl<C: Config> BincodeCodec<C> {
+    /// Creates a new `BincodeCodec` with the given configuration.
+    pub fn with_config(config: C) -> Self {
+        Self { config }
+    }
+
+    /// Returns the configuration.
+    pub fn config(&self) -> &C {
+        &self.config
+    }
+}
+
+impl Default for BincodeCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Errors that can occur during bincode encoding or decoding.
+#[derive(Debug, Error)]
+pub enum BincodeCodecError {
+    /// Error from bincode during serialization.
+    #[error("bincode encode error: {0}")]
+    EncodeError(EncodeError),
+
+    /// Error from bincode during deserialization.
+    #[error("bincode decode error: {0}")]
+    DecodeError(DecodeError),
+
+    /// I/O error during encoding or decoding.
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+}
+
+impl<T: Serialize, B: IoBufMut, C: Config> Encoder<T, B> for BincodeCodec<C> {
+    type Error = BincodeCodecError;
+
+    fn encode(&mut self, item: T, buf: &mut B) -> Result<(), Self::Error> {
+        bincode::serde::encode_into_std_write(&item, &mut buf.as_writer(), self.config)
+            .map(|_| ())
+            .map_err(BincodeCodecError::EncodeError)
+    }
+}
+
+impl<T: DeserializeOwned, B: IoBuf, C: Config> Decoder<T, B> for BincodeCodec<C> {
+    type Error = BincodeCodecError;
+
+    fn decode(&mut self, buf: &Slice<B>) -> Result<T, Self::Error> {
+        bincode::serde::decode_from_slice(buf, self.config)
+            .map(|(item, _)| item)
+            .map_err(BincodeCodecError::DecodeError)
+    }
+}
+
+#[test]
+fn test_bincode_codec() {
+    use compio_buf::IoBufExt;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    struct TestStruct {
+        id: u32,
+        name: String,
+    }
+
+    let item = TestStruct {
+        id: 114514,
+        name: "Test".to_string(),
+    };
+
+    // Test encoding, with variable and fixed int encodings
+    let mut codec = BincodeCodec::new();
+    let mut buffer = Vec::new();
+    codec.encode(item.clone(), &mut buffer).unwrap();
+    assert_eq!(buffer, b"\xfc\x52\xbf\x01\x00\x04Test");
+
+    let mut fixed = BincodeCodec::with_config(config::legacy());
+    let mut fixed_buffer = Vec::new();
+    fixed.encode(item.clone(), &mut fixed_buffer).unwrap();
+    assert_eq!(fixed_buffer.len(), 4 + 8 + 4);
+
+    // Test decoding
+    let slice = buffer.slice(..);
+    let decoded: TestStruct = codec.decode(&slice).unwrap();
+    assert_eq!(item, decoded);
+
+    let slice = fixed_buffer.slice(..);
+    let decoded: TestStruct = fixed.decode(&slice).unwrap();
+    assert_eq!(item, decoded);
+}
@@ -0,0 +1,117 @@
+//! [`Encoder`]/[`Decoder`] implementation with ciborium
+//!
+//! This module provides a codec implementation for CBOR serialization and
+//! deserialization using ciborium.
+//!
+//! # Examples
+//!
+//! ```
+//! use compio_buf::IoBufExt;
+//! use compio_io::framed::codec::{Decoder, Encoder, cbor::CborCodec};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Person {
+//!     name: String,
+//!     age: u32,
+//! }
+//!
+//! let mut codec = CborCodec::new();
+//! let person = Person {
+//!     name: "Alice".to_string(),
+//!     age: 30,
+//! };
+//!
+//! // Encoding
+//! let mut buffer = Vec::new();
+//! codec.encode(person, &mut buffer).unwrap();
+//!
+//! // Decoding
+//! let buf = buffer.slice(..);
+//! let decoded: Person = codec.decode(&buf).unwrap();
+//! assert_eq!(decoded.name, "Alice");
+//! assert_eq!(decoded.age, 30);
+//! ```
+//!
+//! [`Encoder`]: crate::framed::codec::Encoder
+//! [`Decoder`]: crate::framed::codec::Decoder
+
+use std::io;
+
+use compio_buf::{IoBuf, IoBufMut, IoBufMutExt, Slice};
+use serde::{Serialize, de::DeserializeOwned};
+use thiserror::Error;
+
+use crate::framed::codec::{Decoder, Encoder};
+
+/// A codec for CBOR serialization and deserialization using ciborium.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CborCodec;
+
+impl CborCodec {
+    /// Creates a new `CborCodec`.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// Errors that can occur during CBOR encoding or decoding.
+#[derive(Debug, Error)]
+pub enum CborCodecError {
+    /// Error from ciborium during serialization.
+    #[error("cbor encode error: {0}")]
+    EncodeError(ciborium::ser::Error<io::Error>),
+
+    /// Error from ciborium during deserialization.
+    #[error("cbor decode error: {0}")]
+    DecodeError(ciborium::de::Error<io::Error>),
+
+    /// I/O error during encoding or decoding.
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+}
+
+impl<T: Serialize, B: IoBufMut> Encoder<T, B> for CborCodec {
+    type Error = CborCodecError;
+
+    fn encode(&mut self, item: T, buf: &mut B) -> Result<(), Self::Error> {
+        ciborium::into_writer(&item, buf.as_writer()).map_err(CborCodecError::EncodeError)
+    }
+}
+
+impl<T: DeserializeOwned, B: IoBuf> Decoder<T, B> for CborCodec {
+    type Error = CborCodecError;
+
+    fn decode(&mut self, buf: &Slice<B>) -> Result<T, Self::Error> {
+        ciborium::from_reader(buf.as_init()).map_err(CborCodecError::DecodeError)
+    }
+}
+
+#[test]
+fn test_cbor_codec() {
+    use compio_buf::IoBufExt;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    struct TestStruct {
+        id: u32,
+        name: String,
+    }
+
+    let mut codec = CborCodec::new();
+    let item = TestStruct {
+        id: 114514,
+        name: "Test".to_string(),
+    };
+
+    // Test encoding
+    let mut buffer = Vec::new();
+    codec.encode(item.clone(), &mut buffer).unwrap();
+    assert_eq!(buffer, b"\xa2\x62id\x1a\x00\x01\xbf\x52\x64name\x64Test");
+
+    // Test decoding
+    let slice = buffer.slice(..);
+    let decoded: TestStruct = codec.decode(&slice).unwrap();
+
+    assert_eq!(item, decoded);
+}
@@ -11,6 +11,21 @@ pub mod bytes;
 #[cfg(feature = "codec-serde-json")]
 pub mod serde_json;
 
+#[cfg(feature = "codec-postcard")]
+pub mod postcard;
+
+#[cfg(feature = "codec-bincode")]
+pub mod bincode;
+
+#[cfg(feature = "codec-cbor")]
+pub mod cbor;
+
+#[cfg(feature = "codec-msgpack")]
+pub mod msgpack;
+
+#[cfg(feature = "codec-prost")]
+pub mod prost;
+
 /// Trait for types that encode values into bytes.
 pub trait Encoder<Item, B: IoBufMut> {
     /// The error type that can be returned during encoding operations.
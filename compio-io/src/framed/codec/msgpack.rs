@@ -0,0 +1,154 @@
+//! [`Encoder`]/[`Decoder`] implementation with rmp-serde
+//!
+//! This module provides a codec implementation for MessagePack serialization
+//! and deserialization using rmp-serde.
+//!
+//! # Examples
+//!
+//! ```
+//! use compio_buf::IoBufExt;
+//! use compio_io::framed::codec::{Decoder, Encoder, msgpack::MsgPackCodec};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Person {
+//!     name: String,
+//!     age: u32,
+//! }
+//!
+//! let mut codec = MsgPackCodec::new();
+//! let person = Person {
+//!     name: "Alice".to_string(),
+//!     age: 30,
+//! };
+//!
+//! // Encoding
+//! let mut buffer = Vec::new();
+//! codec.encode(person, &mut buffer).unwrap();
+//!
+//! // Decoding
+//! let buf = buffer.slice(..);
+//! let decoded: Person = codec.decode(&buf).unwrap();
+//! assert_eq!(decoded.name, "Alice");
+//! assert_eq!(decoded.age, 30);
+//! ```
+//!
+//! [`Encoder`]: crate::framed::codec::Encoder
+//! [`Decoder`]: crate::framed::codec::Decoder
+
+use std::io;
+
+use compio_buf::{IoBuf, IoBufMut, IoBufMutExt, Slice};
+use rmp_serde::{decode, encode};
+use serde::{Serialize, de::DeserializeOwned};
+use thiserror::Error;
+
+use crate::framed::codec::{Decoder, Encoder};
+
+/// A codec for MessagePack serialization and deserialization using rmp-serde.
+///
+/// Structs are encoded as arrays of their fields by default. This codec can
+/// be configured to encode them as maps keyed by field names by setting the
+/// `named` flag, which is larger but tolerates reordered fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MsgPackCodec {
+    named: bool,
+}
+
+impl MsgPackCodec {
+    /// Creates a new `MsgPackCodec` with default settings (structs as
+    /// arrays).
+    pub fn new() -> Self {
+        Self { named: false }
+    }
+
+    /// Creates a new `MsgPackCodec` encoding structs as maps.
+    pub fn named() -> Self {
+        Self { named: true }
+    }
+
+    /// Sets whether structs should be encoded as maps.
+    pub fn set_named(&mut self, named: bool) -> &mut Self {
+        self.named = named;
+        self
+    }
+
+    /// Returns whether structs are encoded as maps.
+    pub fn is_named(&self) -> bool {
+        self.named
+    }
+}
+
+impl Default for MsgPackCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Errors that can occur during MessagePack encoding or decoding.
+#[derive(Debug, Error)]
+pub enum MsgPackCodecError {
+    /// Error from rmp-serde during serialization.
+    #[error("msgpack encode error: {0}")]
+    EncodeError(encode::Error),
+
+    /// Error from rmp-serde during deserialization.
+    #[error("msgpack decode error: {0}")]
+    DecodeError(decode::Error),
+
+    /// I/O error during encoding or decoding.
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+}
+
+impl<T: Serialize, B: IoBufMut> Encoder<T, B> for MsgPackCodec {
+    type Error = MsgPackCodecError;
+
+    fn encode(&mut self, item: T, buf: &mut B) -> Result<(), Self::Error> {
+        let mut writer = buf.as_writer();
+        if self.named {
+            encode::write_named(&mut writer, &item)
+        } else {
+            encode::write(&mut writer, &item)
+        }
+        .map_err(MsgPackCodecError::EncodeError)
+    }
+}
+
+impl<T: DeserializeOwned, B: IoBuf> Decoder<T, B> for MsgPackCodec {
+    type Error = MsgPackCodecError;
+
+    fn decode(&mut self, buf: &Slice<B>) -> Result<T, Self::Error> {
+        rmp_serde::from_slice(buf).map_err(MsgPackCodecError::DecodeError)
+    }
+}
+
+#[test]
+fn test_msgpack_codec() {
+    use compio_buf::IoBufExt;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    struct TestStruct {
+        id: u32,
+        name: String,
+    }
+
+    let item = TestStruct {
+        id: 114514,
+        name: "Test".to_string(),
+    };
+
+    for mut codec in [MsgPackCodec::new(), MsgPackCodec::named()] {
+        // Test encoding
+        let mut buffer = Vec::new();
+        codec.encode(item.clone(), &mut buffer).unwrap();
+        assert_eq!(buffer[0], if codec.is_named() { 0x82 } else { 0x92 });
+
+        // Test decoding
+        let slice = buffer.slice(..);
+        let decoded: TestStruct = codec.decode(&slice).unwrap();
+
+        assert_eq!(item, decoded);
+    }
+}
@@ -0,0 +1,115 @@
+//! [`Encoder`]/[`Decoder`] implementation with postcard
+//!
+//! This module provides a codec implementation for the compact binary format
+//! of postcard.
+//!
+//! # Examples
+//!
+//! ```
+//! use compio_buf::IoBufExt;
+//! use compio_io::framed::codec::{Decoder, Encoder, postcard::PostcardCodec};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Person {
+//!     name: String,
+//!     age: u32,
+//! }
+//!
+//! let mut codec = PostcardCodec::new();
+//! let person = Person {
+//!     name: "Alice".to_string(),
+//!     age: 30,
+//! };
+//!
+//! // Encoding
+//! let mut buffer = Vec::new();
+//! codec.encode(person, &mut buffer).unwrap();
+//!
+//! // Decoding
+//! let buf = buffer.slice(..);
+//! let decoded: Person = codec.decode(&buf).unwrap();
+//! assert_eq!(decoded.name, "Alice");
+//! assert_eq!(decoded.age, 30);
+//! ```
+//!
+//! [`Encoder`]: crate::framed::codec::Encoder
+//! [`Decoder`]: crate::framed::codec::Decoder
+
+use std::io;
+
+use compio_buf::{IoBuf, IoBufMut, IoBufMutExt, Slice};
+use serde::{Serialize, de::DeserializeOwned};
+use thiserror::Error;
+
+use crate::framed::codec::{Decoder, Encoder};
+
+/// A codec for serialization and deserialization using postcard.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PostcardCodec;
+
+impl PostcardCodec {
+    /// Creates a new `PostcardCodec`.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// Errors that can occur during postcard encoding or decoding.
+#[derive(Debug, Error)]
+pub enum PostcardCodecError {
+    /// Error from postcard during serialization or deserialization.
+    #[error("postcard error: {0}")]
+    PostcardError(postcard::Error),
+
+    /// I/O error during encoding or decoding.
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+}
+
+impl<T: Serialize, B: IoBufMut> Encoder<T, B> for PostcardCodec {
+    type Error = PostcardCodecError;
+
+    fn encode(&mut self, item: T, buf: &mut B) -> Result<(), Self::Error> {
+        postcard::to_io(&item, buf.as_writer())
+            .map(|_| ())
+            .map_err(PostcardCodecError::PostcardError)
+    }
+}
+
+impl<T: DeserializeOwned, B: IoBuf> Decoder<T, B> for PostcardCodec {
+    type Error = PostcardCodecError;
+
+    fn decode(&mut self, buf: &Slice<B>) -> Result<T, Self::Error> {
+        postcard::from_bytes(buf).map_err(PostcardCodecError::PostcardError)
+    }
+}
+
+#[test]
+fn test_postcard_codec() {
+    use compio_buf::IoBufExt;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    struct TestStruct {
+        id: u32,
+        name: String,
+    }
+
+    let mut codec = PostcardCodec::new();
+    let item = TestStruct {
+        id: 114514,
+        name: "Test".to_string(),
+    };
+
+    // Test encoding
+    let mut buffer = Vec::new();
+    codec.encode(item.clone(), &mut buffer).unwrap();
+    assert_eq!(buffer, b"\xd2\xfe\x06\x04Test");
+
+    // Test decoding
+    let slice = buffer.slice(..);
+    let decoded: TestStruct = codec.decode(&slice).unwrap();
+
+    assert_eq!(item, decoded);
+}
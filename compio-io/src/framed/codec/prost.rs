@@ -0,0 +1,158 @@
+//! [`Encoder`]/[`Decoder`] implementation with prost
+//!
+//! This module provides a codec implementation for protobuf messages
+//! generated by prost.
+//!
+//! # Examples
+//!
+//! ```
+//! use compio_buf::IoBufExt;
+//! use compio_io::framed::codec::{Decoder, Encoder, prost::ProstCodec};
+//!
+//! #[derive(Clone, PartialEq, prost::Message)]
+//! struct Person {
+//!     #[prost(string, tag = "1")]
+//!     name: String,
+//!     #[prost(uint32, tag = "2")]
+//!     age: u32,
+//! }
+//!
+//! let mut codec = ProstCodec::<Person>::new();
+//! let person = Person {
+//!     name: "Alice".to_string(),
+//!     age: 30,
+//! };
+//!
+//! // Encoding
+//! let mut buffer = Vec::new();
+//! codec.encode(person, &mut buffer).unwrap();
+//!
+//! // Decoding
+//! let buf = buffer.slice(..);
+//! let decoded = codec.decode(&buf).unwrap();
+//! assert_eq!(decoded.name, "Alice");
+//! assert_eq!(decoded.age, 30);
+//! ```
+//!
+//! [`Encoder`]: crate::framed::codec::Encoder
+//! [`Decoder`]: crate::framed::codec::Decoder
+
+use std::{fmt, io, marker::PhantomData};
+
+use compio_buf::{IoBuf, IoBufExt, IoBufMut, SetLenExt, Slice};
+use prost::{DecodeError, EncodeError, Message};
+use thiserror::Error;
+
+use crate::framed::codec::{Decoder, Encoder};
+
+/// A codec for protobuf messages of type `T` using prost.
+///
+/// Messages are encoded directly into the spare capacity of the buffer,
+/// which is reserved up front from their encoded length.
+pub struct ProstCodec<T> {
+    _marker: PhantomData<fn(T) -> T>,
+}
+
+impl<T: Message> ProstCodec<T> {
+    /// Creates a new `ProstCodec`.
+    pub fn new() -> Self {
+        Self {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Message> Default for ProstCodec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for ProstCodec<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ProstCodec<T> {}
+
+impl<T> fmt::Debug for ProstCodec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProstCodec").finish()
+    }
+}
+
+/// Errors that can occur during protobuf encoding or decoding.
+#[derive(Debug, Error)]
+pub enum ProstCodecError {
+    /// Error from prost during encoding.
+    #[error("prost encode error: {0}")]
+    EncodeError(EncodeError),
+
+    /// Error from prost during decoding.
+    #[error("prost decode error: {0}")]
+    DecodeError(DecodeError),
+
+    /// I/O error during encoding or decoding.
+    #[error("IO error: {0}")]
+    IoError(#[from] io::Error),
+}
+
+impl<T: Message, B: IoBufMut> Encoder<T, B> for ProstCodec<T> {
+    type Error = ProstCodecError;
+
+    fn encode(&mut self, item: T, buf: &mut B) -> Result<(), Self::Error> {
+        let len = item.encoded_len();
+        buf.reserve(len).map_err(io::Error::from)?;
+        let start = (*buf).buf_len();
+        let mut spare = &mut buf.as_uninit()[start..start + len];
+        item.encode(&mut spare)
+            .map_err(ProstCodecError::EncodeError)?;
+        // SAFETY: `encode` initialized `len` bytes after `start`.
+        unsafe { buf.advance_to(start + len) };
+        Ok(())
+    }
+}
+
+impl<T: Message + Default, B: IoBuf> Decoder<T, B> for ProstCodec<T> {
+    type Error = ProstCodecError;
+
+    fn decode(&mut self, buf: &Slice<B>) -> Result<T, Self::Error> {
+        T::decode(buf.as_init()).map_err(ProstCodecError::DecodeError)
+    }
+}
+
+#[test]
+fn test_prost_codec() {
+    use compio_buf::IoBufExt;
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct TestMessage {
+        #[prost(uint32, tag = "1")]
+        id: u32,
+        #[prost(string, tag = "2")]
+        name: String,
+    }
+
+    let mut codec = ProstCodec::<TestMessage>::new();
+    let item = TestMessage {
+        id: 114514,
+        name: "Test".to_string(),
+    };
+
+    // Test encoding
+    let mut buffer = Vec::new();
+    codec.encode(item.clone(), &mut buffer).unwrap();
+    assert_eq!(buffer, b"\x08\xd2\xfe\x06\x12\x04Test");
+
+    // Test decoding
+    let slice = buffer.slice(..);
+    let decoded = codec.decode(&slice).unwrap();
+    assert_eq!(item, decoded);
+
+    // A fixed-size buffer must have room for the message
+    let mut small = compio_buf::arrayvec::ArrayVec::<u8, 4>::new();
+    let res = codec.encode(item, &mut small);
+    assert!(matches!(res, Err(ProstCodecError::IoError(_))));
+    assert!(small.is_empty());
+}
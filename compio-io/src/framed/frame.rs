@@ -1,6 +1,6 @@
 //! Traits and implementations for frame extraction and enclosing
 
-use std::io;
+use std::{fmt, io};
 
 use compio_buf::{IoBuf, IoBufExt, IoBufMut, IoBufMutExt, SetLenExt, Slice};
 
@@ -65,20 +65,121 @@ pub trait Framer<B: IoBufMut> {
     fn extract(&mut self, buf: &Slice<B>) -> io::Result<Option<Frame>>;
 }
 
+/// The error of a frame longer than the maximum length its framer accepts.
+///
+/// [`Framer::extract`] returns it as the source of an
+/// [`io::ErrorKind::InvalidData`] error. The buffer still holds the frame, so
+/// the stream cannot be read any further.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FrameTooLong {
+    length: u64,
+    max_frame_length: usize,
+}
+
+impl FrameTooLong {
+    /// Returns the length the frame declared.
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    /// Returns the maximum length of a frame.
+    pub fn max_frame_length(&self) -> usize {
+        self.max_frame_length
+    }
+
+    /// Returns the length of a frame with a `header` and a payload of
+    /// `length` bytes, which must fit in a `usize`.
+    fn check(header: usize, length: u64, max_frame_length: usize) -> io::Result<usize> {
+        let frame_len = usize::try_from(length)
+            .ok()
+            .filter(|len| *len <= max_frame_length)
+            .and_then(|len| header.checked_add(len));
+        match frame_len {
+            Some(frame_len) => Ok(frame_len),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                Self {
+                    length,
+                    max_frame_length,
+                },
+            )),
+        }
+    }
+}
+
+impl fmt::Display for FrameTooLong {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "frame of {} bytes exceeds the maximum of {} bytes",
+            self.length, self.max_frame_length
+        )
+    }
+}
+
+impl std::error::Error for FrameTooLong {}
+
+/// Prepends `header` to the initialized bytes of `buf`.
+fn prepend<B: IoBufMut>(buf: &mut B, header: &[u8]) {
+    let len = (*buf).buf_len();
+
+    buf.reserve(header.len()).expect("Reserve failed");
+    buf.copy_within(0..len, header.len()); // Shift existing data
+    unsafe { buf.advance_to(len + header.len()) };
+    buf.as_mut_slice()[..header.len()].copy_from_slice(header);
+}
+
 /// A simple extractor that frames data by its length.
 ///
-/// It uses 8 bytes to represent the length of the data at the beginning.
+/// By default, a frame is a 4-byte big-endian length followed by that many
+/// bytes of payload. The header can be configured further for other
+/// protocols:
+///
+/// - [`set_length_field_offset`] skips fields before the length field,
+/// - [`set_length_adjustment`] is added to the length, for lengths that count
+///   more or less than the bytes after the length field,
+/// - [`set_num_skip`] sets how many bytes of the header to strip from the
+///   payload, the whole header by default.
+///
+/// For example, a one-byte type followed by a 2-byte length that counts the
+/// whole frame, keeping the type in the payload:
+///
+/// ```
+/// use compio_io::framed::frame::LengthDelimited;
+///
+/// let framer = LengthDelimited::new()
+///     .set_length_field_offset(1)
+///     .set_length_field_len(2)
+///     .set_length_adjustment(-3)
+///     .set_num_skip(0);
+/// # let _ = framer;
+/// ```
+///
+/// [`Framer::enclose`] only prepends the length field, minus the adjustment,
+/// so the offset and skipped bytes only apply to extraction.
+///
+/// [`set_length_field_offset`]: Self::set_length_field_offset
+/// [`set_length_adjustment`]: Self::set_length_adjustment
+/// [`set_num_skip`]: Self::set_num_skip
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct LengthDelimited {
+    length_field_offset: usize,
     length_field_len: usize,
     length_field_is_big_endian: bool,
+    length_adjustment: isize,
+    num_skip: Option<usize>,
+    max_frame_length: usize,
 }
 
 impl Default for LengthDelimited {
     fn default() -> Self {
         Self {
+            length_field_offset: 0,
             length_field_len: 4,
             length_field_is_big_endian: true,
+            length_adjustment: 0,
+            num_skip: None,
+            max_frame_length: usize::MAX,
         }
     }
 }
@@ -122,51 +223,222 @@ impl LengthDelimited {
         self.length_field_is_big_endian = big_endian;
         self
     }
+
+    /// Returns the number of bytes before the length field.
+    pub fn length_field_offset(&self) -> usize {
+        self.length_field_offset
+    }
+
+    /// Sets the number of bytes before the length field. Defaults to 0.
+    pub fn set_length_field_offset(mut self, offset: usize) -> Self {
+        self.length_field_offset = offset;
+        self
+    }
+
+    /// Returns the value added to the length field to get the number of
+    /// bytes after it.
+    pub fn length_adjustment(&self) -> isize {
+        self.length_adjustment
+    }
+
+    /// Sets the value added to the length field to get the number of bytes
+    /// after it. Defaults to 0.
+    ///
+    /// It is negative for a length that counts the header, and positive for
+    /// one that leaves out a trailer.
+    pub fn set_length_adjustment(mut self, adjustment: isize) -> Self {
+        self.length_adjustment = adjustment;
+        self
+    }
+
+    /// Returns the number of bytes stripped from the start of a frame to get
+    /// its payload.
+    pub fn num_skip(&self) -> usize {
+        self.num_skip
+            .unwrap_or(self.length_field_offset + self.length_field_len)
+    }
+
+    /// Sets the number of bytes stripped from the start of a frame to get its
+    /// payload. Defaults to the whole header, up to the end of the length
+    /// field.
+    pub fn set_num_skip(mut self, num_skip: usize) -> Self {
+        self.num_skip = Some(num_skip);
+        self
+    }
+
+    /// Returns the maximum number of bytes after the length field.
+    pub fn max_frame_length(&self) -> usize {
+        self.max_frame_length
+    }
+
+    /// Sets the maximum number of bytes after the length field, checked when
+    /// a frame is extracted. Unlimited by default.
+    ///
+    /// A longer frame fails with [`FrameTooLong`]. Set a limit when reading
+    /// from an untrusted peer, which could otherwise make the reader buffer
+    /// a frame of any length it announces.
+    pub fn set_max_frame_length(mut self, max_frame_length: usize) -> Self {
+        self.max_frame_length = max_frame_length;
+        self
+    }
 }
 
 impl<B: IoBufMut> Framer<B> for LengthDelimited {
+    /// # Panics
+    ///
+    /// Panics if the length minus the adjustment is negative or does not fit
+    /// in the length field.
     fn enclose(&mut self, buf: &mut B) {
-        let len = (*buf).buf_len();
-
-        buf.reserve(self.length_field_len).expect("Reserve failed");
-        buf.copy_within(0..len, self.length_field_len); // Shift existing data
-        unsafe { buf.advance_to(len + self.length_field_len) };
-
-        let slice = buf.as_mut_slice();
         let lfl = self.length_field_len;
+        let len = (*buf).buf_len() as i128 - self.length_adjustment as i128;
+        let len = u64::try_from(len)
+            .ok()
+            .filter(|len| lfl == Self::MAX_LFL || *len < 1 << (lfl * 8))
+            .expect("Frame length does not fit in the length field");
 
         // Write the length at the beginning
-        let len = len as u64;
         let len_bytes = if self.length_field_is_big_endian {
             &len.to_be_bytes()[Self::MAX_LFL - lfl..]
         } else {
             &len.to_le_bytes()[..lfl]
         };
-        slice[..lfl].copy_from_slice(len_bytes);
+        prepend(buf, len_bytes);
     }
 
     fn extract(&mut self, buf: &Slice<B>) -> io::Result<Option<Frame>> {
-        if buf.len() < self.length_field_len {
+        let header = self.length_field_offset + self.length_field_len;
+        if buf.len() < header {
             return Ok(None);
         }
 
         let buf = buf.as_init();
         let lfl = self.length_field_len;
+        let field = &buf[self.length_field_offset..header];
         let mut len_bytes = [0; Self::MAX_LFL];
 
         let len = if self.length_field_is_big_endian {
-            len_bytes[Self::MAX_LFL - lfl..].copy_from_slice(&buf[..lfl]);
+            len_bytes[Self::MAX_LFL - lfl..].copy_from_slice(field);
             u64::from_be_bytes(len_bytes)
         } else {
-            len_bytes[..lfl].copy_from_slice(&buf[..lfl]);
+            len_bytes[..lfl].copy_from_slice(field);
             u64::from_le_bytes(len_bytes)
-        } as usize;
+        };
+
+        let len = u64::try_from(len as i128 + self.length_adjustment as i128).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "adjusted frame length is negative",
+            )
+        })?;
+        let frame_len = FrameTooLong::check(header, len, self.max_frame_length)?;
+        let num_skip = self.num_skip();
+        if num_skip > frame_len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "frame is shorter than the bytes to skip",
+            ));
+        }
+
+        if buf.len() < frame_len {
+            return Ok(None);
+        }
+
+        Ok(Some(Frame::new(num_skip, frame_len - num_skip, 0)))
+    }
+}
 
-        if buf.len() < self.length_field_len + len {
+/// An extractor that frames data by a length encoded as an unsigned varint,
+/// like the length prefixes of protobuf messages.
+///
+/// The length is written in groups of 7 bits, least significant first, with
+/// the high bit of each byte set when another byte follows. It takes up to 10
+/// bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VarintDelimited {
+    max_frame_length: usize,
+}
+
+impl Default for VarintDelimited {
+    fn default() -> Self {
+        Self {
+            max_frame_length: usize::MAX,
+        }
+    }
+}
+
+impl VarintDelimited {
+    /// Max length of a varint encoding a `u64`
+    const MAX_LEN: usize = 10;
+
+    /// Creates a new `VarintDelimited` framer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the maximum length of a payload.
+    pub fn max_frame_length(&self) -> usize {
+        self.max_frame_length
+    }
+
+    /// Sets the maximum length of a payload, checked when a frame is
+    /// extracted. Unlimited by default.
+    ///
+    /// A longer frame fails with [`FrameTooLong`]. Set a limit when reading
+    /// from an untrusted peer, which could otherwise make the reader buffer
+    /// a frame of any length it announces.
+    pub fn set_max_frame_length(mut self, max_frame_length: usize) -> Self {
+        self.max_frame_length = max_frame_length;
+        self
+    }
+}
+
+impl<B: IoBufMut> Framer<B> for VarintDelimited {
+    fn enclose(&mut self, buf: &mut B) {
+        let mut len = (*buf).buf_len() as u64;
+        let mut varint = [0; Self::MAX_LEN];
+        let mut varint_len = 0;
+        loop {
+            let byte = (len & 0x7f) as u8;
+            len >>= 7;
+            if len == 0 {
+                varint[varint_len] = byte;
+                varint_len += 1;
+                break;
+            }
+            varint[varint_len] = byte | 0x80;
+            varint_len += 1;
+        }
+        prepend(buf, &varint[..varint_len]);
+    }
+
+    fn extract(&mut self, buf: &Slice<B>) -> io::Result<Option<Frame>> {
+        let buf = buf.as_init();
+        let mut len = 0u64;
+        let mut header = 0;
+        loop {
+            let Some(&byte) = buf.get(header) else {
+                return Ok(None);
+            };
+            let bits = u64::from(byte & 0x7f);
+            if header == Self::MAX_LEN || (header == Self::MAX_LEN - 1 && bits > 1) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "varint frame length overflows a u64",
+                ));
+            }
+            len |= bits << (7 * header);
+            header += 1;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+
+        let frame_len = FrameTooLong::check(header, len, self.max_frame_length)?;
+        if buf.len() < frame_len {
             return Ok(None);
         }
 
-        Ok(Some(Frame::new(self.length_field_len, len, 0)))
+        Ok(Some(Frame::new(header, frame_len - header, 0)))
     }
 }
 
@@ -308,6 +580,117 @@ mod tests {
         assert_eq!(payload.as_init(), b"hello");
     }
 
+    #[test]
+    fn test_length_delimited_header() {
+        // A type byte, then a length counting the whole frame.
+        let mut framer = LengthDelimited::new()
+            .set_length_field_offset(1)
+            .set_length_field_len(2)
+            .set_length_adjustment(-3)
+            .set_num_skip(0);
+        assert_eq!(framer.num_skip(), 0);
+
+        let buf = Vec::from(b"\x07\x00\x08hello\x07").slice(..);
+        let frame = framer.extract(&buf).unwrap().unwrap();
+        assert_eq!(frame, Frame::new(0, 8, 0));
+        let payload = frame.slice(buf.into_inner());
+        assert_eq!(payload.as_init(), b"\x07\x00\x08hello");
+
+        let buf = Vec::from(b"\x07\x00\x08hell").slice(..);
+        assert_eq!(framer.extract(&buf).unwrap(), None);
+        let buf = Vec::from(b"\x07\x00\x02hello").slice(..);
+        let err = framer.extract(&buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut framer = LengthDelimited::new()
+            .set_length_field_len(2)
+            .set_length_field_is_big_endian(false)
+            .set_length_adjustment(-2);
+        let mut buf = Vec::from(b"hello");
+        framer.enclose(&mut buf);
+        assert_eq!(buf.as_slice(), b"\x07\x00hello");
+    }
+
+    #[test]
+    fn test_length_delimited_max_frame_length() {
+        assert_eq!(LengthDelimited::new().max_frame_length(), usize::MAX);
+        assert_eq!(VarintDelimited::new().max_frame_length(), usize::MAX);
+        let mut framer = LengthDelimited::new().set_max_frame_length(4);
+
+        let buf = Vec::from(b"\x00\x00\x00\x05hello").slice(..);
+        let err = framer.extract(&buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = err
+            .get_ref()
+            .unwrap()
+            .downcast_ref::<FrameTooLong>()
+            .unwrap();
+        assert_eq!((err.length(), err.max_frame_length()), (5, 4));
+
+        let buf = Vec::from(b"\x00\x00\x00\x04hell").slice(..);
+        assert_eq!(framer.extract(&buf).unwrap(), Some(Frame::new(4, 4, 0)));
+    }
+
+    #[test]
+    fn test_length_delimited_frame_length_overflow() {
+        let mut framer = LengthDelimited::new().set_length_field_len(8);
+
+        let buf = Vec::from([0xff; 8]).slice(..);
+        let err = framer.extract(&buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = err
+            .get_ref()
+            .unwrap()
+            .downcast_ref::<FrameTooLong>()
+            .unwrap();
+        assert_eq!(err.length(), u64::MAX);
+    }
+
+    #[test]
+    #[should_panic = "does not fit in the length field"]
+    fn test_length_delimited_overflow() {
+        let mut framer = LengthDelimited::new().set_length_field_len(1);
+        let mut buf = vec![0; 256];
+        framer.enclose(&mut buf);
+    }
+
+    #[test]
+    fn test_varint_delimited() {
+        let mut framer = VarintDelimited::new();
+
+        let mut buf = Vec::from(b"hello");
+        framer.enclose(&mut buf);
+        assert_eq!(buf.as_slice(), b"\x05hello");
+
+        let mut buf = vec![b'a'; 300];
+        framer.enclose(&mut buf);
+        assert_eq!(&buf[..3], b"\xac\x02a");
+
+        let buf = buf.slice(..);
+        let frame = framer.extract(&buf).unwrap().unwrap();
+        assert_eq!(frame, Frame::new(2, 300, 0));
+        let buf = buf.into_inner();
+        assert_eq!(framer.extract(&buf[..1].to_vec().slice(..)).unwrap(), None);
+        assert_eq!(
+            framer.extract(&buf[..100].to_vec().slice(..)).unwrap(),
+            None
+        );
+
+        let buf = Vec::from([0xff; 11]).slice(..);
+        let err = framer.extract(&buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut framer = framer.set_max_frame_length(299);
+        let buf = Vec::from(b"\xac\x02").slice(..);
+        let err = framer.extract(&buf).unwrap_err();
+        let err = err
+            .get_ref()
+            .unwrap()
+            .downcast_ref::<FrameTooLong>()
+            .unwrap();
+        assert_eq!(err.length(), 300);
+    }
+
     #[test]
     fn test_noop_framer() {
         let mut framer = NoopFramer::new();
@@ -29,6 +29,8 @@
 //!
 //! - [`AsyncReadExt`]: Extension trait for [`AsyncRead`]
 //! - [`AsyncReadAtExt`]: Extension trait for [`AsyncReadAt`]
+//! - [`AsyncBufReadExt`]: Extension trait for [`AsyncBufRead`], reading lines
+//!   and delimited segments
 //! - [`AsyncWriteExt`]: Extension trait for [`AsyncWrite`]
 //! - [`AsyncWriteAtExt`]: Extension trait for [`AsyncWriteAt`]
 //!
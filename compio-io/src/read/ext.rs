@@ -6,8 +6,9 @@ use compio_buf::{
     BufResult, IntoInner, IoBufExt, IoBufMut, IoBufMutExt, IoVectoredBufMut, Uninit, t_alloc,
 };
 
+use super::lines::{read_until_max, skip_until};
 use crate::{
-    AsyncRead, AsyncReadAt, IoResult, framed,
+    AsyncBufRead, AsyncRead, AsyncReadAt, IoResult, Lines, Split, framed,
     util::{Splittable, Take},
 };
 
@@ -343,6 +344,86 @@ pub trait AsyncReadAtExt: AsyncReadAt {
 
 impl<A: AsyncReadAt + ?Sized> AsyncReadAtExt for A {}
 
+/// Implemented as an extension trait, adding line and delimiter reading to all
+/// [`AsyncBufRead`] types. Callers will tend to import this trait instead of
+/// [`AsyncBufRead`].
+///
+/// Nothing here bounds how much is read before the delimiter. To guard
+/// against unbounded input, read through [`AsyncReadExt::take`], or use
+/// [`Split::with_max_length`] and [`Lines::with_max_length`].
+pub trait AsyncBufReadExt: AsyncBufRead {
+    /// Read all bytes until the delimiter `delim` or `EOF` is reached, and
+    /// append them to the end of the buffer.
+    ///
+    /// The delimiter, if found, is appended too. Returns the number of bytes
+    /// appended, which is `0` only at `EOF`. On error, the buffer keeps the
+    /// bytes read so far.
+    async fn read_until(&mut self, delim: u8, buf: Vec<u8>) -> BufResult<usize, Vec<u8>> {
+        let BufResult(res, buf) = read_until_max(self, delim, buf, usize::MAX).await;
+        BufResult(
+            res.map(|read| read.expect("read more than usize::MAX bytes")),
+            buf,
+        )
+    }
+
+    /// Read all bytes until a newline (`0xA` byte) or `EOF` is reached, and
+    /// append them to the end of the [`String`].
+    ///
+    /// The newline, if found, is appended too. Like [`read_to_string`], if the
+    /// bytes read are not valid UTF-8, nothing is appended and an
+    /// [`ErrorKind::InvalidData`] error is returned.
+    ///
+    /// [`read_to_string`]: AsyncReadExt::read_to_string
+    async fn read_line(&mut self, buf: String) -> BufResult<usize, String> {
+        let buf = buf.into_bytes();
+        let len = buf.len();
+        let BufResult(res, buf) = self.read_until(b'\n', buf).await;
+        match String::from_utf8(buf) {
+            Ok(buf) => BufResult(res, buf),
+            Err(err) => {
+                let mut buf = err.into_bytes();
+                buf.truncate(len);
+                let res = res.and_then(|_| {
+                    Err(io::Error::new(
+                        ErrorKind::InvalidData,
+                        "stream did not contain valid UTF-8",
+                    ))
+                });
+                // SAFETY: the bytes before `len` came from a `String`
+                BufResult(res, unsafe { String::from_utf8_unchecked(buf) })
+            }
+        }
+    }
+
+    /// Skip all bytes until the delimiter `delim` or `EOF` is reached.
+    ///
+    /// The delimiter, if found, is skipped too. Returns the number of bytes
+    /// skipped.
+    async fn skip_until(&mut self, delim: u8) -> IoResult<usize> {
+        skip_until(self, delim).await
+    }
+
+    /// Returns a stream over the lines of this reader, without their
+    /// trailing `\n` or `\r\n`.
+    fn lines(self) -> Lines<Self>
+    where
+        Self: Sized,
+    {
+        Lines::new(self)
+    }
+
+    /// Returns a stream over the segments of this reader separated by
+    /// `delim`, without the delimiter.
+    fn split(self, delim: u8) -> Split<Self>
+    where
+        Self: Sized,
+    {
+        Split::new(self, delim)
+    }
+}
+
+impl<A: AsyncBufRead + ?Sized> AsyncBufReadExt for A {}
+
 /// An adaptor which implements [`Splittable`] for any [`AsyncRead`], with the
 /// write half being `()`.
 ///
@@ -0,0 +1,233 @@
+use std::{
+    io::{self, ErrorKind},
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use compio_buf::BufResult;
+use futures_util::{FutureExt, Stream};
+
+use crate::{AsyncBufRead, IoResult, PinBoxFuture};
+
+/// Appends bytes to `buf` until `delim` or `EOF` is reached, reading at most
+/// `max` bytes.
+///
+/// Returns the number of bytes appended, or `None` if `max` bytes were
+/// appended without reaching either.
+pub(super) async fn read_until_max<R: AsyncBufRead + ?Sized>(
+    reader: &mut R,
+    delim: u8,
+    mut buf: Vec<u8>,
+    max: usize,
+) -> BufResult<Option<usize>, Vec<u8>> {
+    let mut read = 0;
+    loop {
+        let available = match reader.fill_buf().await {
+            Ok(available) => available,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return BufResult(Err(e), buf),
+        };
+        let limit = available.len().min(max - read);
+        let (used, done) = match available[..limit].iter().position(|&b| b == delim) {
+            Some(index) => (index + 1, true),
+            None => (limit, available.is_empty()),
+        };
+        buf.extend_from_slice(&available[..used]);
+        reader.consume(used);
+        read += used;
+        if done {
+            return BufResult(Ok(Some(read)), buf);
+        } else if read == max {
+            return BufResult(Ok(None), buf);
+        }
+    }
+}
+
+pub(super) async fn skip_until<R: AsyncBufRead + ?Sized>(
+    reader: &mut R,
+    delim: u8,
+) -> IoResult<usize> {
+    let mut read = 0;
+    loop {
+        let available = match reader.fill_buf().await {
+            Ok(available) => available,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        let (used, done) = match available.iter().position(|&b| b == delim) {
+            Some(index) => (index + 1, true),
+            None => (available.len(), available.is_empty()),
+        };
+        reader.consume(used);
+        read += used;
+        if done {
+            return Ok(read);
+        }
+    }
+}
+
+/// Reads the next segment, without its delimiter, or `None` at `EOF`.
+async fn next_segment<R: AsyncBufRead>(
+    reader: &mut R,
+    delim: u8,
+    max_length: usize,
+) -> IoResult<Option<Vec<u8>>> {
+    // Allow for the delimiter after a segment of the maximum length.
+    let max = max_length.saturating_add(1);
+    let BufResult(res, mut segment) = read_until_max(reader, delim, Vec::new(), max).await;
+    match res? {
+        Some(0) => Ok(None),
+        Some(_) => {
+            if segment.last() == Some(&delim) {
+                segment.pop();
+            }
+            Ok(Some(segment))
+        }
+        None => {
+            skip_until(reader, delim).await?;
+            Err(too_long(max_length))
+        }
+    }
+}
+
+fn too_long(max_length: usize) -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidData,
+        format!("segment longer than {max_length} bytes"),
+    )
+}
+
+enum State<R> {
+    Idle(Option<R>),
+    Reading(PinBoxFuture<(R, IoResult<Option<Vec<u8>>>)>),
+    Done,
+}
+
+/// A stream over the segments of an [`AsyncBufRead`] separated by a
+/// delimiter.
+///
+/// Created by [`AsyncBufReadExt::split`](crate::AsyncBufReadExt::split). Each
+/// item is a segment without its delimiter. The last segment is yielded even
+/// if it does not end with the delimiter, unless it is empty.
+pub struct Split<R> {
+    state: State<R>,
+    delim: u8,
+    max_length: usize,
+}
+
+impl<R> Split<R> {
+    pub(crate) fn new(reader: R, delim: u8) -> Self {
+        Self {
+            state: State::Idle(Some(reader)),
+            delim,
+            max_length: usize::MAX,
+        }
+    }
+
+    /// Limits the length of a segment.
+    ///
+    /// A longer segment is skipped up to the next delimiter, and yields an
+    /// [`ErrorKind::InvalidData`] error instead. The stream carries on with
+    /// the segment after it.
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    /// Returns the maximum length of a segment.
+    pub fn max_length(&self) -> usize {
+        self.max_length
+    }
+}
+
+// The reader is never pinned, only moved in and out of the read future.
+impl<R> Unpin for Split<R> {}
+
+impl<R: AsyncBufRead + 'static> Stream for Split<R> {
+    type Item = IoResult<Vec<u8>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match &mut this.state {
+                State::Idle(reader) => {
+                    let mut reader = reader.take().expect("Inconsistent state");
+                    let (delim, max_length) = (this.delim, this.max_length);
+                    this.state = State::Reading(Box::pin(async move {
+                        let segment = next_segment(&mut reader, delim, max_length).await;
+                        (reader, segment)
+                    }));
+                }
+                State::Reading(fut) => {
+                    let (reader, segment) = ready!(fut.poll_unpin(cx));
+                    return Poll::Ready(match segment.transpose() {
+                        Some(segment) => {
+                            this.state = State::Idle(Some(reader));
+                            Some(segment)
+                        }
+                        None => {
+                            this.state = State::Done;
+                            None
+                        }
+                    });
+                }
+                State::Done => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+/// A stream over the lines of an [`AsyncBufRead`].
+///
+/// Created by [`AsyncBufReadExt::lines`](crate::AsyncBufReadExt::lines). Each
+/// item is a line without its trailing `\n` or `\r\n`. A line that is not
+/// valid UTF-8 yields an [`ErrorKind::InvalidData`] error.
+pub struct Lines<R> {
+    split: Split<R>,
+    max_length: usize,
+}
+
+impl<R> Lines<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Self {
+            split: Split::new(reader, b'\n'),
+            max_length: usize::MAX,
+        }
+    }
+
+    /// Limits the length of a line, excluding its line ending.
+    ///
+    /// A longer line is skipped, and yields an [`ErrorKind::InvalidData`]
+    /// error instead. The stream carries on with the line after it.
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        // Leave room for the carriage return.
+        self.split = self.split.with_max_length(max_length.saturating_add(1));
+        self.max_length = max_length;
+        self
+    }
+
+    /// Returns the maximum length of a line.
+    pub fn max_length(&self) -> usize {
+        self.max_length
+    }
+}
+
+impl<R: AsyncBufRead + 'static> Stream for Lines<R> {
+    type Item = IoResult<String>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let Some(line) = ready!(Pin::new(&mut this.split).poll_next(cx)) else {
+            return Poll::Ready(None);
+        };
+        Poll::Ready(Some(line.and_then(|mut line| {
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            if line.len() > this.max_length {
+                return Err(too_long(this.max_length));
+            }
+            String::from_utf8(line).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+        })))
+    }
+}
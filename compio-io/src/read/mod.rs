@@ -9,11 +9,13 @@ use compio_buf::{
 mod buf;
 #[macro_use]
 mod ext;
+mod lines;
 mod managed;
 mod multi;
 
 pub use buf::*;
 pub use ext::*;
+pub use lines::*;
 pub use managed::*;
 pub use multi::*;
 
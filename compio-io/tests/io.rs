@@ -5,10 +5,11 @@ use compio_buf::{
     arrayvec::ArrayVec,
 };
 use compio_io::{
-    AsyncBufRead, AsyncRead, AsyncReadAt, AsyncReadAtExt, AsyncReadExt, AsyncWrite, AsyncWriteAt,
-    AsyncWriteAtExt, AsyncWriteExt, BufReader, BufWriter, split,
+    AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadAt, AsyncReadAtExt, AsyncReadExt,
+    AsyncWrite, AsyncWriteAt, AsyncWriteAtExt, AsyncWriteExt, BufReader, BufWriter, split,
 };
 use futures_executor::block_on;
+use futures_util::StreamExt;
 
 #[test]
 fn io_read() {
@@ -446,6 +447,111 @@ impl AsyncReadAt for ReadOneAt {
     }
 }
 
+#[test]
+fn read_until_and_read_line() {
+    block_on(async {
+        let mut src = BufReader::with_capacity(4, &b"hello\nworld\r\nend"[..]);
+        let (len, buf) = src.read_until(b'\n', vec![b'>']).await.unwrap();
+        assert_eq!(len, 6);
+        assert_eq!(buf, b">hello\n");
+
+        let (len, buf) = src.read_line(String::from("x")).await.unwrap();
+        assert_eq!(len, 7);
+        assert_eq!(buf, "xworld\r\n");
+
+        let (len, buf) = src.read_line(String::new()).await.unwrap();
+        assert_eq!(len, 3);
+        assert_eq!(buf, "end");
+
+        let (len, buf) = src.read_line(String::new()).await.unwrap();
+        assert_eq!(len, 0);
+        assert_eq!(buf, "");
+
+        let mut src = BufReader::with_capacity(4, &[0xff, b'\n', b'o', b'k'][..]);
+        let BufResult(res, buf) = src.read_line(String::from("a")).await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(buf, "a");
+
+        let (len, buf) = src.read_line(buf).await.unwrap();
+        assert_eq!(len, 2);
+        assert_eq!(buf, "aok");
+    })
+}
+
+#[test]
+fn skip_until() {
+    block_on(async {
+        let mut src = BufReader::with_capacity(2, &b"skip;me;rest"[..]);
+        assert_eq!(src.skip_until(b';').await.unwrap(), 5);
+
+        let (len, buf) = src.read_until(b';', Vec::new()).await.unwrap();
+        assert_eq!(len, 3);
+        assert_eq!(buf, b"me;");
+
+        assert_eq!(src.skip_until(b';').await.unwrap(), 4);
+        assert_eq!(src.skip_until(b';').await.unwrap(), 0);
+    })
+}
+
+#[test]
+fn lines_and_split() {
+    block_on(async {
+        let src = BufReader::with_capacity(4, &b"one\r\ntwo\n\nthree"[..]);
+        let lines = src.lines().map(Result::unwrap).collect::<Vec<_>>().await;
+        assert_eq!(lines, ["one", "two", "", "three"]);
+
+        let src = BufReader::with_capacity(4, &b"a,bb,,c,"[..]);
+        let segments = src
+            .split(b',')
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(segments, [&b"a"[..], b"bb", b"", b"c"]);
+
+        let src = BufReader::with_capacity(4, &b""[..]);
+        assert_eq!(src.lines().count().await, 0);
+    })
+}
+
+#[test]
+fn lines_and_split_max_length() {
+    block_on(async {
+        let src = BufReader::with_capacity(4, &b"abc\r\nabcd\nok\nabcdefgh\nxyz"[..]);
+        let lines = src.lines().with_max_length(3);
+        assert_eq!(lines.max_length(), 3);
+        let lines = lines
+            .map(|line| line.map_err(|e| e.kind()))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(
+            lines,
+            [
+                Ok("abc".to_string()),
+                Err(std::io::ErrorKind::InvalidData),
+                Ok("ok".to_string()),
+                Err(std::io::ErrorKind::InvalidData),
+                Ok("xyz".to_string()),
+            ]
+        );
+
+        let src = BufReader::with_capacity(4, &b"ab;abc;abcdef"[..]);
+        let segments = src
+            .split(b';')
+            .with_max_length(2)
+            .map(|segment| segment.map_err(|e| e.kind()))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(
+            segments,
+            [
+                Ok(b"ab".to_vec()),
+                Err(std::io::ErrorKind::InvalidData),
+                Err(std::io::ErrorKind::InvalidData),
+            ]
+        );
+    })
+}
+
 #[test]
 fn read_to_end_at() {
     block_on(async {
@@ -6,7 +6,7 @@ use std::{
     marker::PhantomData,
     pin::Pin,
     rc::Rc,
-    task::{Context, Poll, Waker},
+    task::{Context, Poll, Waker, ready},
 };
 
 use compio_buf::BufResult;
@@ -14,7 +14,7 @@ use compio_driver::{Extra, Key, OpCode, Proactor, PushEntry};
 use futures_util::future::FusedFuture;
 
 use crate::{
-    CancelToken,
+    CancelToken, coop,
     future::{poll_task, poll_task_with_extra, submit_raw},
     waker::{get_ext, get_waker},
 };
@@ -128,6 +128,7 @@ impl<T: OpCode + 'static> Future for Submit<T, ()> {
     type Output = BufResult<usize, T>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut coop = ready!(coop::poll_proceed(cx));
         let this = self.project();
 
         loop {
@@ -139,7 +140,10 @@ impl<T: OpCode + 'static> Future for Submit<T, ()> {
                             *this.state = Some(State::submitted(key));
                             return Poll::Pending;
                         }
-                        PushEntry::Ready(res) => return Poll::Ready(res),
+                        PushEntry::Ready(res) => {
+                            coop.made_progress();
+                            return Poll::Ready(res);
+                        }
                     }
                 }
                 State::Idle { op } => {
@@ -156,6 +160,7 @@ impl<T: OpCode + 'static> Future for Submit<T, ()> {
                             *this.state = Some(State::submitted(key))
                         }
                         PushEntry::Ready(res) => {
+                            coop.made_progress();
                             return Poll::Ready(res);
                         }
                     }
@@ -169,6 +174,7 @@ impl<T: OpCode + 'static> Future for Submit<T, Extra> {
     type Output = (BufResult<usize, T>, Extra);
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut coop = ready!(coop::poll_proceed(cx));
         let this = self.project();
 
         loop {
@@ -181,7 +187,10 @@ impl<T: OpCode + 'static> Future for Submit<T, Extra> {
                             *this.state = Some(State::submitted(key));
                             return Poll::Pending;
                         }
-                        PushEntry::Ready(res) => return Poll::Ready(res),
+                        PushEntry::Ready(res) => {
+                            coop.made_progress();
+                            return Poll::Ready(res);
+                        }
                     }
                 }
                 State::Idle { op } => {
@@ -196,6 +205,7 @@ impl<T: OpCode + 'static> Future for Submit<T, Extra> {
                             *this.state = Some(State::submitted(key))
                         }
                         PushEntry::Ready(res) => {
+                            coop.made_progress();
                             return Poll::Ready((res, this.driver.borrow().default_extra()));
                         }
                     }
@@ -4,7 +4,7 @@ use std::{
     marker::PhantomData,
     pin::Pin,
     rc::Rc,
-    task::{Context, Poll},
+    task::{Context, Poll, ready},
 };
 
 use compio_buf::{BufResult, SetLenExt};
@@ -15,7 +15,7 @@ use compio_driver::{
 use futures_util::{Stream, StreamExt, stream::FusedStream};
 
 use crate::{
-    CancelToken, ContextExt,
+    CancelToken, ContextExt, coop,
     future::{poll_multishot, poll_task_with_extra, submit_raw},
 };
 
@@ -83,6 +83,7 @@ impl<T: OpCode + 'static> Stream for SubmitMulti<T> {
     type Item = BufResult<usize, Extra>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut coop = ready!(coop::poll_proceed(cx));
         let this = self.project();
 
         loop {
@@ -102,6 +103,7 @@ impl<T: OpCode + 'static> Stream for SubmitMulti<T> {
                             *this.state = Some(State::Finished { op });
                             let extra = this.driver.borrow().default_extra();
 
+                            coop.made_progress();
                             return Poll::Ready(Some(BufResult(res, extra)));
                         }
                     }
@@ -113,6 +115,7 @@ impl<T: OpCode + 'static> Stream for SubmitMulti<T> {
                     {
                         *this.state = Some(State::submitted(key));
 
+                        coop.made_progress();
                         return Poll::Ready(Some(res));
                     };
 
@@ -127,6 +130,7 @@ impl<T: OpCode + 'static> Stream for SubmitMulti<T> {
                         PushEntry::Ready((BufResult(res, op), extra)) => {
                             *this.state = Some(State::Finished { op });
 
+                            coop.made_progress();
                             return Poll::Ready(Some(BufResult(res, extra)));
                         }
                     }
@@ -135,6 +139,7 @@ impl<T: OpCode + 'static> Stream for SubmitMulti<T> {
                 State::Finished { op } => {
                     *this.state = Some(State::Finished { op });
 
+                    coop.made_progress();
                     return Poll::Ready(None);
                 }
             }
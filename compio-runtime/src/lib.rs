@@ -28,6 +28,7 @@ mod future;
 mod waker;
 
 pub mod fd;
+pub mod sync;
 
 #[cfg(feature = "time")]
 pub mod time;
@@ -41,14 +42,17 @@ use std::{
     panic::{AssertUnwindSafe, catch_unwind, resume_unwind},
     rc::Rc,
     task::{Context, Poll, Waker},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use compio_buf::{BufResult, IntoInner};
 use compio_driver::{AsRawFd, DriverType, OpCode, Proactor, ProactorBuilder, RawFd, op::Asyncify};
 pub use compio_driver::{BufferPool, ErrorExt};
+pub use compio_executor::{
+    AccessError, JoinError, JoinHandle, LocalKey, Priority, PriorityStats, ResumeUnwind, SpawnMeta,
+    TaskLocalFuture, console, coop, coop::yield_now, task_local,
+};
 use compio_executor::{Executor, ExecutorConfig};
-pub use compio_executor::{JoinError, JoinHandle, ResumeUnwind, SpawnMeta, console};
 use compio_log::{debug, instrument};
 
 use crate::affinity::bind_to_cpu_set;
@@ -70,6 +74,7 @@ fn not_in_compio_runtime() -> ! {
 pub struct Runtime {
     executor: Rc<Executor>,
     driver: Rc<RefCell<Proactor>>,
+    coop_budget: Option<u32>,
     #[cfg(feature = "time")]
     timer_runtime: Rc<RefCell<TimerRuntime>>,
 }
@@ -188,7 +193,9 @@ impl Runtime {
                 let mut context = Context::from_waker(&waker);
                 let mut future = std::pin::pin!(future);
                 loop {
-                    if let Poll::Ready(result) = future.as_mut().poll(&mut context) {
+                    let poll =
+                        coop::with_budget(self.coop_budget, || future.as_mut().poll(&mut context));
+                    if let Poll::Ready(result) = poll {
                         self.run();
                         return result;
                     }
@@ -213,6 +220,63 @@ impl Runtime {
         }
     }
 
+    /// Shut the runtime down, waiting at most `timeout` for it to drain.
+    ///
+    /// This stops accepting new tasks: anything spawned from now on is
+    /// cancelled before it runs. All operations in flight are cancelled, and
+    /// the tasks waiting for them are run, so that they observe the
+    /// cancellation as an error and get a chance to finish. Once no task or
+    /// operation is left, or the deadline is reached, the remaining tasks are
+    /// dropped.
+    ///
+    /// Operations are only cancelled and counted when the proactor tracks them,
+    /// see [`ProactorBuilder::track_in_flight`]. Otherwise only the tasks are
+    /// waited for.
+    ///
+    /// Returns what was still outstanding at the deadline. The buffers owned
+    /// by operations still in flight are kept alive by the driver until the
+    /// kernel is done with them, and are reclaimed when the runtime is dropped
+    /// at the latest.
+    ///
+    /// ## Panics
+    ///
+    /// This method panics if called from within the runtime itself.
+    pub fn shutdown(&self, timeout: Duration) -> ShutdownReport {
+        instrument!(compio_log::Level::DEBUG, "shutdown", ?timeout);
+        if Self::try_with_current(|r| Rc::ptr_eq(&r.executor, &self.executor)).unwrap_or(false) {
+            panic!("cannot shut down a runtime from within itself");
+        }
+
+        let deadline = Instant::now() + timeout;
+        self.executor.close();
+        let cancelled = self.driver.borrow_mut().cancel_all();
+        debug!("cancelled {cancelled} operations");
+
+        let report = self.enter(|| {
+            loop {
+                let remaining_tasks = self.run();
+                let report = ShutdownReport {
+                    tasks: self.executor.task_count(),
+                    ops: self.driver.borrow_mut().in_flight(),
+                };
+                let now = Instant::now();
+                if report.is_clean() || now >= deadline {
+                    return report;
+                }
+                let timeout = if remaining_tasks {
+                    Duration::ZERO
+                } else {
+                    let timeout = deadline - now;
+                    self.current_timeout()
+                        .map_or(timeout, |current| current.min(timeout))
+                };
+                self.poll_with(Some(timeout));
+            }
+        });
+        self.enter(|| self.executor.clear());
+        report
+    }
+
     /// Spawns a new asynchronous task, returning a [`JoinHandle`] for it.
     ///
     /// Spawning a task enables the task to execute concurrently to other tasks.
@@ -240,9 +304,31 @@ impl Runtime {
         self.executor.spawn_at(future, meta)
     }
 
+    /// Spawns a new asynchronous task in the given [`Priority`] class.
+    ///
+    /// See [`RuntimeBuilder::priority_weights`] for how the classes share the
+    /// thread.
+    #[track_caller]
+    pub fn spawn_with_priority<F: Future + 'static>(
+        &self,
+        future: F,
+        priority: Priority,
+    ) -> JoinHandle<F::Output> {
+        self.executor
+            .spawn_with(future, priority, SpawnMeta::capture())
+    }
+
+    /// Run-time statistics of the tasks in the given [`Priority`] class.
+    pub fn priority_stats(&self, priority: Priority) -> PriorityStats {
+        self.executor.priority_stats(priority)
+    }
+
     /// Spawns a blocking task in a new thread, and wait for it.
     ///
     /// The task will not be cancelled even if the future is dropped.
+    ///
+    /// Task-local values are not visible to `f`. Wrap it with
+    /// [`LocalKey::inherit`] to carry them over.
     #[track_caller]
     pub fn spawn_blocking<T: Send + 'static>(
         &self,
@@ -400,6 +486,24 @@ impl Runtime {
     }
 }
 
+/// What was left when [`Runtime::shutdown`] gave up waiting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ShutdownReport {
+    /// The number of tasks not finished, which have been dropped.
+    pub tasks: usize,
+    /// The number of operations still in flight, always zero unless the
+    /// proactor tracks them.
+    pub ops: usize,
+}
+
+impl ShutdownReport {
+    /// Whether every task finished and every operation completed in time.
+    pub fn is_clean(&self) -> bool {
+        self.tasks == 0 && self.ops == 0
+    }
+}
+
 impl Drop for Runtime {
     fn drop(&mut self) {
         // this is not the last runtime reference, no need to clear
@@ -441,6 +545,9 @@ pub struct RuntimeBuilder {
     sync_queue_size: usize,
     local_queue_size: usize,
     event_interval: u32,
+    priority_weights: [u32; Priority::COUNT],
+    coop_budget: Option<u32>,
+    track_busy_time: bool,
 }
 
 impl Default for RuntimeBuilder {
@@ -457,6 +564,9 @@ impl RuntimeBuilder {
             event_interval: 61,
             sync_queue_size: 64,
             local_queue_size: 64,
+            priority_weights: [4, 2, 1],
+            coop_budget: Some(128),
+            track_busy_time: false,
             thread_affinity: HashSet::new(),
         }
     }
@@ -501,6 +611,41 @@ impl RuntimeBuilder {
         self
     }
 
+    /// Sets the number of tasks the scheduler takes from each [`Priority`]
+    /// class in turn, when tasks of several classes are ready.
+    ///
+    /// The default is 4, 2 and 1 for [`High`], [`Normal`] and [`Low`]. A
+    /// weight of 0 is treated as 1, so that no class is starved.
+    ///
+    /// [`High`]: Priority::High
+    /// [`Normal`]: Priority::Normal
+    /// [`Low`]: Priority::Low
+    pub fn priority_weights(&mut self, high: u32, normal: u32, low: u32) -> &mut Self {
+        self.priority_weights = [high, normal, low];
+        self
+    }
+
+    /// Sets the [`coop`] budget each poll of a task is given, or `None` to
+    /// leave tasks unconstrained.
+    ///
+    /// Once a task has driven this many operations or channel receives to
+    /// completion in a single poll, the next one returns [`Poll::Pending`] and
+    /// the task is rescheduled, so that a task whose I/O is always ready
+    /// cannot monopolize the thread. The default is 128.
+    pub fn coop_budget(&mut self, budget: Option<u32>) -> &mut Self {
+        self.coop_budget = budget;
+        self
+    }
+
+    /// Sets whether to measure the time spent polling tasks, reported in
+    /// [`PriorityStats::busy`].
+    ///
+    /// This reads the clock twice per poll, so it's off by default.
+    pub fn track_busy_time(&mut self, track: bool) -> &mut Self {
+        self.track_busy_time = track;
+        self
+    }
+
     /// Build [`Runtime`].
     pub fn build(&self) -> io::Result<Runtime> {
         let RuntimeBuilder {
@@ -509,21 +654,28 @@ impl RuntimeBuilder {
             sync_queue_size,
             local_queue_size,
             event_interval,
+            priority_weights,
+            coop_budget,
+            track_busy_time,
         } = self;
 
         if !thread_affinity.is_empty() {
             bind_to_cpu_set(thread_affinity);
         }
         let driver = proactor_builder.build()?;
-        let executor = Executor::with_config(ExecutorConfig {
-            max_interval: *event_interval,
-            sync_queue_size: *sync_queue_size,
-            local_queue_size: *local_queue_size,
-            waker: Some(driver.waker()),
-        });
+        let config = ExecutorConfig::default()
+            .with_max_interval(*event_interval)
+            .with_sync_queue_size(*sync_queue_size)
+            .with_local_queue_size(*local_queue_size)
+            .with_priority_weights(*priority_weights)
+            .with_coop_budget(*coop_budget)
+            .with_track_busy_time(*track_busy_time)
+            .with_waker(Some(driver.waker()));
+        let executor = Executor::with_config(config);
         Ok(Runtime {
             executor: Rc::new(executor),
             driver: Rc::new(RefCell::new(driver)),
+            coop_budget: *coop_budget,
             #[cfg(feature = "time")]
             timer_runtime: Rc::new(RefCell::new(TimerRuntime::new())),
         })
@@ -576,6 +728,25 @@ pub fn spawn_at<F: Future + 'static>(future: F, meta: SpawnMeta) -> JoinHandle<F
     Runtime::with_current(|r| r.spawn_at(future, meta))
 }
 
+/// Spawns a new asynchronous task in the given [`Priority`] class.
+///
+/// See [`RuntimeBuilder::priority_weights`] for how the classes share the
+/// thread.
+///
+/// ## Panics
+///
+/// This method doesn't create runtime. It tries to obtain the current runtime
+/// by [`Runtime::with_current`].
+#[track_caller]
+pub fn spawn_with_priority<F: Future + 'static>(
+    future: F,
+    priority: Priority,
+) -> JoinHandle<F::Output> {
+    // See the note in `spawn` on why this is not inlined below.
+    let meta = SpawnMeta::capture();
+    Runtime::with_current(|r| r.executor.spawn_with(future, priority, meta))
+}
+
 /// Spawns a blocking task in a new thread, and wait for it.
 ///
 /// The task will not be cancelled even if the future is dropped.
@@ -0,0 +1,295 @@
+//! A multi-producer, multi-consumer channel where every receiver sees every
+//! message.
+//!
+//! The channel keeps the last `capacity` messages. A receiver falling behind
+//! by more than that skips the oldest ones, and is told how many with
+//! [`RecvError::Lagged`].
+
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt::{Debug, Display},
+    future::poll_fn,
+    task::{Context, Poll, ready},
+};
+
+use super::{Lock, Shared, WaitList};
+use crate::coop;
+
+struct State<T> {
+    buffer: VecDeque<T>,
+    capacity: usize,
+    /// Sequence number of the first message in `buffer`.
+    head: u64,
+    senders: usize,
+    receivers: usize,
+    waiters: WaitList,
+}
+
+impl<T> State<T> {
+    fn tail(&self) -> u64 {
+        self.head + self.buffer.len() as u64
+    }
+}
+
+/// Create a broadcast channel keeping the last `capacity` messages.
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+pub fn channel<T: Clone>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "broadcast channel requires capacity > 0");
+    let inner = Shared::new(Lock::new(State {
+        buffer: VecDeque::with_capacity(capacity),
+        capacity,
+        head: 0,
+        senders: 1,
+        receivers: 1,
+        waiters: WaitList::new(),
+    }));
+    (
+        Sender {
+            inner: inner.clone(),
+        },
+        Receiver {
+            inner,
+            next: 0,
+            id: None,
+        },
+    )
+}
+
+/// The sending half of a broadcast channel.
+pub struct Sender<T> {
+    inner: Shared<Lock<State<T>>>,
+}
+
+impl<T> Sender<T> {
+    /// Send a message to all receivers, and return how many there are.
+    ///
+    /// Returns the message back if there's no receiver.
+    pub fn send(&self, value: T) -> Result<usize, SendError<T>> {
+        let (receivers, _evicted, _wakers) = {
+            let mut state = self.inner.lock();
+            if state.receivers == 0 {
+                return Err(SendError(value));
+            }
+            let evicted = if state.buffer.len() == state.capacity {
+                state.head += 1;
+                state.buffer.pop_front()
+            } else {
+                None
+            };
+            state.buffer.push_back(value);
+            (state.receivers, evicted, state.waiters.take_all())
+        };
+        Ok(receivers)
+    }
+
+    /// Create a new receiver, which receives messages sent from now on.
+    pub fn subscribe(&self) -> Receiver<T> {
+        let mut state = self.inner.lock();
+        state.receivers += 1;
+        Receiver {
+            inner: self.inner.clone(),
+            next: state.tail(),
+            id: None,
+        }
+    }
+
+    /// The number of receivers.
+    pub fn receiver_count(&self) -> usize {
+        self.inner.lock().receivers
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.inner.lock().senders += 1;
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let _wakers = {
+            let mut state = self.inner.lock();
+            state.senders -= 1;
+            if state.senders > 0 {
+                return;
+            }
+            state.waiters.take_all()
+        };
+    }
+}
+
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
+
+/// The receiving half of a broadcast channel.
+pub struct Receiver<T> {
+    inner: Shared<Lock<State<T>>>,
+    /// Sequence number of the next message to receive.
+    next: u64,
+    id: Option<u64>,
+}
+
+impl<T: Clone> Receiver<T> {
+    /// Receive the next message.
+    pub async fn recv(&mut self) -> Result<T, RecvError> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Poll to receive the next message.
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Result<T, RecvError>> {
+        let mut coop = ready!(coop::poll_proceed(cx));
+        let Self { inner, next, id } = self;
+        let mut state = inner.lock();
+        let res = match recv(&state, next) {
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Lagged(n)) => Some(Err(RecvError::Lagged(n))),
+            Err(TryRecvError::Closed) => Some(Err(RecvError::Closed)),
+            Ok(value) => Some(Ok(value)),
+        };
+        if let Some(res) = res {
+            coop.made_progress();
+            return Poll::Ready(res);
+        }
+        match *id {
+            Some(id) if state.waiters.update(id, cx.waker()) => {}
+            _ => *id = Some(state.waiters.push(cx.waker(), ())),
+        }
+        Poll::Pending
+    }
+
+    /// Try to receive the next message without waiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let state = self.inner.lock();
+        recv(&state, &mut self.next)
+    }
+}
+
+fn recv<T: Clone>(state: &State<T>, next: &mut u64) -> Result<T, TryRecvError> {
+    if *next < state.head {
+        let lagged = state.head - *next;
+        *next = state.head;
+        return Err(TryRecvError::Lagged(lagged));
+    }
+    let index = (*next - state.head) as usize;
+    match state.buffer.get(index) {
+        Some(value) => {
+            *next += 1;
+            Ok(value.clone())
+        }
+        None if state.senders == 0 => Err(TryRecvError::Closed),
+        None => Err(TryRecvError::Empty),
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Create a new receiver, which receives messages sent from now on.
+    pub fn resubscribe(&self) -> Self {
+        let mut state = self.inner.lock();
+        state.receivers += 1;
+        Self {
+            inner: self.inner.clone(),
+            next: state.tail(),
+            id: None,
+        }
+    }
+
+    /// The number of messages not received yet.
+    pub fn len(&self) -> usize {
+        let state = self.inner.lock();
+        (state.tail() - self.next.max(state.head)) as usize
+    }
+
+    /// Whether all messages are received.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.inner.lock();
+        state.receivers -= 1;
+        if let Some(id) = self.id {
+            state.waiters.remove(id);
+        }
+    }
+}
+
+impl<T> Debug for Receiver<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Receiver")
+            .field("next", &self.next)
+            .finish_non_exhaustive()
+    }
+}
+
+/// There's no receiver. The message is returned back.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> Debug for SendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SendError").finish_non_exhaustive()
+    }
+}
+
+impl<T> Display for SendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("channel closed")
+    }
+}
+
+impl<T> Error for SendError<T> {}
+
+/// Error of [`Receiver::recv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvError {
+    /// All senders are dropped, and all messages are received.
+    Closed,
+    /// The receiver fell behind, and skipped this many messages.
+    Lagged(u64),
+}
+
+impl Display for RecvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Closed => f.write_str("channel closed"),
+            Self::Lagged(n) => write!(f, "channel lagged by {n}"),
+        }
+    }
+}
+
+impl Error for RecvError {}
+
+/// Error of [`Receiver::try_recv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// No new message.
+    Empty,
+    /// All senders are dropped, and all messages are received.
+    Closed,
+    /// The receiver fell behind, and skipped this many messages.
+    Lagged(u64),
+}
+
+impl Display for TryRecvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => f.write_str("channel empty"),
+            Self::Closed => f.write_str("channel closed"),
+            Self::Lagged(n) => write!(f, "channel lagged by {n}"),
+        }
+    }
+}
+
+impl Error for TryRecvError {}
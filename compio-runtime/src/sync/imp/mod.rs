@@ -0,0 +1,22 @@
+//! Flavor-agnostic implementation of the primitives.
+//!
+//! This module is compiled once per flavor, with `super::flavor` pointing to
+//! either [`synchrony::sync`] or [`synchrony::unsync`]. It must not assume
+//! anything about thread safety beyond what the flavor provides.
+
+pub mod broadcast;
+pub mod mpsc;
+mod mutex;
+mod notify;
+pub mod oneshot;
+mod rwlock;
+mod semaphore;
+mod wait_list;
+pub mod watch;
+
+use self::wait_list::WaitList;
+pub use self::{mutex::*, notify::*, rwlock::*, semaphore::*};
+use super::flavor::{
+    mutex_blocking::{Mutex as Lock, MutexGuard as LockGuard},
+    shared::Shared,
+};
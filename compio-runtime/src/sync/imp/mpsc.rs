@@ -0,0 +1,520 @@
+//! A multi-producer, single-consumer queue.
+//!
+//! A bounded channel is created by [`channel`]: sending waits for capacity,
+//! with senders served in FIFO order. An unbounded channel is created by
+//! [`unbounded_channel`]: sending never waits.
+//!
+//! The receiver takes messages one by one with [`Receiver::recv`], or in
+//! batches with [`Receiver::recv_many`], which frees capacity for all of them
+//! at once.
+
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt::{Debug, Display},
+    future::poll_fn,
+    task::{Context, Poll, Waker, ready},
+};
+
+use super::{Lock, Semaphore, Shared, TryAcquireError, semaphore::Acquire};
+use crate::coop;
+
+struct Chan<T> {
+    queue: VecDeque<T>,
+    senders: usize,
+    rx_closed: bool,
+    rx_waker: Option<Waker>,
+}
+
+struct Inner<T> {
+    chan: Lock<Chan<T>>,
+    /// Capacity of a bounded channel.
+    sem: Option<Semaphore>,
+}
+
+impl<T> Inner<T> {
+    fn new(sem: Option<Semaphore>) -> Shared<Self> {
+        Shared::new(Self {
+            chan: Lock::new(Chan {
+                queue: VecDeque::new(),
+                senders: 1,
+                rx_closed: false,
+                rx_waker: None,
+            }),
+            sem,
+        })
+    }
+
+    /// Push a value, for which capacity has been reserved.
+    fn push(&self, value: T) -> Result<(), T> {
+        let waker = {
+            let mut chan = self.chan.lock();
+            if chan.rx_closed {
+                return Err(value);
+            }
+            chan.queue.push_back(value);
+            chan.rx_waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        Ok(())
+    }
+
+    fn add_sender(&self) {
+        self.chan.lock().senders += 1;
+    }
+
+    fn drop_sender(&self) {
+        let waker = {
+            let mut chan = self.chan.lock();
+            chan.senders -= 1;
+            if chan.senders > 0 {
+                return;
+            }
+            chan.rx_waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.chan.lock().rx_closed
+    }
+
+    fn close(&self) {
+        self.chan.lock().rx_closed = true;
+        if let Some(sem) = &self.sem {
+            sem.close();
+        }
+    }
+
+    fn poll_recv_many(&self, cx: &mut Context<'_>, buf: &mut Vec<T>, limit: usize) -> Poll<usize> {
+        if limit == 0 {
+            return Poll::Ready(0);
+        }
+        self.poll_drain(cx, |queue| {
+            let n = queue.len().min(limit);
+            buf.extend(queue.drain(..n));
+            n
+        })
+    }
+
+    fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut value = None;
+        ready!(self.poll_drain(cx, |queue| {
+            value = queue.pop_front();
+            1
+        }));
+        Poll::Ready(value)
+    }
+
+    /// Wait until the queue is non-empty and let `f` take messages from it, or
+    /// the channel is closed and empty.
+    fn poll_drain(
+        &self,
+        cx: &mut Context<'_>,
+        f: impl FnOnce(&mut VecDeque<T>) -> usize,
+    ) -> Poll<usize> {
+        let mut coop = ready!(coop::poll_proceed(cx));
+        let mut chan = self.chan.lock();
+        if !chan.queue.is_empty() {
+            let n = f(&mut chan.queue);
+            drop(chan);
+            if let Some(sem) = &self.sem {
+                sem.add_permits(n);
+            }
+            coop.made_progress();
+            Poll::Ready(n)
+        } else if chan.senders == 0 || chan.rx_closed {
+            coop.made_progress();
+            Poll::Ready(0)
+        } else {
+            match &mut chan.rx_waker {
+                Some(waker) if waker.will_wake(cx.waker()) => {}
+                waker => *waker = Some(cx.waker().clone()),
+            }
+            Poll::Pending
+        }
+    }
+
+    fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut chan = self.chan.lock();
+        match chan.queue.pop_front() {
+            Some(value) => {
+                drop(chan);
+                if let Some(sem) = &self.sem {
+                    sem.add_permits(1);
+                }
+                Ok(value)
+            }
+            None if chan.senders == 0 || chan.rx_closed => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.chan.lock().queue.len()
+    }
+}
+
+/// Create a bounded channel holding at most `capacity` messages.
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero or exceeds [`Semaphore::MAX_PERMITS`].
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "mpsc bounded channel requires capacity > 0");
+    let inner = Inner::new(Some(Semaphore::new(capacity)));
+    (
+        Sender {
+            inner: inner.clone(),
+        },
+        Receiver { inner },
+    )
+}
+
+/// Create an unbounded channel.
+pub fn unbounded_channel<T>() -> (UnboundedSender<T>, UnboundedReceiver<T>) {
+    let inner = Inner::new(None);
+    (
+        UnboundedSender {
+            inner: inner.clone(),
+        },
+        UnboundedReceiver { inner },
+    )
+}
+
+/// The sending half of a bounded channel, created by [`channel`].
+pub struct Sender<T> {
+    inner: Shared<Inner<T>>,
+}
+
+impl<T> Sender<T> {
+    fn sem(&self) -> &Semaphore {
+        self.inner.sem.as_ref().expect("bounded channel")
+    }
+
+    /// Send a value, waiting for capacity if the channel is full.
+    ///
+    /// Returns the value back if the receiver is closed.
+    pub async fn send(&self, value: T) -> Result<(), SendError<T>> {
+        if Acquire::new(self.sem(), 1).await.is_err() {
+            return Err(SendError(value));
+        }
+        self.inner.push(value).map_err(|value| {
+            self.sem().add_permits(1);
+            SendError(value)
+        })
+    }
+
+    /// Try to send a value without waiting.
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        match self.sem().try_take(1) {
+            Ok(()) => self.inner.push(value).map_err(|value| {
+                self.sem().add_permits(1);
+                TrySendError::Closed(value)
+            }),
+            Err(TryAcquireError::Closed) => Err(TrySendError::Closed(value)),
+            Err(TryAcquireError::NoPermits) => Err(TrySendError::Full(value)),
+        }
+    }
+
+    /// The number of messages that can be sent without waiting.
+    pub fn capacity(&self) -> usize {
+        self.sem().available_permits()
+    }
+
+    /// Whether the receiver is closed or dropped.
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
+    /// Whether both senders send to the same channel.
+    pub fn same_channel(&self, other: &Self) -> bool {
+        Shared::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.inner.add_sender();
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.inner.drop_sender();
+    }
+}
+
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
+
+/// The receiving half of a bounded channel, created by [`channel`].
+pub struct Receiver<T> {
+    inner: Shared<Inner<T>>,
+}
+
+impl<T> Receiver<T> {
+    /// Receive the next message, or `None` if the channel is closed and empty.
+    pub async fn recv(&mut self) -> Option<T> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Receive at most `limit` messages into `buf`, waiting until at least one
+    /// is available.
+    ///
+    /// Returns the number of messages received, which is 0 only if `limit` is
+    /// 0, or the channel is closed and empty.
+    pub async fn recv_many(&mut self, buf: &mut Vec<T>, limit: usize) -> usize {
+        poll_fn(|cx| self.poll_recv_many(cx, buf, limit)).await
+    }
+
+    /// Poll to receive the next message.
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.inner.poll_recv(cx)
+    }
+
+    /// Poll to receive at most `limit` messages into `buf`.
+    pub fn poll_recv_many(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &mut Vec<T>,
+        limit: usize,
+    ) -> Poll<usize> {
+        self.inner.poll_recv_many(cx, buf, limit)
+    }
+
+    /// Try to receive the next message without waiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.inner.try_recv()
+    }
+
+    /// Close the channel, so that no more messages can be sent. Messages
+    /// already sent can still be received.
+    pub fn close(&mut self) {
+        self.inner.close();
+    }
+
+    /// The number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Whether the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.inner.close();
+    }
+}
+
+impl<T> Debug for Receiver<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Receiver").finish_non_exhaustive()
+    }
+}
+
+/// The sending half of an unbounded channel, created by
+/// [`unbounded_channel`].
+pub struct UnboundedSender<T> {
+    inner: Shared<Inner<T>>,
+}
+
+impl<T> UnboundedSender<T> {
+    /// Send a value without waiting.
+    ///
+    /// Returns the value back if the receiver is closed.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        self.inner.push(value).map_err(SendError)
+    }
+
+    /// Whether the receiver is closed or dropped.
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
+    /// Whether both senders send to the same channel.
+    pub fn same_channel(&self, other: &Self) -> bool {
+        Shared::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl<T> Clone for UnboundedSender<T> {
+    fn clone(&self) -> Self {
+        self.inner.add_sender();
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Drop for UnboundedSender<T> {
+    fn drop(&mut self) {
+        self.inner.drop_sender();
+    }
+}
+
+impl<T> Debug for UnboundedSender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UnboundedSender").finish_non_exhaustive()
+    }
+}
+
+/// The receiving half of an unbounded channel, created by
+/// [`unbounded_channel`].
+pub struct UnboundedReceiver<T> {
+    inner: Shared<Inner<T>>,
+}
+
+impl<T> UnboundedReceiver<T> {
+    /// Receive the next message, or `None` if the channel is closed and empty.
+    pub async fn recv(&mut self) -> Option<T> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Receive at most `limit` messages into `buf`, waiting until at least one
+    /// is available.
+    ///
+    /// Returns the number of messages received, which is 0 only if `limit` is
+    /// 0, or the channel is closed and empty.
+    pub async fn recv_many(&mut self, buf: &mut Vec<T>, limit: usize) -> usize {
+        poll_fn(|cx| self.poll_recv_many(cx, buf, limit)).await
+    }
+
+    /// Poll to receive the next message.
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.inner.poll_recv(cx)
+    }
+
+    /// Poll to receive at most `limit` messages into `buf`.
+    pub fn poll_recv_many(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &mut Vec<T>,
+        limit: usize,
+    ) -> Poll<usize> {
+        self.inner.poll_recv_many(cx, buf, limit)
+    }
+
+    /// Try to receive the next message without waiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.inner.try_recv()
+    }
+
+    /// Close the channel, so that no more messages can be sent. Messages
+    /// already sent can still be received.
+    pub fn close(&mut self) {
+        self.inner.close();
+    }
+
+    /// The number of messages in the channel.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Whether the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Drop for UnboundedReceiver<T> {
+    fn drop(&mut self) {
+        self.inner.close();
+    }
+}
+
+impl<T> Debug for UnboundedReceiver<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UnboundedReceiver").finish_non_exhaustive()
+    }
+}
+
+/// The receiver is closed. The value is returned back.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> Debug for SendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SendError").finish_non_exhaustive()
+    }
+}
+
+impl<T> Display for SendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("channel closed")
+    }
+}
+
+impl<T> Error for SendError<T> {}
+
+/// Error of [`Sender::try_send`]. The value is returned back.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TrySendError<T> {
+    /// The channel is full.
+    Full(T),
+    /// The receiver is closed.
+    Closed(T),
+}
+
+impl<T> TrySendError<T> {
+    /// Get the value back.
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Full(value) | Self::Closed(value) => value,
+        }
+    }
+}
+
+impl<T> Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Full(_) => f.write_str("Full(..)"),
+            Self::Closed(_) => f.write_str("Closed(..)"),
+        }
+    }
+}
+
+impl<T> Display for TrySendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Full(_) => f.write_str("channel full"),
+            Self::Closed(_) => f.write_str("channel closed"),
+        }
+    }
+}
+
+impl<T> Error for TrySendError<T> {}
+
+/// Error of `try_recv`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// The channel is empty.
+    Empty,
+    /// The channel is closed and empty.
+    Disconnected,
+}
+
+impl Display for TryRecvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => f.write_str("channel empty"),
+            Self::Disconnected => f.write_str("channel closed"),
+        }
+    }
+}
+
+impl Error for TryRecvError {}
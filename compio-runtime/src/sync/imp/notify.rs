@@ -0,0 +1,164 @@
+use std::{
+    fmt::Debug,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::{Lock, WaitList};
+
+/// Notify a single task or all tasks waiting on it.
+///
+/// [`notify_one`] stores a permit if no task is waiting, which is consumed by
+/// the next [`notified`] future. [`notify_waiters`] wakes all the futures
+/// created before it, and stores nothing.
+///
+/// [`notify_one`]: Notify::notify_one
+/// [`notify_waiters`]: Notify::notify_waiters
+/// [`notified`]: Notify::notified
+pub struct Notify {
+    state: Lock<State>,
+}
+
+struct State {
+    permit: bool,
+    generation: u64,
+    waiters: WaitList,
+}
+
+impl Notify {
+    /// Create a new [`Notify`] without a stored permit.
+    pub const fn new() -> Self {
+        Self {
+            state: Lock::new(State {
+                permit: false,
+                generation: 0,
+                waiters: WaitList::new(),
+            }),
+        }
+    }
+
+    /// Wait for a notification.
+    ///
+    /// The returned future is woken by [`notify_waiters`] even if it's not
+    /// polled before the call.
+    ///
+    /// [`notify_waiters`]: Notify::notify_waiters
+    pub fn notified(&self) -> Notified<'_> {
+        Notified {
+            notify: self,
+            generation: self.state.lock().generation,
+            id: None,
+            done: false,
+        }
+    }
+
+    /// Wake the first waiting task, or store a permit for the next one if none
+    /// is waiting.
+    pub fn notify_one(&self) {
+        let waker = {
+            let mut state = self.state.lock();
+            match state.waiters.pop_front() {
+                Some((waker, ())) => Some(waker),
+                None => {
+                    state.permit = true;
+                    None
+                }
+            }
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Wake all waiting tasks.
+    pub fn notify_waiters(&self) {
+        let wakers = {
+            let mut state = self.state.lock();
+            state.generation = state.generation.wrapping_add(1);
+            state.waiters.take_all()
+        };
+        drop(wakers);
+    }
+}
+
+impl Default for Notify {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for Notify {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Notify")
+            .field("permit", &self.state.lock().permit)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Future returned by [`Notify::notified`].
+#[must_use = "futures do nothing unless polled"]
+pub struct Notified<'a> {
+    notify: &'a Notify,
+    generation: u64,
+    id: Option<u64>,
+    done: bool,
+}
+
+impl Future for Notified<'_> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.done {
+            return Poll::Ready(());
+        }
+        let mut state = self.notify.state.lock();
+        let ready = if state.generation != self.generation {
+            if let Some(id) = self.id {
+                state.waiters.remove(id);
+            }
+            true
+        } else if let Some(id) = self.id {
+            // Popped by `notify_one`.
+            !state.waiters.update(id, cx.waker())
+        } else if state.permit {
+            state.permit = false;
+            true
+        } else {
+            let id = state.waiters.push(cx.waker(), ());
+            drop(state);
+            self.id = Some(id);
+            return Poll::Pending;
+        };
+        drop(state);
+        if ready {
+            self.id = None;
+            self.done = true;
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for Notified<'_> {
+    fn drop(&mut self) {
+        let Some(id) = self.id else {
+            return;
+        };
+        let mut state = self.notify.state.lock();
+        if state.waiters.remove(id).is_none() && state.generation == self.generation {
+            // We were picked by `notify_one` but never observed it: pass the
+            // notification on instead of losing it.
+            drop(state);
+            self.notify.notify_one();
+        }
+    }
+}
+
+impl Debug for Notified<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Notified")
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,203 @@
+//! A channel for sending a single value.
+//!
+//! The [`Receiver`] is a future resolving to the value, or to [`RecvError`] if
+//! the [`Sender`] is dropped without sending.
+
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    future::poll_fn,
+    pin::Pin,
+    task::{Context, Poll, Waker, ready},
+};
+
+use super::{Lock, Shared};
+use crate::coop;
+
+struct State<T> {
+    value: Option<T>,
+    tx_dropped: bool,
+    rx_closed: bool,
+    rx_waker: Option<Waker>,
+    tx_waker: Option<Waker>,
+}
+
+/// Create a oneshot channel.
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let inner = Shared::new(Lock::new(State {
+        value: None,
+        tx_dropped: false,
+        rx_closed: false,
+        rx_waker: None,
+        tx_waker: None,
+    }));
+    (
+        Sender {
+            inner: inner.clone(),
+        },
+        Receiver { inner },
+    )
+}
+
+fn register(slot: &mut Option<Waker>, waker: &Waker) {
+    match slot {
+        Some(w) if w.will_wake(waker) => {}
+        slot => *slot = Some(waker.clone()),
+    }
+}
+
+/// The sending half of a oneshot channel.
+pub struct Sender<T> {
+    inner: Shared<Lock<State<T>>>,
+}
+
+impl<T> Sender<T> {
+    /// Send the value, or return it back if the receiver is closed.
+    pub fn send(self, value: T) -> Result<(), T> {
+        let waker = {
+            let mut state = self.inner.lock();
+            if state.rx_closed {
+                return Err(value);
+            }
+            state.value = Some(value);
+            state.rx_waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        Ok(())
+    }
+
+    /// Whether the receiver is closed or dropped.
+    pub fn is_closed(&self) -> bool {
+        self.inner.lock().rx_closed
+    }
+
+    /// Wait until the receiver is closed or dropped.
+    pub async fn closed(&mut self) {
+        poll_fn(|cx| self.poll_closed(cx)).await
+    }
+
+    /// Poll until the receiver is closed or dropped.
+    pub fn poll_closed(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.inner.lock();
+        if state.rx_closed {
+            Poll::Ready(())
+        } else {
+            register(&mut state.tx_waker, cx.waker());
+            Poll::Pending
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let waker = {
+            let mut state = self.inner.lock();
+            state.tx_dropped = true;
+            state.rx_waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
+
+/// The receiving half of a oneshot channel.
+pub struct Receiver<T> {
+    inner: Shared<Lock<State<T>>>,
+}
+
+impl<T> Receiver<T> {
+    /// Try to receive the value without waiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let mut state = self.inner.lock();
+        match state.value.take() {
+            Some(value) => Ok(value),
+            None if state.tx_dropped => Err(TryRecvError::Closed),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Close the channel, so the sender cannot send. A value already sent can
+    /// still be received.
+    pub fn close(&mut self) {
+        let waker = {
+            let mut state = self.inner.lock();
+            state.rx_closed = true;
+            state.tx_waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Future for Receiver<T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut coop = ready!(coop::poll_proceed(cx));
+        let mut state = self.inner.lock();
+        let res = match state.value.take() {
+            Some(value) => Ok(value),
+            None if state.tx_dropped => Err(RecvError(())),
+            None => {
+                register(&mut state.rx_waker, cx.waker());
+                return Poll::Pending;
+            }
+        };
+        coop.made_progress();
+        Poll::Ready(res)
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+impl<T> Debug for Receiver<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Receiver").finish_non_exhaustive()
+    }
+}
+
+/// The sender is dropped without sending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError(());
+
+impl Display for RecvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("channel closed")
+    }
+}
+
+impl Error for RecvError {}
+
+/// Error of [`Receiver::try_recv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// The value is not sent yet.
+    Empty,
+    /// The sender is dropped without sending, or the value is already taken.
+    Closed,
+}
+
+impl Display for TryRecvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => f.write_str("channel empty"),
+            Self::Closed => f.write_str("channel closed"),
+        }
+    }
+}
+
+impl Error for TryRecvError {}
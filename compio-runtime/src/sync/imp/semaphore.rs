@@ -0,0 +1,399 @@
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use super::{Lock, Shared, WaitList, wait_list::Wakers};
+use crate::coop;
+
+/// A counting semaphore, handing out permits in FIFO order.
+///
+/// A waiter asking for more permits than available blocks the ones queued
+/// after it, even if those could be satisfied, so that it's not starved.
+pub struct Semaphore {
+    state: Lock<State>,
+}
+
+struct State {
+    permits: usize,
+    closed: bool,
+    waiters: WaitList<Waiter>,
+}
+
+struct Waiter {
+    needed: usize,
+    granted: bool,
+}
+
+impl State {
+    /// Hand out released permits to the waiters in order, and return their
+    /// wakers.
+    fn grant(&mut self) -> Wakers {
+        let mut wakers = Wakers::default();
+        for entry in self.waiters.iter_mut().filter(|e| !e.data.granted) {
+            if entry.data.needed > self.permits {
+                break;
+            }
+            self.permits -= entry.data.needed;
+            entry.data.granted = true;
+            wakers.0.push(entry.waker().clone());
+        }
+        wakers
+    }
+}
+
+impl Semaphore {
+    /// The maximum number of permits a semaphore can hold.
+    pub const MAX_PERMITS: usize = usize::MAX >> 3;
+
+    /// Create a new semaphore with the initial number of permits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `permits` exceeds [`Semaphore::MAX_PERMITS`].
+    pub const fn new(permits: usize) -> Self {
+        assert!(permits <= Self::MAX_PERMITS, "too many permits");
+        Self {
+            state: Lock::new(State {
+                permits,
+                closed: false,
+                waiters: WaitList::new(),
+            }),
+        }
+    }
+
+    /// The number of permits currently available.
+    pub fn available_permits(&self) -> usize {
+        self.state.lock().permits
+    }
+
+    /// Add `n` new permits to the semaphore.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the total exceeds [`Semaphore::MAX_PERMITS`].
+    pub fn add_permits(&self, n: usize) {
+        if n == 0 {
+            return;
+        }
+        let wakers = {
+            let mut state = self.state.lock();
+            state.permits = state
+                .permits
+                .checked_add(n)
+                .filter(|&p| p <= Self::MAX_PERMITS)
+                .expect("too many permits");
+            state.grant()
+        };
+        drop(wakers);
+    }
+
+    /// Close the semaphore. Pending and future acquisitions fail with
+    /// [`AcquireError`], while acquired permits stay valid.
+    ///
+    /// Waiters already granted their permits still acquire them.
+    pub fn close(&self) {
+        let wakers = {
+            let mut state = self.state.lock();
+            state.closed = true;
+            // Granted waiters hold permits taken from the count, and have been
+            // woken already.
+            state.waiters.take_if(|waiter| !waiter.granted)
+        };
+        drop(wakers);
+    }
+
+    /// Whether the semaphore is closed.
+    pub fn is_closed(&self) -> bool {
+        self.state.lock().closed
+    }
+
+    /// Try to acquire a permit without waiting.
+    pub fn try_acquire(&self) -> Result<SemaphorePermit<'_>, TryAcquireError> {
+        self.try_acquire_many(1)
+    }
+
+    /// Try to acquire `n` permits without waiting.
+    pub fn try_acquire_many(&self, n: u32) -> Result<SemaphorePermit<'_>, TryAcquireError> {
+        self.try_take(n as _).map(|()| SemaphorePermit {
+            sem: self,
+            permits: n,
+        })
+    }
+
+    /// Acquire a permit, waiting for one to be released if none is available.
+    pub async fn acquire(&self) -> Result<SemaphorePermit<'_>, AcquireError> {
+        self.acquire_many(1).await
+    }
+
+    /// Acquire `n` permits, waiting for them to be released if not enough are
+    /// available.
+    pub async fn acquire_many(&self, n: u32) -> Result<SemaphorePermit<'_>, AcquireError> {
+        Acquire::new(self, n as _).await?;
+        Ok(SemaphorePermit {
+            sem: self,
+            permits: n,
+        })
+    }
+
+    /// Try to acquire a permit owning a reference to the semaphore, without
+    /// waiting.
+    pub fn try_acquire_owned(self: Shared<Self>) -> Result<OwnedSemaphorePermit, TryAcquireError> {
+        self.try_acquire_many_owned(1)
+    }
+
+    /// Try to acquire `n` permits owning a reference to the semaphore, without
+    /// waiting.
+    pub fn try_acquire_many_owned(
+        self: Shared<Self>,
+        n: u32,
+    ) -> Result<OwnedSemaphorePermit, TryAcquireError> {
+        self.try_take(n as _).map(|()| OwnedSemaphorePermit {
+            sem: self,
+            permits: n,
+        })
+    }
+
+    /// Acquire a permit owning a reference to the semaphore.
+    pub async fn acquire_owned(self: Shared<Self>) -> Result<OwnedSemaphorePermit, AcquireError> {
+        self.acquire_many_owned(1).await
+    }
+
+    /// Acquire `n` permits owning a reference to the semaphore.
+    pub async fn acquire_many_owned(
+        self: Shared<Self>,
+        n: u32,
+    ) -> Result<OwnedSemaphorePermit, AcquireError> {
+        Acquire::new(&self, n as _).await?;
+        Ok(OwnedSemaphorePermit {
+            sem: self,
+            permits: n,
+        })
+    }
+
+    pub(crate) fn try_take(&self, n: usize) -> Result<(), TryAcquireError> {
+        let mut state = self.state.lock();
+        if state.closed {
+            Err(TryAcquireError::Closed)
+        } else if state.waiters.is_empty() && state.permits >= n {
+            state.permits -= n;
+            Ok(())
+        } else {
+            Err(TryAcquireError::NoPermits)
+        }
+    }
+}
+
+impl Debug for Semaphore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.lock();
+        f.debug_struct("Semaphore")
+            .field("permits", &state.permits)
+            .field("closed", &state.closed)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Future of taking permits from a [`Semaphore`].
+pub(crate) struct Acquire<'a> {
+    sem: &'a Semaphore,
+    needed: usize,
+    id: Option<u64>,
+}
+
+impl<'a> Acquire<'a> {
+    pub fn new(sem: &'a Semaphore, needed: usize) -> Self {
+        Self {
+            sem,
+            needed,
+            id: None,
+        }
+    }
+}
+
+impl Acquire<'_> {
+    fn poll_acquire(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), AcquireError>> {
+        let mut state = self.sem.state.lock();
+        match self.id {
+            None => {
+                if state.closed {
+                    return Poll::Ready(Err(AcquireError(())));
+                }
+                if state.waiters.is_empty() && state.permits >= self.needed {
+                    state.permits -= self.needed;
+                    return Poll::Ready(Ok(()));
+                }
+                let id = state.waiters.push(
+                    cx.waker(),
+                    Waiter {
+                        needed: self.needed,
+                        granted: false,
+                    },
+                );
+                drop(state);
+                self.id = Some(id);
+                Poll::Pending
+            }
+            Some(id) => match state.waiters.get_mut(id) {
+                Some(entry) if entry.data.granted => {
+                    state.waiters.remove(id);
+                    drop(state);
+                    self.id = None;
+                    self.needed = 0;
+                    Poll::Ready(Ok(()))
+                }
+                Some(entry) => {
+                    entry.set_waker(cx.waker());
+                    Poll::Pending
+                }
+                // Only closing removes a waiter without granting it.
+                None => {
+                    drop(state);
+                    self.id = None;
+                    self.needed = 0;
+                    Poll::Ready(Err(AcquireError(())))
+                }
+            },
+        }
+    }
+}
+
+impl Future for Acquire<'_> {
+    type Output = Result<(), AcquireError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut coop = ready!(coop::poll_proceed(cx));
+        let res = self.poll_acquire(cx);
+        if res.is_ready() {
+            coop.made_progress();
+        }
+        res
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        let Some(id) = self.id else {
+            return;
+        };
+        let wakers = {
+            let mut state = self.sem.state.lock();
+            match state.waiters.remove(id) {
+                // Give back the permits granted to us, or let the waiters behind us
+                // try again if we were blocking them.
+                Some(Waiter { granted: true, .. }) => {
+                    state.permits += self.needed;
+                    state.grant()
+                }
+                Some(_) => state.grant(),
+                None => Wakers::default(),
+            }
+        };
+        drop(wakers);
+    }
+}
+
+/// Permits acquired from a [`Semaphore`], released on drop.
+#[must_use]
+pub struct SemaphorePermit<'a> {
+    sem: &'a Semaphore,
+    permits: u32,
+}
+
+impl SemaphorePermit<'_> {
+    /// Forget the permits, so they are not released on drop.
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
+
+    /// The number of permits held.
+    pub fn num_permits(&self) -> usize {
+        self.permits as _
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        self.sem.add_permits(self.permits as _);
+    }
+}
+
+impl Debug for SemaphorePermit<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SemaphorePermit")
+            .field("permits", &self.permits)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Permits acquired from a shared [`Semaphore`], released on drop.
+#[must_use]
+pub struct OwnedSemaphorePermit {
+    sem: Shared<Semaphore>,
+    permits: u32,
+}
+
+impl OwnedSemaphorePermit {
+    /// Forget the permits, so they are not released on drop.
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
+
+    /// The number of permits held.
+    pub fn num_permits(&self) -> usize {
+        self.permits as _
+    }
+
+    /// The semaphore the permits are acquired from.
+    pub fn semaphore(&self) -> &Shared<Semaphore> {
+        &self.sem
+    }
+}
+
+impl Drop for OwnedSemaphorePermit {
+    fn drop(&mut self) {
+        self.sem.add_permits(self.permits as _);
+    }
+}
+
+impl Debug for OwnedSemaphorePermit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OwnedSemaphorePermit")
+            .field("permits", &self.permits)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The [`Semaphore`] is closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcquireError(());
+
+impl Display for AcquireError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("semaphore closed")
+    }
+}
+
+impl Error for AcquireError {}
+
+/// Error of [`Semaphore::try_acquire`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryAcquireError {
+    /// The semaphore is closed.
+    Closed,
+    /// Not enough permits are available.
+    NoPermits,
+}
+
+impl Display for TryAcquireError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Closed => f.write_str("semaphore closed"),
+            Self::NoPermits => f.write_str("no permits available"),
+        }
+    }
+}
+
+impl Error for TryAcquireError {}
@@ -0,0 +1,115 @@
+use std::{collections::VecDeque, task::Waker};
+
+/// A FIFO list of parked tasks, identified by the id returned from
+/// [`WaitList::push`].
+pub(crate) struct WaitList<T = ()> {
+    next_id: u64,
+    entries: VecDeque<Entry<T>>,
+}
+
+pub(crate) struct Entry<T> {
+    id: u64,
+    waker: Waker,
+    pub data: T,
+}
+
+impl<T> WaitList<T> {
+    pub const fn new() -> Self {
+        Self {
+            next_id: 0,
+            entries: VecDeque::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Park a task at the tail of the list.
+    pub fn push(&mut self, waker: &Waker, data: T) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entries.push_back(Entry {
+            id,
+            waker: waker.clone(),
+            data,
+        });
+        id
+    }
+
+    /// Refresh the waker of a parked task. Returns `false` if it's no longer
+    /// in the list.
+    pub fn update(&mut self, id: u64, waker: &Waker) -> bool {
+        match self.get_mut(id) {
+            Some(entry) => {
+                entry.set_waker(waker);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut Entry<T>> {
+        self.entries.iter_mut().find(|entry| entry.id == id)
+    }
+
+    pub fn remove(&mut self, id: u64) -> Option<T> {
+        let index = self.entries.iter().position(|entry| entry.id == id)?;
+        self.entries.remove(index).map(|entry| entry.data)
+    }
+
+    /// Unpark the task at the head of the list, and return its waker to be
+    /// woken after the lock is released.
+    pub fn pop_front(&mut self) -> Option<(Waker, T)> {
+        self.entries
+            .pop_front()
+            .map(|entry| (entry.waker, entry.data))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Entry<T>> {
+        self.entries.iter_mut()
+    }
+
+    /// Unpark all tasks, and return their wakers to be woken after the lock is
+    /// released.
+    pub fn take_all(&mut self) -> Wakers {
+        Wakers(self.entries.drain(..).map(|entry| entry.waker).collect())
+    }
+
+    /// Unpark the tasks whose data matches `f`, and return their wakers to be
+    /// woken after the lock is released.
+    pub fn take_if(&mut self, mut f: impl FnMut(&T) -> bool) -> Wakers {
+        let mut wakers = Wakers::default();
+        self.entries.retain(|entry| {
+            let take = f(&entry.data);
+            if take {
+                wakers.0.push(entry.waker.clone());
+            }
+            !take
+        });
+        wakers
+    }
+}
+
+impl<T> Entry<T> {
+    pub fn waker(&self) -> &Waker {
+        &self.waker
+    }
+
+    pub fn set_waker(&mut self, waker: &Waker) {
+        if !self.waker.will_wake(waker) {
+            self.waker.clone_from(waker);
+        }
+    }
+}
+
+/// Wakers collected under a lock, woken on drop.
+#[must_use = "wakers are woken on drop"]
+#[derive(Default)]
+pub(crate) struct Wakers(pub Vec<Waker>);
+
+impl Drop for Wakers {
+    fn drop(&mut self) {
+        self.0.drain(..).for_each(Waker::wake);
+    }
+}
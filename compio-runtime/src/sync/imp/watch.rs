@@ -0,0 +1,296 @@
+//! A single-producer, multi-consumer channel keeping only the latest value.
+//!
+//! Receivers see the current value with [`Receiver::borrow`], and wait for a
+//! new one with [`Receiver::changed`]. Intermediate values may be skipped.
+
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    future::poll_fn,
+    mem,
+    ops::Deref,
+    task::{Context, Poll, ready},
+};
+
+use super::{Lock, LockGuard, Shared, WaitList};
+use crate::coop;
+
+struct Inner<T> {
+    value: Lock<T>,
+    state: Lock<State>,
+}
+
+struct State {
+    version: u64,
+    closed: bool,
+    receivers: usize,
+    waiters: WaitList,
+}
+
+impl<T> Inner<T> {
+    fn version(&self) -> u64 {
+        self.state.lock().version
+    }
+}
+
+/// Create a watch channel with the initial value.
+pub fn channel<T>(init: T) -> (Sender<T>, Receiver<T>) {
+    let inner = Shared::new(Inner {
+        value: Lock::new(init),
+        state: Lock::new(State {
+            version: 0,
+            closed: false,
+            receivers: 1,
+            waiters: WaitList::new(),
+        }),
+    });
+    (
+        Sender {
+            inner: inner.clone(),
+        },
+        Receiver {
+            inner,
+            seen: 0,
+            id: None,
+        },
+    )
+}
+
+/// A reference to the value in the channel.
+///
+/// The channel is locked while it's alive, so it should be dropped as soon as
+/// possible, and never held across an `.await` point.
+pub struct Ref<'a, T> {
+    guard: LockGuard<'a, T>,
+}
+
+impl<T> Deref for Ref<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T: Debug> Debug for Ref<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+/// The sending half of a watch channel.
+pub struct Sender<T> {
+    inner: Shared<Inner<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Replace the value and notify the receivers.
+    ///
+    /// Returns the value back if there's no receiver.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        if self.receiver_count() == 0 {
+            return Err(SendError(value));
+        }
+        self.send_replace(value);
+        Ok(())
+    }
+
+    /// Replace the value and notify the receivers, even if there's none.
+    /// Returns the previous value.
+    pub fn send_replace(&self, value: T) -> T {
+        let mut value = value;
+        self.send_modify(|old| mem::swap(old, &mut value));
+        value
+    }
+
+    /// Modify the value in place and notify the receivers, even if there's
+    /// none.
+    pub fn send_modify(&self, f: impl FnOnce(&mut T)) {
+        f(&mut self.inner.value.lock());
+        let _wakers = {
+            let mut state = self.inner.state.lock();
+            state.version += 1;
+            state.waiters.take_all()
+        };
+    }
+
+    /// Borrow the current value.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref {
+            guard: self.inner.value.lock(),
+        }
+    }
+
+    /// Create a new receiver, which has seen the current value.
+    pub fn subscribe(&self) -> Receiver<T> {
+        let mut state = self.inner.state.lock();
+        state.receivers += 1;
+        Receiver {
+            inner: self.inner.clone(),
+            seen: state.version,
+            id: None,
+        }
+    }
+
+    /// The number of receivers.
+    pub fn receiver_count(&self) -> usize {
+        self.inner.state.lock().receivers
+    }
+
+    /// Whether all receivers are dropped.
+    pub fn is_closed(&self) -> bool {
+        self.receiver_count() == 0
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let _wakers = {
+            let mut state = self.inner.state.lock();
+            state.closed = true;
+            state.waiters.take_all()
+        };
+    }
+}
+
+impl<T: Debug> Debug for Sender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sender")
+            .field("value", &*self.borrow())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The receiving half of a watch channel.
+pub struct Receiver<T> {
+    inner: Shared<Inner<T>>,
+    /// Version of the value last seen.
+    seen: u64,
+    id: Option<u64>,
+}
+
+impl<T> Receiver<T> {
+    /// Borrow the current value, without marking it as seen.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref {
+            guard: self.inner.value.lock(),
+        }
+    }
+
+    /// Borrow the current value, and mark it as seen.
+    pub fn borrow_and_update(&mut self) -> Ref<'_, T> {
+        self.seen = self.inner.version();
+        Ref {
+            guard: self.inner.value.lock(),
+        }
+    }
+
+    /// Whether there's a value not seen yet.
+    ///
+    /// Returns an error if the sender is dropped.
+    pub fn has_changed(&self) -> Result<bool, RecvError> {
+        let state = self.inner.state.lock();
+        if state.closed {
+            Err(RecvError(()))
+        } else {
+            Ok(state.version != self.seen)
+        }
+    }
+
+    /// Mark the current value as seen.
+    pub fn mark_unchanged(&mut self) {
+        self.seen = self.inner.version();
+    }
+
+    /// Wait for a value not seen yet, and mark it as seen.
+    ///
+    /// Returns an error if the sender is dropped.
+    pub async fn changed(&mut self) -> Result<(), RecvError> {
+        poll_fn(|cx| self.poll_changed(cx)).await
+    }
+
+    /// Poll for a value not seen yet, and mark it as seen.
+    pub fn poll_changed(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), RecvError>> {
+        let mut coop = ready!(coop::poll_proceed(cx));
+        let Self { inner, seen, id } = self;
+        let mut state = inner.state.lock();
+        if state.version != *seen {
+            *seen = state.version;
+            coop.made_progress();
+            return Poll::Ready(Ok(()));
+        }
+        if state.closed {
+            coop.made_progress();
+            return Poll::Ready(Err(RecvError(())));
+        }
+        match *id {
+            Some(id) if state.waiters.update(id, cx.waker()) => {}
+            _ => *id = Some(state.waiters.push(cx.waker(), ())),
+        }
+        Poll::Pending
+    }
+
+    /// Whether both receivers receive from the same channel.
+    pub fn same_channel(&self, other: &Self) -> bool {
+        Shared::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.inner.state.lock().receivers += 1;
+        Self {
+            inner: self.inner.clone(),
+            seen: self.seen,
+            id: None,
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.inner.state.lock();
+        state.receivers -= 1;
+        if let Some(id) = self.id {
+            state.waiters.remove(id);
+        }
+    }
+}
+
+impl<T: Debug> Debug for Receiver<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Receiver")
+            .field("value", &*self.borrow())
+            .finish_non_exhaustive()
+    }
+}
+
+/// There's no receiver. The value is returned back.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> Debug for SendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SendError").finish_non_exhaustive()
+    }
+}
+
+impl<T> Display for SendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("channel closed")
+    }
+}
+
+impl<T> Error for SendError<T> {}
+
+/// The sender is dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError(());
+
+impl Display for RecvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("channel closed")
+    }
+}
+
+impl Error for RecvError {}
@@ -0,0 +1,19 @@
+//! Single-threaded versions of the primitives in [`compio_runtime::sync`].
+//!
+//! They are `!Send` or `!Sync`, and can only be shared between tasks on the
+//! same runtime. Shared states are guarded by [`RefCell`] and shared by
+//! [`Rc`], so no atomic operation is involved.
+//!
+//! [`compio_runtime::sync`]: crate::sync
+//! [`RefCell`]: std::cell::RefCell
+//! [`Rc`]: std::rc::Rc
+
+use synchrony::unsync as flavor;
+
+// The implementation is shared with the `Send` flavor, and compiled again
+// against `synchrony::unsync`.
+#[allow(clippy::duplicate_mod)]
+#[path = "imp/mod.rs"]
+mod imp;
+
+pub use imp::*;
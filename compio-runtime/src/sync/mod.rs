@@ -0,0 +1,49 @@
+//! Asynchronous synchronization primitives.
+//!
+//! The primitives come in two flavors with the same API:
+//!
+//! - The ones in this module are [`Send`] and [`Sync`], for sharing between
+//!   tasks on different runtimes. A task woken from another thread is
+//!   scheduled through the waker of its own runtime, so no runtime is blocked
+//!   while waiting.
+//! - The ones in [`local`] are `!Send`, for tasks on the same runtime. They
+//!   avoid atomics and locking altogether.
+//!
+//! All primitives are fair: waiting tasks are served in the order they start
+//! waiting. Waiting for them spends the [`coop`] budget of the task, so a loop
+//! that never has to wait still yields now and then.
+//!
+//! [`coop`]: crate::coop
+//!
+//! ```
+//! use compio_runtime::sync::{Mutex, mpsc};
+//!
+//! # compio_runtime::Runtime::new().unwrap().block_on(async {
+//! let (tx, mut rx) = mpsc::channel(16);
+//! let total = std::sync::Arc::new(Mutex::new(0));
+//!
+//! for i in 0..4 {
+//!     let tx = tx.clone();
+//!     compio_runtime::spawn(async move { tx.send(i).await.unwrap() }).detach();
+//! }
+//! drop(tx);
+//!
+//! let mut buf = Vec::new();
+//! while rx.recv_many(&mut buf, 8).await > 0 {}
+//! *total.lock().await += buf.iter().sum::<i32>();
+//! assert_eq!(*total.lock().await, 6);
+//! # })
+//! ```
+
+use synchrony::sync as flavor;
+
+#[allow(clippy::duplicate_mod)]
+mod imp;
+pub mod local;
+
+pub use imp::*;
+
+// SAFETY: The value is only accessed through guards, and the semaphore makes
+// sure a write guard is exclusive.
+unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for RwLock<T> {}
@@ -0,0 +1,82 @@
+use std::{cell::Cell, rc::Rc};
+
+use compio_runtime::{ResumeUnwind, Runtime, sync::local::mpsc};
+
+/// A receive loop that never waits, because a message is always queued.
+async fn busy_recv(stop: Rc<Cell<bool>>) -> usize {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut rounds = 0;
+    while !stop.get() {
+        tx.send(()).unwrap();
+        rx.recv().await.unwrap();
+        rounds += 1;
+        assert!(rounds < 1_000_000, "the other task is starved");
+    }
+    rounds
+}
+
+#[test]
+fn ready_loop_yields() {
+    Runtime::builder()
+        .coop_budget(Some(16))
+        .build()
+        .unwrap()
+        .block_on(async {
+            let stop = Rc::new(Cell::new(false));
+            let busy = compio_runtime::spawn(busy_recv(stop.clone()));
+            compio_runtime::spawn(async move { stop.set(true) })
+                .await
+                .resume_unwind()
+                .unwrap();
+            let rounds = busy.await.resume_unwind().unwrap();
+            assert!(rounds >= 16);
+        })
+}
+
+#[test]
+fn block_on_is_budgeted() {
+    Runtime::builder()
+        .coop_budget(Some(4))
+        .build()
+        .unwrap()
+        .block_on(async {
+            let stop = Rc::new(Cell::new(false));
+            let task = compio_runtime::spawn({
+                let stop = stop.clone();
+                async move { stop.set(true) }
+            });
+            busy_recv(stop).await;
+            task.await.resume_unwind().unwrap();
+        })
+}
+
+#[test]
+fn unconstrained() {
+    Runtime::builder()
+        .coop_budget(None)
+        .build()
+        .unwrap()
+        .block_on(async {
+            assert!(compio_runtime::coop::has_budget_remaining());
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            for i in 0..1000 {
+                tx.send(i).unwrap();
+                assert_eq!(rx.recv().await, Some(i));
+            }
+        })
+}
+
+#[test]
+fn yield_now() {
+    Runtime::new().unwrap().block_on(async {
+        let flag = Rc::new(Cell::new(false));
+        let task = compio_runtime::spawn({
+            let flag = flag.clone();
+            async move { flag.set(true) }
+        });
+        while !flag.get() {
+            compio_runtime::yield_now().await;
+        }
+        task.await.resume_unwind().unwrap();
+    })
+}
@@ -0,0 +1,92 @@
+use std::{cell::Cell, future::pending, rc::Rc, time::Duration};
+
+use compio_runtime::{JoinError, Runtime};
+
+struct DropFlag(Rc<Cell<bool>>);
+
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.0.set(true);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn cancels_ops() {
+    use compio_buf::BufResult;
+    use compio_driver::{
+        ErrorExt, ProactorBuilder,
+        op::{Recv, RecvFlags},
+    };
+
+    let runtime = Runtime::builder()
+        .with_proactor({
+            let mut builder = ProactorBuilder::new();
+            builder.track_in_flight(true);
+            builder
+        })
+        .build()
+        .unwrap();
+    let (socket, _peer) = std::os::unix::net::UnixStream::pair().unwrap();
+    let cancelled = Rc::new(Cell::new(None));
+    runtime.block_on({
+        let cancelled = cancelled.clone();
+        async move {
+            compio_runtime::spawn(async move {
+                let op = Recv::new(socket, Vec::with_capacity(16), RecvFlags::empty());
+                let BufResult(res, _) = Runtime::with_current(|r| r.submit(op)).await;
+                cancelled.set(Some(res.is_cancelled()));
+            })
+            .detach();
+            compio_runtime::yield_now().await;
+        }
+    });
+
+    let report = runtime.shutdown(Duration::from_secs(10));
+    assert!(report.is_clean(), "{report:?}");
+    assert_eq!(cancelled.get(), Some(true));
+}
+
+#[test]
+fn drops_stuck_tasks() {
+    let runtime = Runtime::new().unwrap();
+    let dropped = Rc::new(Cell::new(false));
+    runtime.block_on({
+        let flag = DropFlag(dropped.clone());
+        async move {
+            compio_runtime::spawn(async move {
+                let _flag = flag;
+                pending::<()>().await
+            })
+            .detach();
+        }
+    });
+
+    let report = runtime.shutdown(Duration::from_millis(10));
+    assert_eq!(report.tasks, 1);
+    assert_eq!(report.ops, 0);
+    assert!(dropped.get());
+}
+
+#[test]
+fn rejects_spawns() {
+    let runtime = Runtime::new().unwrap();
+    assert!(runtime.shutdown(Duration::ZERO).is_clean());
+
+    let ran = Rc::new(Cell::new(false));
+    let res = runtime.block_on({
+        let ran = ran.clone();
+        async move { compio_runtime::spawn(async move { ran.set(true) }).await }
+    });
+    assert!(matches!(res, Err(JoinError::Cancelled)));
+    assert!(!ran.get());
+}
+
+#[test]
+#[should_panic = "cannot shut down a runtime from within itself"]
+fn within_itself() {
+    let runtime = Runtime::new().unwrap();
+    runtime.block_on(async {
+        Runtime::with_current(|r| r.shutdown(Duration::ZERO));
+    });
+}
@@ -0,0 +1,339 @@
+use compio_runtime::Runtime;
+
+fn block_on<F: Future>(f: F) -> F::Output {
+    Runtime::new().unwrap().block_on(f)
+}
+
+macro_rules! flavor_tests {
+    ($name:ident, $flavor:path, $shared:ty) => {
+        mod $name {
+            use std::{cell::RefCell, rc::Rc};
+
+            use compio_runtime::{ResumeUnwind, spawn};
+            use $flavor::*;
+
+            use super::block_on;
+
+            type Shared<T> = $shared;
+
+            async fn yield_now() {
+                let mut yielded = false;
+                std::future::poll_fn(|cx| {
+                    if yielded {
+                        std::task::Poll::Ready(())
+                    } else {
+                        yielded = true;
+                        cx.waker().wake_by_ref();
+                        std::task::Poll::Pending
+                    }
+                })
+                .await
+            }
+
+            #[test]
+            fn mutex() {
+                block_on(async {
+                    let lock = Shared::new(Mutex::new(0));
+                    let tasks = (0..8)
+                        .map(|_| {
+                            let lock = lock.clone();
+                            spawn(async move {
+                                for _ in 0..10 {
+                                    let mut guard = lock.lock().await;
+                                    let value = *guard;
+                                    yield_now().await;
+                                    *guard = value + 1;
+                                }
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    for task in tasks {
+                        task.await.resume_unwind();
+                    }
+                    assert_eq!(*lock.lock().await, 80);
+                    assert!(lock.try_lock().is_ok());
+                    let _guard = lock.try_lock().unwrap();
+                    assert!(lock.try_lock().is_err());
+                })
+            }
+
+            #[test]
+            fn rwlock() {
+                block_on(async {
+                    let lock = RwLock::new(1);
+                    let r1 = lock.read().await;
+                    let r2 = lock.read().await;
+                    assert_eq!(*r1 + *r2, 2);
+                    assert!(lock.try_write().is_err());
+                    drop((r1, r2));
+
+                    let mut w = lock.write().await;
+                    *w = 2;
+                    assert!(lock.try_read().is_err());
+                    let r = w.downgrade();
+                    assert_eq!(*r, 2);
+                    assert!(lock.try_read().is_ok());
+                    assert!(lock.try_write().is_err());
+                })
+            }
+
+            #[test]
+            fn semaphore() {
+                block_on(async {
+                    let sem = Shared::new(Semaphore::new(2));
+                    let order = Rc::new(RefCell::new(vec![]));
+                    let held = sem.clone().acquire_many_owned(2).await.unwrap();
+
+                    // The large waiter comes first and is served first.
+                    let big = spawn({
+                        let (sem, order) = (sem.clone(), order.clone());
+                        async move {
+                            let _p = sem.acquire_many(2).await.unwrap();
+                            order.borrow_mut().push("big");
+                        }
+                    });
+                    yield_now().await;
+                    let small = spawn({
+                        let (sem, order) = (sem.clone(), order.clone());
+                        async move {
+                            let _p = sem.acquire().await.unwrap();
+                            order.borrow_mut().push("small");
+                        }
+                    });
+                    yield_now().await;
+                    assert!(sem.try_acquire().is_err());
+
+                    drop(held);
+                    big.await.resume_unwind();
+                    small.await.resume_unwind();
+                    assert_eq!(*order.borrow(), ["big", "small"]);
+                    assert_eq!(sem.available_permits(), 2);
+
+                    let _p = sem.acquire_many(2).await.unwrap();
+                    let waiter = spawn({
+                        let sem = sem.clone();
+                        async move { sem.acquire().await.is_err() }
+                    });
+                    yield_now().await;
+                    sem.close();
+                    assert!(waiter.await.resume_unwind().unwrap());
+                    assert_eq!(sem.try_acquire().unwrap_err(), TryAcquireError::Closed);
+
+                    // Permits granted before closing are still acquired, and
+                    // released afterwards.
+                    let sem = Shared::new(Semaphore::new(0));
+                    let waiter = spawn({
+                        let sem = sem.clone();
+                        async move { sem.acquire().await.is_ok() }
+                    });
+                    yield_now().await;
+                    sem.add_permits(1);
+                    sem.close();
+                    assert!(waiter.await.resume_unwind().unwrap());
+                    assert_eq!(sem.available_permits(), 1);
+                })
+            }
+
+            #[test]
+            fn notify() {
+                block_on(async {
+                    let notify = Shared::new(Notify::new());
+
+                    // A permit is stored without waiters.
+                    notify.notify_one();
+                    notify.notified().await;
+
+                    let waiters = (0..3)
+                        .map(|_| {
+                            let notify = notify.clone();
+                            spawn(async move { notify.notified().await })
+                        })
+                        .collect::<Vec<_>>();
+                    yield_now().await;
+                    notify.notify_waiters();
+                    for waiter in waiters {
+                        waiter.await.resume_unwind();
+                    }
+
+                    // `notify_waiters` stores nothing.
+                    let notified = notify.notified();
+                    notify.notify_waiters();
+                    notified.await;
+                    let mut notified = std::pin::pin!(notify.notified());
+                    assert!(futures_util::poll!(notified.as_mut()).is_pending());
+                    notify.notify_one();
+                    notified.await;
+                })
+            }
+
+            #[test]
+            fn mpsc_bounded() {
+                block_on(async {
+                    let (tx, mut rx) = mpsc::channel(2);
+                    tx.send(1).await.unwrap();
+                    tx.try_send(2).unwrap();
+                    assert!(matches!(tx.try_send(3), Err(mpsc::TrySendError::Full(3))));
+                    assert_eq!(tx.capacity(), 0);
+
+                    let sender = spawn({
+                        let tx = tx.clone();
+                        async move {
+                            for i in 3..8 {
+                                tx.send(i).await.unwrap();
+                            }
+                        }
+                    });
+                    drop(tx);
+
+                    let mut buf = vec![];
+                    while rx.recv_many(&mut buf, 4).await > 0 {}
+                    assert_eq!(buf, (1..8).collect::<Vec<_>>());
+                    sender.await.resume_unwind();
+                    assert_eq!(rx.try_recv(), Err(mpsc::TryRecvError::Disconnected));
+
+                    let (tx, mut rx) = mpsc::channel(1);
+                    rx.close();
+                    assert_eq!(tx.send(1).await.unwrap_err().0, 1);
+                    assert!(tx.is_closed());
+                    assert_eq!(rx.recv().await, None);
+                })
+            }
+
+            #[test]
+            fn mpsc_unbounded() {
+                block_on(async {
+                    let (tx, mut rx) = mpsc::unbounded_channel();
+                    for i in 0..100 {
+                        tx.send(i).unwrap();
+                    }
+                    assert_eq!(rx.len(), 100);
+                    assert_eq!(rx.recv().await, Some(0));
+                    let mut buf = vec![];
+                    assert_eq!(rx.recv_many(&mut buf, 1000).await, 99);
+                    drop(tx);
+                    assert_eq!(rx.recv().await, None);
+                })
+            }
+
+            #[test]
+            fn oneshot() {
+                block_on(async {
+                    let (tx, rx) = oneshot::channel();
+                    let task = spawn(rx);
+                    yield_now().await;
+                    tx.send(42).unwrap();
+                    assert_eq!(task.await.resume_unwind().unwrap(), Ok(42));
+
+                    let (tx, rx) = oneshot::channel::<()>();
+                    drop(tx);
+                    assert!(rx.await.is_err());
+
+                    let (mut tx, rx) = oneshot::channel::<()>();
+                    drop(rx);
+                    tx.closed().await;
+                    assert_eq!(tx.send(()), Err(()));
+                })
+            }
+
+            #[test]
+            fn broadcast() {
+                block_on(async {
+                    let (tx, mut rx1) = broadcast::channel(2);
+                    let mut rx2 = tx.subscribe();
+                    assert_eq!(tx.send(1).unwrap(), 2);
+                    assert_eq!(rx1.recv().await, Ok(1));
+                    tx.send(2).unwrap();
+                    tx.send(3).unwrap();
+                    assert_eq!(rx1.recv().await, Ok(2));
+                    assert_eq!(rx2.recv().await, Err(broadcast::RecvError::Lagged(1)));
+                    assert_eq!(rx2.recv().await, Ok(2));
+
+                    let task = spawn(async move { rx1.recv().await });
+                    yield_now().await;
+                    drop(tx);
+                    assert_eq!(task.await.resume_unwind().unwrap(), Ok(3));
+                    assert_eq!(rx2.recv().await, Ok(3));
+                    assert_eq!(rx2.recv().await, Err(broadcast::RecvError::Closed));
+                })
+            }
+
+            #[test]
+            fn watch() {
+                block_on(async {
+                    let (tx, mut rx) = watch::channel(0);
+                    assert!(!rx.has_changed().unwrap());
+                    let task = spawn({
+                        let mut rx = rx.clone();
+                        async move {
+                            rx.changed().await.unwrap();
+                            *rx.borrow_and_update()
+                        }
+                    });
+                    yield_now().await;
+                    tx.send(1).unwrap();
+                    tx.send_modify(|v| *v += 1);
+                    assert_eq!(task.await.resume_unwind().unwrap(), 2);
+                    assert!(rx.has_changed().unwrap());
+                    assert_eq!(tx.send_replace(3), 2);
+                    rx.changed().await.unwrap();
+                    assert_eq!(*rx.borrow(), 3);
+                    drop(tx);
+                    assert!(rx.changed().await.is_err());
+                })
+            }
+        }
+    };
+}
+
+flavor_tests!(send, compio_runtime::sync, std::sync::Arc<T>);
+flavor_tests!(local, compio_runtime::sync::local, std::rc::Rc<T>);
+
+#[test]
+fn cross_runtime() {
+    use std::sync::Arc;
+
+    use compio_runtime::sync::{Notify, mpsc};
+
+    const N: usize = 1000;
+
+    let (tx, mut rx) = mpsc::channel(8);
+    let notify = Arc::new(Notify::new());
+    let producer = std::thread::spawn({
+        let notify = notify.clone();
+        move || {
+            block_on(async move {
+                notify.notified().await;
+                for i in 0..N {
+                    tx.send(i).await.unwrap();
+                }
+            })
+        }
+    });
+    let received = block_on(async move {
+        notify.notify_one();
+        let mut buf = vec![];
+        while rx.recv_many(&mut buf, 16).await > 0 {}
+        buf
+    });
+    producer.join().unwrap();
+    assert_eq!(received, (0..N).collect::<Vec<_>>());
+}
+
+#[test]
+fn send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    use compio_runtime::sync::*;
+
+    assert_send_sync::<Mutex<i32>>();
+    assert_send_sync::<MutexGuard<'_, i32>>();
+    assert_send_sync::<RwLock<i32>>();
+    assert_send_sync::<Semaphore>();
+    assert_send_sync::<Notify>();
+    assert_send_sync::<mpsc::Sender<i32>>();
+    assert_send_sync::<mpsc::Receiver<i32>>();
+    assert_send_sync::<oneshot::Sender<i32>>();
+    assert_send_sync::<oneshot::Receiver<i32>>();
+    assert_send_sync::<broadcast::Sender<i32>>();
+    assert_send_sync::<watch::Receiver<i32>>();
+}
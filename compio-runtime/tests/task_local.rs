@@ -0,0 +1,25 @@
+use compio_runtime::ResumeUnwind;
+
+compio_runtime::task_local! {
+    static REQUEST_ID: u64;
+}
+
+#[test]
+fn spawn_blocking_inherit() {
+    compio_runtime::Runtime::new()
+        .unwrap()
+        .block_on(REQUEST_ID.scope(42, async {
+            let id =
+                compio_runtime::spawn_blocking(REQUEST_ID.inherit(|| REQUEST_ID.try_get().ok()))
+                    .await
+                    .resume_unwind()
+                    .unwrap();
+            assert_eq!(id, Some(42));
+
+            let id = compio_runtime::spawn_blocking(|| REQUEST_ID.try_get().ok())
+                .await
+                .resume_unwind()
+                .unwrap();
+            assert_eq!(id, None);
+        }))
+}